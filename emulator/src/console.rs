@@ -0,0 +1,194 @@
+// A byte stream standing in for the Arduino's USART: stdin and stdout, or a pseudo-terminal
+use std::cell::Cell;
+use std::convert::Infallible;
+use std::io::{self, Read, StdoutLock, Write};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+// Ctrl+] quits, as in miniterm. Ctrl+C and Ctrl+D also quit, since the terminal no longer
+// turns them into a signal or end of file.
+const QUIT_KEYS: [u8; 3] = [0x1d, 0x03, 0x04];
+const DELETE: u8 = 0x7f;
+const BACKSPACE: u8 = 0x08;
+
+// The input is read by a thread of its own, so `read` never blocks and says WouldBlock while
+// nothing has arrived, as the USART does. `sync` relies on that to time the host's reply and to
+// give up on one that never comes.
+pub struct Console<'a, W> {
+    input: Receiver<io::Result<u8>>,
+    pending: Arc<Pending>,
+    output: W,
+    // Set once the input is closed or a quit key is pressed
+    hangup: &'a Cell<bool>,
+}
+
+// How much the reader thread has queued, so the emulator can sleep until there is a byte to
+// read instead of spinning on WouldBlock
+#[derive(Default)]
+pub struct Pending {
+    count: Mutex<usize>,
+    changed: Condvar,
+}
+
+impl Pending {
+    // Block until a byte, or the end of the input, is waiting to be read
+    pub fn wait(&self) {
+        let mut count = self.count.lock().unwrap();
+        while *count == 0 {
+            count = self.changed.wait(count).unwrap();
+        }
+    }
+
+    fn add(&self) {
+        *self.count.lock().unwrap() += 1;
+        self.changed.notify_all();
+    }
+
+    fn take(&self) {
+        let mut count = self.count.lock().unwrap();
+        *count = count.saturating_sub(1);
+    }
+}
+
+impl<'a, W: Write> Console<'a, W> {
+    pub fn new<R: Read + Send + 'static>(input: R, output: W, hangup: &'a Cell<bool>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let pending = Arc::new(Pending::default());
+        spawn_reader(input, sender, pending.clone());
+
+        Self { input: receiver, pending, output, hangup }
+    }
+
+    pub fn pending(&self) -> Arc<Pending> {
+        self.pending.clone()
+    }
+}
+
+impl<'a> Console<'a, StdoutLock<'static>> {
+    pub fn stdio(hangup: &'a Cell<bool>) -> Self {
+        Self::new(io::stdin(), io::stdout().lock(), hangup)
+    }
+}
+
+// Pass the input on a byte at a time until it ends, fails, or the console is dropped
+fn spawn_reader<R: Read + Send + 'static>(mut input: R, sender: Sender<io::Result<u8>>, pending: Arc<Pending>) {
+    thread::spawn(move || {
+        let mut byte = [0_u8; 1];
+        loop {
+            let result = match input.read(&mut byte) {
+                Ok(0) => break,
+                Ok(_) => Ok(byte[0]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            // Counted before it is sent, so the count is never short of what is queued and a
+            // woken emulator at worst polls until the byte lands
+            let failed = result.is_err();
+            pending.add();
+            if sender.send(result).is_err() {
+                return;
+            }
+            if failed {
+                break;
+            }
+        }
+
+        // Wake the emulator to find the input closed
+        drop(sender);
+        pending.add();
+    });
+}
+
+impl<W> embedded_hal::serial::Read<u8> for Console<'_, W> {
+    type Error = io::Error;
+
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let byte = match self.input.try_recv() {
+            Ok(result) => {
+                self.pending.take();
+                result
+            },
+            Err(TryRecvError::Empty) => return Err(nb::Error::WouldBlock),
+            Err(TryRecvError::Disconnected) => Err(io::ErrorKind::UnexpectedEof.into()),
+        };
+
+        match byte {
+            Ok(byte) if QUIT_KEYS.contains(&byte) => {
+                self.hangup.set(true);
+                Err(nb::Error::Other(io::ErrorKind::Interrupted.into()))
+            },
+            // Terminals send DEL for the backspace key, miniterm on Windows sends BS
+            Ok(DELETE) => Ok(BACKSPACE),
+            Ok(byte) => Ok(byte),
+            Err(e) => {
+                self.hangup.set(true);
+                Err(nb::Error::Other(e))
+            },
+        }
+    }
+}
+
+impl<W: Write> ufmt::uWrite for Console<'_, W> {
+    type Error = Infallible;
+
+    // Flush every write, since the prompt does not end in a newline. Output that cannot be
+    // written is dropped, the same as on a serial line with nothing listening.
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        let _ = self.output.write_all(s.as_bytes()).and_then(|_| self.output.flush());
+        Ok(())
+    }
+}
+
+// Puts an interactive terminal into character-at-a-time mode without local echo, since the
+// console echoes input itself. The previous settings are restored when this is dropped.
+pub struct RawMode {
+    #[cfg(unix)]
+    saved: Option<String>,
+}
+
+impl RawMode {
+    #[cfg(unix)]
+    pub fn enter() -> Self {
+        use std::io::IsTerminal;
+
+        if !io::stdin().is_terminal() {
+            return Self { saved: None };
+        }
+
+        let saved = stty(&["-g"]).map(|settings| settings.trim().to_string());
+        if saved.is_some() {
+            stty(&["-icanon", "-echo", "-isig", "min", "1"]);
+        }
+
+        Self { saved }
+    }
+
+    // Windows consoles are left as they are
+    #[cfg(not(unix))]
+    pub fn enter() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            stty(&[saved]);
+        }
+    }
+}
+
+// Run stty on the terminal behind stdin
+// Return: stty's output, or None if it failed
+#[cfg(unix)]
+fn stty(args: &[&str]) -> Option<String> {
+    use std::process::{Command, Stdio};
+
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output().ok()?;
+    match output.status.success() {
+        true => String::from_utf8(output.stdout).ok(),
+        false => None,
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod reading {
+    use std::cell::Cell;
+    use std::io::{self, Read};
+    use std::sync::mpsc::{self, Receiver};
+
+    use embedded_hal::serial::Read as SerialRead;
+
+    use crate::console::Console;
+
+    // Input that only arrives when the test sends it, like a person typing
+    struct Typing(Receiver<u8>);
+
+    impl Read for Typing {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            match self.0.recv() {
+                Ok(byte) => {
+                    buffer[0] = byte;
+                    Ok(1)
+                },
+                Err(_) => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn would_block_until_input_arrives() {
+        let (keys, typed) = mpsc::channel();
+        let hangup = Cell::new(false);
+        let mut console = Console::new(Typing(typed), io::sink(), &hangup);
+        let pending = console.pending();
+
+        assert!(matches!(console.read(), Err(nb::Error::WouldBlock)));
+
+        keys.send(b'a').unwrap();
+        keys.send(0x7f).unwrap();
+        pending.wait();
+        assert!(matches!(nb::block!(console.read()), Ok(b'a')));
+        assert!(matches!(nb::block!(console.read()), Ok(0x08)));
+        assert!(matches!(console.read(), Err(nb::Error::WouldBlock)));
+        assert!(!hangup.get());
+
+        // The end of the input wakes the waiter and hangs up
+        drop(keys);
+        pending.wait();
+        assert!(nb::block!(console.read()).is_err());
+        assert!(hangup.get());
+    }
+
+    #[test]
+    fn quit_keys_hang_up() {
+        let (keys, typed) = mpsc::channel();
+        let hangup = Cell::new(false);
+        let mut console = Console::new(Typing(typed), io::sink(), &hangup);
+
+        keys.send(0x1d).unwrap();
+        assert!(nb::block!(console.read()).is_err());
+        assert!(hangup.get());
+    }
+}
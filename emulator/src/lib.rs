@@ -0,0 +1,5 @@
+// The pieces of the emulator, so other host tools can run the console in-process
+pub mod console;
+pub mod module;
+
+mod console_tests;
@@ -0,0 +1,94 @@
+// Runs the OTP console on stdin and stdout, with a simulated RTC module in place of the
+// Arduino's. The module's EEPROM is kept in an image file between runs.
+use std::cell::Cell;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::process::ExitCode;
+
+use otp_core::clock::Clock;
+use otp_core::datetime::Datetime;
+use otp_core::sim::{Sim, EEPROM_SIZE};
+use otp_core::tty::TTY;
+use otp_emulator::console::{Console, RawMode};
+use otp_emulator::module::{self, Module};
+
+mod options;
+
+mod options_tests;
+
+use options::{Options, StartTime};
+
+// Room temperature in quarter degrees Celsius, as in the README session
+const TEMPERATURE: i16 = 25 * 4 + 3;
+
+fn main() -> ExitCode {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n{}", message, options::USAGE);
+            return ExitCode::from(2);
+        },
+    };
+
+    let image = match read_image(&options.eeprom) {
+        Ok(image) => image,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let sim = Sim::new();
+    sim.load_eeprom(&image);
+    sim.set_temperature(TEMPERATURE);
+
+    // The DS3231 converts the temperature as soon as it powers up
+    let mut module = Module::new(&sim);
+    let _ = module.update_temperature();
+    let start = match options.start_time {
+        StartTime::System => Some(module::system_timestamp()),
+        StartTime::At(timestamp) => Some(timestamp),
+        StartTime::PowerOn => None,
+    };
+    if let Some(timestamp) = start {
+        let _ = module.set(Datetime::from_timestamp(timestamp));
+    }
+
+    let _raw_mode = RawMode::enter();
+    if io::stdin().is_terminal() {
+        eprintln!("--- Quit: Ctrl+] ---");
+    }
+
+    let hangup = Cell::new(false);
+    let console = Console::stdio(&hangup);
+    let pending = console.pending();
+    let mut tty = TTY::new(console, module);
+    let mut saved = image;
+    while !hangup.get() {
+        pending.wait();
+        tty.wait_for_byte();
+
+        // Write the image back as soon as a command changes it, like the real EEPROM
+        let current = sim.eeprom();
+        if current != saved {
+            if let Err(e) = fs::write(&options.eeprom, current) {
+                eprintln!("Error saving EEPROM image {} - {}", options.eeprom, e);
+            }
+            saved = current;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+// Load the EEPROM image, or start with an erased EEPROM if there is no image yet
+fn read_image(path: &str) -> Result<[u8; EEPROM_SIZE], String> {
+    match fs::read(path) {
+        Ok(bytes) => bytes.try_into().map_err(|bytes: Vec<u8>| {
+            format!("EEPROM image {} is {} bytes, expected {}", path, bytes.len(), EEPROM_SIZE)
+        }),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok([0xff; EEPROM_SIZE]),
+        Err(e) => Err(format!("Error reading EEPROM image {} - {}", path, e)),
+    }
+}
@@ -0,0 +1,126 @@
+// The RTC module behind the emulated console: the simulated DS3231 and AT24C32 from
+// `otp_core::sim`, kept in step with the host's wall clock so the time moves while the
+// console waits for input.
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use otp_core::alarm::{self, Alarm, Schedule};
+use otp_core::clock::Clock;
+use otp_core::datetime::Datetime;
+use otp_core::eeprom::{Storage, PAGE_SIZE};
+use otp_core::rtc::{Rtc, RtcError};
+use otp_core::sim::{Sim, SimBus, SimDelay, SimError};
+use otp_core::sqw::{self, Rate};
+
+pub struct Module<'a> {
+    rtc: Rtc<SimBus<'a>, SimDelay<'a>>,
+    sim: &'a Sim,
+    started: Instant,
+}
+
+impl<'a> Module<'a> {
+    pub fn new(sim: &'a Sim) -> Self {
+        Self { rtc: Rtc::new(sim.bus(), sim.delay()), sim, started: Instant::now() }
+    }
+
+    // Advance the simulation to the wall clock. Delays for EEPROM writes may have run it
+    // slightly ahead, in which case it waits for the wall clock to catch up.
+    fn sync(&self) {
+        let elapsed = self.started.elapsed().as_millis() as u64;
+        self.sim.advance_ms(elapsed.saturating_sub(self.sim.millis()));
+    }
+}
+
+// Return: the host's current UNIX timestamp, for setting the module to the system clock
+pub fn system_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs())
+}
+
+impl Clock for Module<'_> {
+    type Error = RtcError<SimError>;
+
+    fn now(&mut self) -> Result<Datetime, Self::Error> {
+        self.sync();
+        Clock::now(&mut self.rtc)
+    }
+
+    fn set(&mut self, time: Datetime) -> Result<(), Self::Error> {
+        self.sync();
+        Clock::set(&mut self.rtc, time)
+    }
+
+    fn lost_time(&mut self) -> Result<bool, Self::Error> {
+        self.sync();
+        Clock::lost_time(&mut self.rtc)
+    }
+
+    fn alarm(&mut self, alarm: Alarm) -> Result<alarm::State, Self::Error> {
+        self.sync();
+        Clock::alarm(&mut self.rtc, alarm)
+    }
+
+    fn set_alarm(&mut self, alarm: Alarm, schedule: Schedule) -> Result<(), Self::Error> {
+        self.sync();
+        Clock::set_alarm(&mut self.rtc, alarm, schedule)
+    }
+
+    fn clear_alarm(&mut self, alarm: Alarm) -> Result<(), Self::Error> {
+        self.sync();
+        Clock::clear_alarm(&mut self.rtc, alarm)
+    }
+
+    fn square_wave(&mut self) -> Result<sqw::State, Self::Error> {
+        self.sync();
+        Clock::square_wave(&mut self.rtc)
+    }
+
+    fn set_square_wave(&mut self, rate: Option<Rate>) -> Result<(), Self::Error> {
+        self.sync();
+        Clock::set_square_wave(&mut self.rtc, rate)
+    }
+
+    fn set_32khz_output(&mut self, enabled: bool) -> Result<(), Self::Error> {
+        self.sync();
+        Clock::set_32khz_output(&mut self.rtc, enabled)
+    }
+
+    fn read_temperature(&mut self) -> Result<(i8, u8), Self::Error> {
+        self.sync();
+        Clock::read_temperature(&mut self.rtc)
+    }
+
+    fn update_temperature(&mut self) -> Result<bool, Self::Error> {
+        self.sync();
+        Clock::update_temperature(&mut self.rtc)
+    }
+
+    // Really wait, so PIN lockouts take as long as they do on the Arduino
+    fn delay_ms(&mut self, ms: u16) {
+        thread::sleep(Duration::from_millis(ms as u64));
+        self.sync();
+    }
+}
+
+impl Storage for Module<'_> {
+    type Error = RtcError<SimError>;
+
+    // The EEPROM finishes a write cycle while the next command is typed, as it would on the Arduino
+    fn read_eeprom(&mut self, address: [u8; 2], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.sync();
+        self.rtc.read_eeprom(address, buffer)
+    }
+
+    fn write_byte_eeprom(&mut self, address: [u8; 2], input: u8) -> Result<(), Self::Error> {
+        self.sync();
+        Storage::write_byte_eeprom(&mut self.rtc, address, input)
+    }
+
+    fn write_page_eeprom(&mut self, address: [u8; 2], input: [u8; PAGE_SIZE]) -> Result<(), Self::Error> {
+        self.sync();
+        Storage::write_page_eeprom(&mut self.rtc, address, input)
+    }
+
+    fn wait_for_write(&mut self) {
+        self.rtc.wait_for_write();
+    }
+}
@@ -0,0 +1,53 @@
+// Command line options for the emulator
+pub const USAGE: &str = "\
+Usage: otp-emulator [--eeprom <image>] [--clock <system|power-on|UNIX timestamp>]
+
+  --eeprom <image>  EEPROM image file, created on the first write. (default is eeprom.bin)
+  --clock system    Start the RTC at the host's time. (default)
+  --clock power-on  Start the RTC at 1900/1/1 00:00:00, as after it lost all power.
+  --clock <time>    Start the RTC at the given UNIX timestamp.";
+
+const DEFAULT_EEPROM: &str = "eeprom.bin";
+
+#[derive(Debug, PartialEq)]
+pub enum StartTime {
+    System,
+    PowerOn,
+    At(u64),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub eeprom: String,
+    pub start_time: StartTime,
+}
+
+impl Options {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Options {
+            eeprom: DEFAULT_EEPROM.to_string(),
+            start_time: StartTime::System,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--eeprom" => {
+                    options.eeprom = args.next().ok_or("--eeprom needs a file name")?;
+                },
+                "--clock" => {
+                    let clock = args.next().ok_or("--clock needs a value")?;
+                    options.start_time = match clock.as_str() {
+                        "system" => StartTime::System,
+                        "power-on" => StartTime::PowerOn,
+                        timestamp => StartTime::At(timestamp.parse().map_err(|_| {
+                            format!("Unknown clock {}", timestamp)
+                        })?),
+                    };
+                },
+                _ => return Err(format!("Unknown option {}", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
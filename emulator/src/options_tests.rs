@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod parse {
+    use crate::options::{Options, StartTime};
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(parse(&[]), Ok(Options { eeprom: "eeprom.bin".to_string(), start_time: StartTime::System }));
+    }
+
+    #[test]
+    fn eeprom_and_clock() {
+        assert_eq!(
+            parse(&["--clock", "1669714637", "--eeprom", "module.bin"]),
+            Ok(Options { eeprom: "module.bin".to_string(), start_time: StartTime::At(1_669_714_637) })
+        );
+        assert_eq!(parse(&["--clock", "power-on"]).unwrap().start_time, StartTime::PowerOn);
+        assert_eq!(parse(&["--clock", "system"]).unwrap().start_time, StartTime::System);
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert!(parse(&["--clock", "yesterday"]).is_err());
+        assert!(parse(&["--clock"]).is_err());
+        assert!(parse(&["--eeprom"]).is_err());
+        assert!(parse(&["--baud", "9600"]).is_err());
+    }
+}
@@ -3,13 +3,6 @@
 
 use panic_halt as _;
 
-mod sha1_tests;
-
-pub mod tty;
-pub mod rtc;
-pub mod byte_helper;
-pub mod sha1;
-
 #[arduino_hal::entry]
 fn main() -> ! {
     let dp = arduino_hal::Peripherals::take().unwrap();
@@ -27,13 +20,16 @@ fn main() -> ! {
 
     // let mut led = pins.d13.into_output();
 
-    let mut tty = tty::TTY::new(
+    let mut tty = otp_core::tty::TTY::new(
         arduino_hal::default_serial!(dp, pins, 9600), 
-        arduino_hal::I2c::new(
-            dp.TWI,
-            pins.a4.into_pull_up_input(),
-            pins.a5.into_pull_up_input(),
-            50000
+        otp_core::rtc::Rtc::new(
+            arduino_hal::I2c::new(
+                dp.TWI,
+                pins.a4.into_pull_up_input(),
+                pins.a5.into_pull_up_input(),
+                50000
+            ),
+            arduino_hal::Delay::new()
         )
     );
 
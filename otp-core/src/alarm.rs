@@ -0,0 +1,221 @@
+use ufmt::derive::uDebug;
+
+// The DS3231 has two alarms, each a set of time registers with a mask bit (bit 7) per register.
+// An alarm fires when every unmasked register matches the time, which sets its flag (A1F/A2F)
+// in the status register and, if its interrupt is enabled, pulls INT/SQW low until the flag
+// is cleared.
+// Alarm 1 => 0x07..0x0b: seconds, minutes, hours, day or date
+// Alarm 2 => 0x0b..0x0e: minutes, hours, day or date, and fires at 00 seconds
+// Bit 6 of the day or date register (DY/DT) picks matching the day of the week over the date
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub enum Alarm {
+    One,
+    Two,
+}
+
+impl Alarm {
+    // The first alarm register
+    pub fn register(self) -> u8 {
+        match self {
+            Alarm::One => 0x07,
+            Alarm::Two => 0x0b,
+        }
+    }
+
+    // A1F/A2F in the status register, and A1IE/A2IE in the control register
+    pub fn bit(self) -> u8 {
+        match self {
+            Alarm::One => 0b0000_0001,
+            Alarm::Two => 0b0000_0010,
+        }
+    }
+}
+
+// Which registers have to match for the alarm to fire
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub enum Match {
+    // Alarm 1 only
+    EverySecond,
+    // Alarm 2 only, at 00 seconds
+    EveryMinute,
+    // The seconds, so once a minute. Alarm 1 only.
+    Seconds,
+    // The minutes (and seconds on alarm 1), so once an hour
+    Minutes,
+    // The hours, minutes (and seconds on alarm 1), so once a day
+    Hours,
+    // The date of the month, 1 to 31, and the time
+    Date(u8),
+    // The day of the week, 1 (Monday) to 7, and the time
+    Day(u8),
+}
+
+// The time fields a `Match` leaves out are kept, but don't matter. Alarm 2 has no seconds,
+// so they must be 0.
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub matching: Match,
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+}
+
+// What the shell shows for an alarm
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub struct State {
+    // None if the registers don't hold a schedule, e.g. since the DS3231 powered up
+    pub schedule: Option<Schedule>,
+    // A1IE/A2IE: the alarm pulls INT/SQW low when it fires
+    pub interrupt: bool,
+    // A1F/A2F: the alarm has fired since the flag was last cleared
+    pub fired: bool,
+}
+
+const MASK: u8 = 0b1000_0000;
+const DY: u8 = 0b0100_0000;
+const TWELVE_HOUR: u8 = 0b0100_0000;
+const PM: u8 = 0b0010_0000;
+
+impl Schedule {
+    // Parse the shell's `HH:MM:SS` for alarm 1 or `HH:MM` for alarm 2, where a `*` matches
+    // anything, followed by an optional `date <1-31>` or `day <1-7>`
+    // e.g. `*:*:*` fires every second, `*:15:00` every hour and `08:30 day 1` every Monday
+    // Return: None if the text is malformed, or the alarm can't fire that way
+    pub fn parse(alarm: Alarm, text: &[u8]) -> Option<Self> {
+        let mut words = text.split(|byte| *byte == b' ').filter(|word| !word.is_empty());
+        let mut fields = words.next()?.split(|byte| *byte == b':');
+        let hours = parse_field(fields.next()?, 23)?;
+        let minutes = parse_field(fields.next()?, 59)?;
+        let seconds = match alarm {
+            Alarm::One => parse_field(fields.next()?, 59)?,
+            Alarm::Two => Some(0),
+        };
+        if fields.next().is_some() {
+            return None;
+        }
+
+        let day = match (words.next(), words.next()) {
+            (None, _) => None,
+            (Some(b"date"), Some(date)) => Some(Match::Date(parse_field(date, 31)?.filter(|date| *date >= 1)?)),
+            (Some(b"day"), Some(day)) => Some(Match::Day(parse_field(day, 7)?.filter(|day| *day >= 1)?)),
+            _ => return None,
+        };
+        if words.next().is_some() {
+            return None;
+        }
+
+        // Only a run of leading fields can be left out, e.g. there is no way to fire at
+        // 30 seconds past every minute of 8 o'clock
+        let matching = match (day, hours, minutes, seconds, alarm) {
+            (None, None, None, None, Alarm::One) => Match::EverySecond,
+            (None, None, None, Some(_), Alarm::One) => Match::Seconds,
+            (None, None, None, Some(_), Alarm::Two) => Match::EveryMinute,
+            (None, None, Some(_), Some(_), _) => Match::Minutes,
+            (None, Some(_), Some(_), Some(_), _) => Match::Hours,
+            (Some(day), Some(_), Some(_), Some(_), _) => day,
+            _ => return None,
+        };
+
+        Some(Schedule {
+            matching,
+            hours: hours.unwrap_or(0),
+            minutes: minutes.unwrap_or(0),
+            seconds: seconds.unwrap_or(0),
+        })
+    }
+
+    // Return: the seconds, minutes, hours and day or date registers, where alarm 2 leaves
+    // out the seconds. None if the alarm can't fire that way, or a field is out of range.
+    pub fn to_registers(&self, alarm: Alarm) -> Option<[u8; 4]> {
+        let masks: [bool; 4] = match (self.matching, alarm) {
+            (Match::EverySecond, Alarm::One) => [true, true, true, true],
+            (Match::EveryMinute, Alarm::Two) => [false, true, true, true],
+            (Match::Seconds, Alarm::One) => [false, true, true, true],
+            (Match::Minutes, _) => [false, false, true, true],
+            (Match::Hours, _) => [false, false, false, true],
+            (Match::Date(_), _) | (Match::Day(_), _) => [false; 4],
+            _ => return None,
+        };
+        if self.hours > 23 || self.minutes > 59 || self.seconds > 59 {
+            return None;
+        }
+        if alarm == Alarm::Two && self.seconds != 0 {
+            return None;
+        }
+
+        let day_or_date = match self.matching {
+            Match::Date(date) if (1..=31).contains(&date) => to_bcd(date),
+            Match::Day(day) if (1..=7).contains(&day) => DY | day,
+            Match::Date(_) | Match::Day(_) => return None,
+            _ => 0x01,
+        };
+
+        let mut registers = [to_bcd(self.seconds), to_bcd(self.minutes), to_bcd(self.hours), day_or_date];
+        for (register, masked) in registers.iter_mut().zip(masks) {
+            if masked {
+                *register |= MASK;
+            }
+        }
+
+        Some(registers)
+    }
+
+    // Read back what `to_registers` wrote, or what something else left in the registers.
+    // Alarm 2 takes the seconds register as 0.
+    // Return: None if the masks don't make up a mode, or a field is out of range
+    pub fn from_registers(alarm: Alarm, registers: [u8; 4]) -> Option<Self> {
+        let [seconds, minutes, hours, day_or_date] = registers;
+        let masks = (seconds & MASK != 0, minutes & MASK != 0, hours & MASK != 0, day_or_date & MASK != 0);
+        let matching = match (masks, alarm) {
+            ((true, true, true, true), Alarm::One) => Match::EverySecond,
+            ((false, true, true, true), Alarm::One) => Match::Seconds,
+            ((false, true, true, true), Alarm::Two) => Match::EveryMinute,
+            ((false, false, true, true), _) => Match::Minutes,
+            ((false, false, false, true), _) => Match::Hours,
+            ((false, false, false, false), _) if day_or_date & DY != 0 => Match::Day(day_or_date & 0b0000_1111),
+            ((false, false, false, false), _) => Match::Date(from_bcd(day_or_date & 0b0011_1111)),
+            _ => return None,
+        };
+
+        // Bit 6 selects 12-hour time, where bit 5 is the PM flag
+        let hours = match hours & TWELVE_HOUR != 0 {
+            true => from_bcd(hours & 0b0001_1111) % 12 + if hours & PM != 0 { 12 } else { 0 },
+            false => from_bcd(hours & 0b0011_1111),
+        };
+
+        let schedule = Schedule {
+            matching,
+            hours,
+            minutes: from_bcd(minutes & !MASK),
+            seconds: from_bcd(seconds & !MASK),
+        };
+        schedule.to_registers(alarm)?;
+
+        Some(schedule)
+    }
+}
+
+// Return: Some(None) for `*`, Some(Some(value)) for a number no higher than `max`, or None
+// if the field is malformed
+fn parse_field(field: &[u8], max: u8) -> Option<Option<u8>> {
+    if field == b"*" {
+        return Some(None);
+    }
+    if field.is_empty() || field.len() > 2 || !field.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    let value = field.iter().fold(0, |value, digit| value * 10 + (digit - b'0'));
+    match value <= max {
+        true => Some(Some(value)),
+        false => None,
+    }
+}
+
+fn from_bcd(byte: u8) -> u8 {
+    (byte >> 4) * 10 + (byte & 0b1111)
+}
+
+fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
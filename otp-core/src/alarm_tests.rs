@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod parse {
+    use crate::alarm::{Alarm, Match, Schedule};
+
+    fn schedule(matching: Match, hours: u8, minutes: u8, seconds: u8) -> Option<Schedule> {
+        Some(Schedule { matching, hours, minutes, seconds })
+    }
+
+    #[test]
+    fn alarm_1_modes() {
+        assert_eq!(Schedule::parse(Alarm::One, b"*:*:*"), schedule(Match::EverySecond, 0, 0, 0));
+        assert_eq!(Schedule::parse(Alarm::One, b"*:*:30"), schedule(Match::Seconds, 0, 0, 30));
+        assert_eq!(Schedule::parse(Alarm::One, b"*:15:30"), schedule(Match::Minutes, 0, 15, 30));
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15:30"), schedule(Match::Hours, 8, 15, 30));
+        assert_eq!(Schedule::parse(Alarm::One, b"8:15:30 date 31"), schedule(Match::Date(31), 8, 15, 30));
+        assert_eq!(Schedule::parse(Alarm::One, b"23:59:59  day 7"), schedule(Match::Day(7), 23, 59, 59));
+    }
+
+    #[test]
+    fn alarm_2_modes() {
+        assert_eq!(Schedule::parse(Alarm::Two, b"*:*"), schedule(Match::EveryMinute, 0, 0, 0));
+        assert_eq!(Schedule::parse(Alarm::Two, b"*:15"), schedule(Match::Minutes, 0, 15, 0));
+        assert_eq!(Schedule::parse(Alarm::Two, b"08:15"), schedule(Match::Hours, 8, 15, 0));
+        assert_eq!(Schedule::parse(Alarm::Two, b"08:15 date 1"), schedule(Match::Date(1), 8, 15, 0));
+        assert_eq!(Schedule::parse(Alarm::Two, b"08:15 day 1"), schedule(Match::Day(1), 8, 15, 0));
+    }
+
+    #[test]
+    fn unsupported_schedules() {
+        // Only leading fields can be left out
+        assert_eq!(Schedule::parse(Alarm::One, b"08:*:30"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"*:15:*"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"*:15:30 day 1"), None);
+        assert_eq!(Schedule::parse(Alarm::Two, b"08:*"), None);
+        // Alarm 2 has no seconds, alarm 1 needs them
+        assert_eq!(Schedule::parse(Alarm::Two, b"08:15:30"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15"), None);
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(Schedule::parse(Alarm::One, b""), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"24:00:00"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:60:00"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15:3x"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"008:15:30"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15:30 date 0"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15:30 date 32"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15:30 day 8"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15:30 day *"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15:30 day"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15:30 week 1"), None);
+        assert_eq!(Schedule::parse(Alarm::One, b"08:15:30 day 1 2"), None);
+    }
+}
+
+#[cfg(test)]
+mod registers {
+    use crate::alarm::{Alarm, Match, Schedule};
+
+    fn schedule(matching: Match, hours: u8, minutes: u8, seconds: u8) -> Schedule {
+        Schedule { matching, hours, minutes, seconds }
+    }
+
+    #[test]
+    fn alarm_1_masks() {
+        // A1M1 to A1M4 are bit 7 of each register, and DY/DT is bit 6 of the last
+        assert_eq!(schedule(Match::EverySecond, 0, 0, 0).to_registers(Alarm::One), Some([0x80, 0x80, 0x80, 0x81]));
+        assert_eq!(schedule(Match::Seconds, 0, 0, 30).to_registers(Alarm::One), Some([0x30, 0x80, 0x80, 0x81]));
+        assert_eq!(schedule(Match::Minutes, 0, 15, 30).to_registers(Alarm::One), Some([0x30, 0x15, 0x80, 0x81]));
+        assert_eq!(schedule(Match::Hours, 23, 15, 30).to_registers(Alarm::One), Some([0x30, 0x15, 0x23, 0x81]));
+        assert_eq!(schedule(Match::Date(31), 23, 15, 30).to_registers(Alarm::One), Some([0x30, 0x15, 0x23, 0x31]));
+        assert_eq!(schedule(Match::Day(7), 23, 15, 30).to_registers(Alarm::One), Some([0x30, 0x15, 0x23, 0x47]));
+    }
+
+    #[test]
+    fn alarm_2_masks() {
+        assert_eq!(schedule(Match::EveryMinute, 0, 0, 0).to_registers(Alarm::Two), Some([0x00, 0x80, 0x80, 0x81]));
+        assert_eq!(schedule(Match::Minutes, 0, 15, 0).to_registers(Alarm::Two), Some([0x00, 0x15, 0x80, 0x81]));
+        assert_eq!(schedule(Match::Day(1), 8, 15, 0).to_registers(Alarm::Two), Some([0x00, 0x15, 0x08, 0x41]));
+    }
+
+    #[test]
+    fn modes_the_alarm_lacks() {
+        assert_eq!(schedule(Match::EverySecond, 0, 0, 0).to_registers(Alarm::Two), None);
+        assert_eq!(schedule(Match::Seconds, 0, 0, 30).to_registers(Alarm::Two), None);
+        assert_eq!(schedule(Match::EveryMinute, 0, 0, 0).to_registers(Alarm::One), None);
+        assert_eq!(schedule(Match::Hours, 8, 15, 30).to_registers(Alarm::Two), None);
+        assert_eq!(schedule(Match::Hours, 24, 0, 0).to_registers(Alarm::One), None);
+        assert_eq!(schedule(Match::Day(0), 8, 0, 0).to_registers(Alarm::One), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let schedules = [
+            (Alarm::One, schedule(Match::EverySecond, 0, 0, 0)),
+            (Alarm::One, schedule(Match::Seconds, 0, 0, 59)),
+            (Alarm::One, schedule(Match::Minutes, 0, 59, 1)),
+            (Alarm::One, schedule(Match::Hours, 12, 0, 0)),
+            (Alarm::One, schedule(Match::Date(15), 0, 30, 0)),
+            (Alarm::One, schedule(Match::Day(3), 19, 45, 10)),
+            (Alarm::Two, schedule(Match::EveryMinute, 0, 0, 0)),
+            (Alarm::Two, schedule(Match::Minutes, 0, 45, 0)),
+            (Alarm::Two, schedule(Match::Hours, 6, 0, 0)),
+            (Alarm::Two, schedule(Match::Date(28), 6, 0, 0)),
+            (Alarm::Two, schedule(Match::Day(5), 6, 0, 0)),
+        ];
+        for (alarm, schedule) in schedules {
+            assert_eq!(Schedule::from_registers(alarm, schedule.to_registers(alarm).unwrap()), Some(schedule));
+        }
+    }
+
+    #[test]
+    fn registers_without_a_schedule() {
+        // Power-on registers are all 0, which is the date mode on the 0th
+        assert_eq!(Schedule::from_registers(Alarm::One, [0; 4]), None);
+        // Masks have to be a run from the start
+        assert_eq!(Schedule::from_registers(Alarm::One, [0x80, 0x00, 0x00, 0x01]), None);
+        assert_eq!(Schedule::from_registers(Alarm::Two, [0x00, 0x80, 0x00, 0x81]), None);
+    }
+
+    #[test]
+    fn twelve_hour_registers() {
+        // Bit 6 selects 12-hour time, where bit 5 is the PM flag
+        assert_eq!(Schedule::from_registers(Alarm::Two, [0x00, 0x30, 0b0110_0001, 0x81]), Some(schedule(Match::Hours, 13, 30, 0)));
+        assert_eq!(Schedule::from_registers(Alarm::Two, [0x00, 0x00, 0b0101_0010, 0x81]), Some(schedule(Match::Hours, 0, 0, 0)));
+    }
+}
@@ -0,0 +1,230 @@
+use ufmt::derive::uDebug;
+
+use crate::sha1;
+
+#[derive(Debug, Clone, Copy, uDebug)]
+pub enum ByteHelperError {
+    InvalidHexChar,
+    InvalidDecimalChar,
+    InvalidBase32Char,
+    InvalidBase64Char,
+    SizingError,
+}
+
+pub fn bytes_to_u32(bytes: [u8; 4]) -> u32  {
+    (((bytes[0] as u32) << 24) & 0xff000000) |
+    (((bytes[1] as u32) << 16) & 0x00ff0000) |
+    (((bytes[2] as u32) <<  8) & 0x0000ff00) |
+    ((bytes[3] as u32)        & 0x000000ff)
+}
+
+pub fn bytes_to_u64(bytes: [u8; 8]) -> u64  {
+    ((bytes_to_u32([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64) << 32) |
+    (bytes_to_u32([bytes[4], bytes[5], bytes[6], bytes[7]]) as u64)
+}
+
+pub fn u16_to_bytes(word: u16) -> [u8; 2] {
+    [
+        ((word >> 8*1) & 0xff) as u8,
+        ((word >> 8*0) & 0xff) as u8,
+    ]
+}
+
+pub fn u32_to_bytes(word: u32) -> [u8; 4] {
+    [
+        ((word >> 8*3) & 0xff) as u8,
+        ((word >> 8*2) & 0xff) as u8,
+        ((word >> 8*1) & 0xff) as u8,
+        ((word >> 8*0) & 0xff) as u8,
+    ]
+}
+
+pub fn u64_to_bytes(word: u64) -> [u8; 8] {
+    [
+        ((word >> 8*7) & 0xff) as u8,
+        ((word >> 8*6) & 0xff) as u8,
+        ((word >> 8*5) & 0xff) as u8,
+        ((word >> 8*4) & 0xff) as u8,
+        ((word >> 8*3) & 0xff) as u8,
+        ((word >> 8*2) & 0xff) as u8,
+        ((word >> 8*1) & 0xff) as u8,
+        ((word >> 8*0) & 0xff) as u8,
+    ]
+}
+
+pub fn hexstring_to_digest(hexstring: &str) -> Result<[u8; sha1::DIGEST_SIZE], ByteHelperError> {
+    // Return an error if there are an odd number of hex digits 
+    if hexstring.len() % 2 != 0 {
+        return Err(ByteHelperError::SizingError);
+    }
+
+    // Convert hexstring into a byte-array
+    let mut digest = [0_u8; sha1::DIGEST_SIZE];
+    for i in (0..hexstring.len()).step_by(2) {
+        match u8::from_str_radix(&hexstring[i..i+2], 16) {
+            Ok(byte) => digest[i/2] = byte,
+            Err(_) => return Err(ByteHelperError::InvalidHexChar),
+        }
+    }
+
+    Ok(digest)
+}
+
+pub fn hex_to_byte(hex: [u8; 2]) -> u8 {
+    let mut byte = 0_u8;
+    byte += 16 * match hex[0] {
+        0x30..=0x39 => hex[0] - 0x30,
+        0x41..=0x46 => hex[0] - 0x41 + 10,
+        0x61..=0x66 => hex[0] - 0x61 + 10,
+        _ => 0,
+    };
+    byte += match hex[1] {
+        0x30..=0x39 => hex[1] - 0x30,
+        0x41..=0x46 => hex[1] - 0x41 + 10,
+        0x61..=0x66 => hex[1] - 0x61 + 10,
+        _ => 0,
+    };
+
+    byte
+}
+
+// Parse an unsigned decimal number, failing on anything but digits or on overflow
+pub fn parse_u64(digits: &[u8]) -> Result<u64, ByteHelperError> {
+    if digits.is_empty() {
+        return Err(ByteHelperError::InvalidDecimalChar);
+    }
+
+    let mut value: u64 = 0;
+    for digit in digits {
+        let digit_value = match digit {
+            b'0'..=b'9' => (digit - b'0') as u64,
+            _ => return Err(ByteHelperError::InvalidDecimalChar),
+        };
+        value = value.checked_mul(10)
+            .and_then(|value| value.checked_add(digit_value))
+            .ok_or(ByteHelperError::SizingError)?;
+    }
+
+    Ok(value)
+}
+
+pub fn byte_to_hex(byte: u8) -> [u8; 2] {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+    [HEX_DIGITS[(byte >> 4) as usize], HEX_DIGITS[(byte & 0x0f) as usize]]
+}
+
+// Decode a hex string of any even length into `output`, skipping spaces
+// Return: number of bytes written to `output`
+pub fn hex_decode(input: &[u8], output: &mut [u8]) -> Result<usize, ByteHelperError> {
+    decode_bits(input, output, 4, |c| match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }, ByteHelperError::InvalidHexChar)
+}
+
+// RFC 4648 base32 - upper or lower case, with or without `=` padding, spaces are skipped
+// Return: number of bytes written to `output`
+pub fn base32_decode(input: &[u8], output: &mut [u8]) -> Result<usize, ByteHelperError> {
+    decode_bits(input, output, 5, |c| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a'),
+        b'2'..=b'7' => Some(c - b'2' + 26),
+        _ => None,
+    }, ByteHelperError::InvalidBase32Char)
+}
+
+// RFC 4648 base64 - with or without `=` padding, spaces are skipped
+// Return: number of bytes written to `output`
+pub fn base64_decode(input: &[u8], output: &mut [u8]) -> Result<usize, ByteHelperError> {
+    decode_bits(input, output, 6, |c| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }, ByteHelperError::InvalidBase64Char)
+}
+
+// RFC 4648 base32 without padding, as used by otpauth URIs
+// Return: number of characters written to `output`
+pub fn base32_encode(input: &[u8], output: &mut [u8]) -> Result<usize, ByteHelperError> {
+    encode_bits(input, output, 5, b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567")
+}
+
+// RFC 4648 base64 with padding
+// Return: number of characters written to `output`
+pub fn base64_encode(input: &[u8], output: &mut [u8]) -> Result<usize, ByteHelperError> {
+    let mut length = encode_bits(input, output, 6, b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/")?;
+    while length % 4 != 0 {
+        *output.get_mut(length).ok_or(ByteHelperError::SizingError)? = b'=';
+        length += 1;
+    }
+
+    Ok(length)
+}
+
+// Shared decoder for the power-of-two bases: every input character carries `bits` bits
+fn decode_bits(input: &[u8], output: &mut [u8], bits: u8, lookup: fn(u8) -> Option<u8>, error: ByteHelperError) -> Result<usize, ByteHelperError> {
+    let mut accumulator: u16 = 0;
+    let mut accumulated_bits: u8 = 0;
+    let mut length = 0;
+    let mut padding = false;
+
+    for c in input {
+        match c {
+            b' ' => continue,
+            b'=' => padding = true,
+            // Nothing but padding may follow the first `=`
+            _ if padding => return Err(error),
+            _ => {
+                let value = lookup(*c).ok_or(error)?;
+                accumulator = (accumulator << bits) | value as u16;
+                accumulated_bits += bits;
+
+                if accumulated_bits >= 8 {
+                    accumulated_bits -= 8;
+                    *output.get_mut(length).ok_or(ByteHelperError::SizingError)? = (accumulator >> accumulated_bits) as u8;
+                    length += 1;
+                }
+            },
+        }
+    }
+
+    // Whatever is left over has to be the zero bits that pad out the last character. A whole
+    // character left over is a truncated input, e.g. an odd number of hex digits.
+    let leftover: u16 = (1 << accumulated_bits) - 1;
+    if accumulated_bits >= bits || accumulator & leftover != 0 {
+        return Err(error);
+    }
+
+    Ok(length)
+}
+
+fn encode_bits(input: &[u8], output: &mut [u8], bits: u8, alphabet: &[u8]) -> Result<usize, ByteHelperError> {
+    let mask: u16 = (1 << bits) - 1;
+    let mut accumulator: u16 = 0;
+    let mut accumulated_bits: u8 = 0;
+    let mut length = 0;
+
+    for byte in input {
+        accumulator = (accumulator << 8) | *byte as u16;
+        accumulated_bits += 8;
+
+        while accumulated_bits >= bits {
+            accumulated_bits -= bits;
+            *output.get_mut(length).ok_or(ByteHelperError::SizingError)? = alphabet[((accumulator >> accumulated_bits) & mask) as usize];
+            length += 1;
+        }
+    }
+
+    // Left-align any leftover bits into one last character
+    if accumulated_bits > 0 {
+        *output.get_mut(length).ok_or(ByteHelperError::SizingError)? = alphabet[((accumulator << (bits - accumulated_bits)) & mask) as usize];
+        length += 1;
+    }
+
+    Ok(length)
+}
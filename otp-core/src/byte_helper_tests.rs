@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod base32 {
+    use crate::byte_helper;
+
+    // RFC 4648 Section 10 test vectors
+    const VECTORS: [(&[u8], &[u8]); 7] = [
+        (b"", b""),
+        (b"f", b"MY======"),
+        (b"fo", b"MZXQ===="),
+        (b"foo", b"MZXW6==="),
+        (b"foob", b"MZXW6YQ="),
+        (b"fooba", b"MZXW6YTB"),
+        (b"foobar", b"MZXW6YTBOI======"),
+    ];
+
+    #[test]
+    fn decode_rfc_vectors() {
+        for (plain, encoded) in VECTORS {
+            let mut output = [0_u8; 16];
+            let length = byte_helper::base32_decode(encoded, &mut output).unwrap();
+            assert_eq!(&output[0..length], plain);
+        }
+    }
+
+    #[test]
+    fn encode_rfc_vectors() {
+        for (plain, encoded) in VECTORS {
+            let mut output = [0_u8; 16];
+            let length = byte_helper::base32_encode(plain, &mut output).unwrap();
+            // Encoding never adds padding
+            let unpadded = encoded.split(|c| *c == b'=').next().unwrap();
+            assert_eq!(&output[0..length], unpadded);
+        }
+    }
+
+    #[test]
+    fn lowercase_spaces_and_no_padding() {
+        let mut output = [0_u8; 16];
+        let length = byte_helper::base32_decode(b"jbsw y3dp ehpk 3pxp", &mut output).unwrap();
+        assert_eq!(&output[0..length], b"Hello!\xde\xad\xbe\xef");
+    }
+
+    #[test]
+    fn invalid_input() {
+        let mut output = [0_u8; 16];
+        assert!(byte_helper::base32_decode(b"MZXW1", &mut output).is_err());
+        assert!(byte_helper::base32_decode(b"MY==MY", &mut output).is_err());
+        let mut small_output = [0_u8; 2];
+        assert!(byte_helper::base32_decode(b"MZXW6", &mut small_output).is_err());
+    }
+
+    #[test]
+    fn leftover_bits() {
+        let mut output = [0_u8; 16];
+        // "foo" is MZXW6, the last character carrying one padding bit, which has to be 0
+        assert!(byte_helper::base32_decode(b"MZXW7", &mut output).is_err());
+        // 3 characters are 15 bits, a byte and a truncated second one
+        assert!(byte_helper::base32_decode(b"MZX", &mut output).is_err());
+        assert!(byte_helper::base32_decode(b"M", &mut output).is_err());
+    }
+}
+
+#[cfg(test)]
+mod base64 {
+    use crate::byte_helper;
+
+    const VECTORS: [(&[u8], &[u8]); 6] = [
+        (b"f", b"Zg=="),
+        (b"fo", b"Zm8="),
+        (b"foo", b"Zm9v"),
+        (b"foob", b"Zm9vYg=="),
+        (b"fooba", b"Zm9vYmE="),
+        (b"foobar", b"Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn round_trip_rfc_vectors() {
+        for (plain, encoded) in VECTORS {
+            let mut output = [0_u8; 16];
+            let length = byte_helper::base64_decode(encoded, &mut output).unwrap();
+            assert_eq!(&output[0..length], plain);
+
+            let length = byte_helper::base64_encode(plain, &mut output).unwrap();
+            assert_eq!(&output[0..length], encoded);
+        }
+    }
+
+    #[test]
+    fn leftover_bits() {
+        let mut output = [0_u8; 16];
+        assert!(byte_helper::base64_decode(b"Zh==", &mut output).is_err());
+        assert!(byte_helper::base64_decode(b"Zm9=", &mut output).is_err());
+        assert!(byte_helper::base64_decode(b"Zm9vY", &mut output).is_err());
+    }
+}
+
+#[cfg(test)]
+mod hex {
+    use crate::byte_helper;
+
+    #[test]
+    fn decode_whole_bytes() {
+        let mut output = [0_u8; 32];
+        let length = byte_helper::hex_decode(b"4865 6c6C 6f21 DEAD beef", &mut output).unwrap();
+        assert_eq!(&output[0..length], b"Hello!\xde\xad\xbe\xef");
+        assert!(byte_helper::hex_decode(b"xyz", &mut output).is_err());
+        assert!(byte_helper::hex_decode(b"4865 6", &mut output).is_err());
+        assert!(byte_helper::hex_decode(b"abc", &mut output).is_err());
+    }
+
+    #[test]
+    fn byte_to_hex() {
+        assert_eq!(&byte_helper::byte_to_hex(0xa5), b"a5");
+        assert_eq!(&byte_helper::byte_to_hex(0x0f), b"0f");
+    }
+}
+
+#[cfg(test)]
+mod decimal {
+    use crate::byte_helper;
+
+    #[test]
+    fn parse_u64() {
+        assert_eq!(byte_helper::parse_u64(b"1669714637").unwrap(), 1669714637);
+        assert_eq!(byte_helper::parse_u64(b"18446744073709551615").unwrap(), u64::MAX);
+        assert!(byte_helper::parse_u64(b"18446744073709551616").is_err());
+        assert!(byte_helper::parse_u64(b"12a").is_err());
+        assert!(byte_helper::parse_u64(b"").is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use ufmt::derive::uDebug;
+
+use crate::otp;
+
+// ChaCha20-Poly1305 AEAD (RFC 8439)
+
+pub const KEY_SIZE: usize = 32;
+pub const NONCE_SIZE: usize = 12;
+pub const TAG_SIZE: usize = 16;
+
+#[derive(Debug, uDebug)]
+pub enum AeadError {
+    // The tag didn't match - the data, associated data or tag were changed
+    AuthenticationFailed,
+}
+
+// Encrypt `data` in place
+// Return: the authentication tag covering `associated_data` and the ciphertext
+pub fn seal(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE], associated_data: &[u8], data: &mut [u8]) -> [u8; TAG_SIZE] {
+    chacha20_xor(key, 1, nonce, data);
+    compute_tag(key, nonce, associated_data, data)
+}
+
+// Check the tag, then decrypt `data` in place. `data` is left untouched if the tag doesn't match.
+pub fn open(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE], associated_data: &[u8], data: &mut [u8], tag: &[u8; TAG_SIZE]) -> Result<(), AeadError> {
+    let expected = compute_tag(key, nonce, associated_data, data);
+    if !otp::constant_time_eq(&expected, tag) {
+        return Err(AeadError::AuthenticationFailed);
+    }
+
+    chacha20_xor(key, 1, nonce, data);
+    Ok(())
+}
+
+// ****************************************
+// *** 2.8 - AEAD Construction ***
+// ****************************************
+fn compute_tag(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE], associated_data: &[u8], ciphertext: &[u8]) -> [u8; TAG_SIZE] {
+    // 2.6 - The one-time Poly1305 key is the start of block 0
+    let mut one_time_key = [0_u8; 32];
+    one_time_key.copy_from_slice(&chacha20_block(key, 0, nonce)[0..32]);
+
+    let mut poly1305 = Poly1305::new(&one_time_key);
+    poly1305.update(associated_data);
+    poly1305.pad();
+    poly1305.update(ciphertext);
+    poly1305.pad();
+    poly1305.update(&(associated_data.len() as u64).to_le_bytes());
+    poly1305.update(&(ciphertext.len() as u64).to_le_bytes());
+    poly1305.finalize()
+}
+
+// ****************************************
+// *** 2.3 - The ChaCha20 Block Function ***
+// ****************************************
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]); state[d] ^= state[a]; state[d] = state[d].rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]); state[b] ^= state[c]; state[b] = state[b].rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]); state[d] ^= state[a]; state[d] = state[d].rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]); state[b] ^= state[c]; state[b] = state[b].rotate_left(7);
+}
+
+pub fn chacha20_block(key: &[u8; KEY_SIZE], counter: u32, nonce: &[u8; NONCE_SIZE]) -> [u8; 64] {
+    let mut initial = [0_u32; 16];
+    initial[0..4].copy_from_slice(&CONSTANTS);
+    for (word, bytes) in initial[4..12].iter_mut().zip(key.chunks(4)) {
+        *word = le_u32(bytes);
+    }
+    initial[12] = counter;
+    for (word, bytes) in initial[13..16].iter_mut().zip(nonce.chunks(4)) {
+        *word = le_u32(bytes);
+    }
+
+    // 20 rounds - alternating column and diagonal rounds
+    let mut state = initial;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut block = [0_u8; 64];
+    for (i, (word, initial_word)) in state.iter().zip(initial).enumerate() {
+        block[i*4..i*4+4].copy_from_slice(&word.wrapping_add(initial_word).to_le_bytes());
+    }
+    block
+}
+
+// 2.4 - XOR `data` with the key stream starting at block `counter`
+pub fn chacha20_xor(key: &[u8; KEY_SIZE], counter: u32, nonce: &[u8; NONCE_SIZE], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let key_stream = chacha20_block(key, counter.wrapping_add(i as u32), nonce);
+        for (byte, key_byte) in chunk.iter_mut().zip(key_stream) {
+            *byte ^= key_byte;
+        }
+    }
+}
+
+// ****************************************
+// *** 2.5 - The Poly1305 Algorithm ***
+// ****************************************
+// Arithmetic modulo 2^130 - 5 in five 26-bit limbs, so every product fits in a u64
+const LIMB_MASK: u32 = 0x3ff_ffff;
+
+pub struct Poly1305 {
+    r: [u32; 5],
+    h: [u32; 5],
+    s: [u32; 4],
+    block: [u8; 16],
+    block_length: usize,
+}
+
+impl Poly1305 {
+    pub fn new(key: &[u8; 32]) -> Self {
+        // r is clamped as it is read
+        let r = [
+            le_u32(&key[0..4]) & 0x3ff_ffff,
+            (le_u32(&key[3..7]) >> 2) & 0x3ff_ff03,
+            (le_u32(&key[6..10]) >> 4) & 0x3ff_c0ff,
+            (le_u32(&key[9..13]) >> 6) & 0x3f0_3fff,
+            (le_u32(&key[12..16]) >> 8) & 0x00f_ffff,
+        ];
+        let s = [le_u32(&key[16..20]), le_u32(&key[20..24]), le_u32(&key[24..28]), le_u32(&key[28..32])];
+
+        Self { r, h: [0; 5], s, block: [0; 16], block_length: 0 }
+    }
+
+    pub fn update(&mut self, message: &[u8]) {
+        for byte in message {
+            self.block[self.block_length] = *byte;
+            self.block_length += 1;
+
+            if self.block_length == 16 {
+                let block = self.block;
+                self.process_block(&block, 1 << 24);
+                self.block_length = 0;
+            }
+        }
+    }
+
+    // Zero-fill up to the next 16-byte boundary, as the AEAD construction requires
+    pub fn pad(&mut self) {
+        if self.block_length > 0 {
+            self.block[self.block_length..].fill(0);
+            let block = self.block;
+            self.process_block(&block, 1 << 24);
+            self.block_length = 0;
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; TAG_SIZE] {
+        // A final partial block gets its 0x01 byte here instead of above the top bit
+        if self.block_length > 0 {
+            self.block[self.block_length] = 1;
+            self.block[self.block_length + 1..].fill(0);
+            let block = self.block;
+            self.process_block(&block, 0);
+        }
+
+        // Fully carry h
+        let [mut h0, mut h1, mut h2, mut h3, mut h4] = self.h;
+        let mut carry;
+        carry = h1 >> 26; h1 &= LIMB_MASK;
+        h2 += carry; carry = h2 >> 26; h2 &= LIMB_MASK;
+        h3 += carry; carry = h3 >> 26; h3 &= LIMB_MASK;
+        h4 += carry; carry = h4 >> 26; h4 &= LIMB_MASK;
+        h0 += carry * 5; carry = h0 >> 26; h0 &= LIMB_MASK;
+        h1 += carry;
+
+        // g = h + 5 - 2^130, used instead of h if it doesn't go negative
+        let mut g0 = h0.wrapping_add(5); carry = g0 >> 26; g0 &= LIMB_MASK;
+        let mut g1 = h1.wrapping_add(carry); carry = g1 >> 26; g1 &= LIMB_MASK;
+        let mut g2 = h2.wrapping_add(carry); carry = g2 >> 26; g2 &= LIMB_MASK;
+        let mut g3 = h3.wrapping_add(carry); carry = g3 >> 26; g3 &= LIMB_MASK;
+        let g4 = h4.wrapping_add(carry).wrapping_sub(1 << 26);
+
+        // Select without branching on the secret value
+        let use_g = (g4 >> 31).wrapping_sub(1);
+        let use_h = !use_g;
+        h0 = (h0 & use_h) | (g0 & use_g);
+        h1 = (h1 & use_h) | (g1 & use_g);
+        h2 = (h2 & use_h) | (g2 & use_g);
+        h3 = (h3 & use_h) | (g3 & use_g);
+        h4 = (h4 & use_h) | (g4 & use_g);
+
+        // h = (h + s) % 2^128
+        let words = [
+            h0 | (h1 << 26),
+            (h1 >> 6) | (h2 << 20),
+            (h2 >> 12) | (h3 << 14),
+            (h3 >> 18) | (h4 << 8),
+        ];
+        let mut tag = [0_u8; TAG_SIZE];
+        let mut sum: u64 = 0;
+        for (i, (word, s_word)) in words.iter().zip(self.s).enumerate() {
+            sum = *word as u64 + s_word as u64 + (sum >> 32);
+            tag[i*4..i*4+4].copy_from_slice(&(sum as u32).to_le_bytes());
+        }
+        tag
+    }
+
+    // h = (h + block) * r % (2^130 - 5)
+    fn process_block(&mut self, block: &[u8; 16], high_bit: u32) {
+        let [r0, r1, r2, r3, r4] = self.r;
+        let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+
+        let h0 = self.h[0] + (le_u32(&block[0..4]) & LIMB_MASK);
+        let h1 = self.h[1] + ((le_u32(&block[3..7]) >> 2) & LIMB_MASK);
+        let h2 = self.h[2] + ((le_u32(&block[6..10]) >> 4) & LIMB_MASK);
+        let h3 = self.h[3] + ((le_u32(&block[9..13]) >> 6) & LIMB_MASK);
+        let h4 = self.h[4] + ((le_u32(&block[12..16]) >> 8) | high_bit);
+
+        let m = |a: u32, b: u32| a as u64 * b as u64;
+        let d0 = m(h0, r0) + m(h1, s4) + m(h2, s3) + m(h3, s2) + m(h4, s1);
+        let mut d1 = m(h0, r1) + m(h1, r0) + m(h2, s4) + m(h3, s3) + m(h4, s2);
+        let mut d2 = m(h0, r2) + m(h1, r1) + m(h2, r0) + m(h3, s4) + m(h4, s3);
+        let mut d3 = m(h0, r3) + m(h1, r2) + m(h2, r1) + m(h3, r0) + m(h4, s4);
+        let mut d4 = m(h0, r4) + m(h1, r3) + m(h2, r2) + m(h3, r1) + m(h4, r0);
+
+        // Partial carry back into 26-bit limbs
+        d1 += d0 >> 26;
+        d2 += d1 >> 26;
+        d3 += d2 >> 26;
+        d4 += d3 >> 26;
+        let mut h0 = (d0 as u32 & LIMB_MASK) + (d4 >> 26) as u32 * 5;
+        let h1 = (d1 as u32 & LIMB_MASK) + (h0 >> 26);
+        h0 &= LIMB_MASK;
+
+        self.h = [h0, h1, d2 as u32 & LIMB_MASK, d3 as u32 & LIMB_MASK, d4 as u32 & LIMB_MASK];
+    }
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
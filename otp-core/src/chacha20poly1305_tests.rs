@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod chacha20 {
+    use crate::chacha20poly1305;
+
+    // RFC 8439 2.3.2
+    #[test]
+    fn block_function() {
+        let mut key = [0_u8; 32];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let nonce = [0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x4a, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(chacha20poly1305::chacha20_block(&key, 1, &nonce), [
+            0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15, 0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20, 0x71, 0xc4,
+            0xc7, 0xd1, 0xf4, 0xc7, 0x33, 0xc0, 0x68, 0x03, 0x04, 0x22, 0xaa, 0x9a, 0xc3, 0xd4, 0x6c, 0x4e,
+            0xd2, 0x82, 0x64, 0x46, 0x07, 0x9f, 0xaa, 0x09, 0x14, 0xc2, 0xd7, 0x05, 0xd9, 0x8b, 0x02, 0xa2,
+            0xb5, 0x12, 0x9c, 0xd1, 0xde, 0x16, 0x4e, 0xb9, 0xcb, 0xd0, 0x83, 0xe8, 0xa2, 0x50, 0x3c, 0x4e,
+        ]);
+    }
+
+    #[test]
+    fn xor_is_its_own_inverse() {
+        let key = [0x42_u8; 32];
+        let nonce = [0x24_u8; 12];
+        let mut data = [0_u8; 100];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let original = data;
+        chacha20poly1305::chacha20_xor(&key, 1, &nonce, &mut data);
+        assert_ne!(data, original);
+        chacha20poly1305::chacha20_xor(&key, 1, &nonce, &mut data);
+        assert_eq!(data, original);
+    }
+}
+
+#[cfg(test)]
+mod poly1305 {
+    use crate::chacha20poly1305::Poly1305;
+
+    // RFC 8439 2.5.2
+    #[test]
+    fn mac() {
+        let key = [
+            0x85, 0xd6, 0xbe, 0x78, 0x57, 0x55, 0x6d, 0x33, 0x7f, 0x44, 0x52, 0xfe, 0x42, 0xd5, 0x06, 0xa8,
+            0x01, 0x03, 0x80, 0x8a, 0xfb, 0x0d, 0xb2, 0xfd, 0x4a, 0xbf, 0xf6, 0xaf, 0x41, 0x49, 0xf5, 0x1b,
+        ];
+        let mut poly1305 = Poly1305::new(&key);
+        poly1305.update(b"Cryptographic Forum Research Group");
+        assert_eq!(poly1305.finalize(), [
+            0xa8, 0x06, 0x1d, 0xc1, 0x30, 0x51, 0x36, 0xc6, 0xc2, 0x2b, 0x8b, 0xaf, 0x0c, 0x01, 0x27, 0xa9,
+        ]);
+    }
+
+    #[test]
+    fn split_updates() {
+        let key = [0x5a_u8; 32];
+        let mut whole = Poly1305::new(&key);
+        whole.update(b"Cryptographic Forum Research Group");
+        let mut split = Poly1305::new(&key);
+        split.update(b"Cryptographic Fo");
+        split.update(b"rum Research ");
+        split.update(b"Group");
+        assert_eq!(whole.finalize(), split.finalize());
+    }
+}
+
+#[cfg(test)]
+mod aead {
+    use crate::chacha20poly1305;
+
+    const PLAINTEXT: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+    const NONCE: [u8; 12] = [0x07, 0x00, 0x00, 0x00, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47];
+    const AAD: [u8; 12] = [0x50, 0x51, 0x52, 0x53, 0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7];
+    const CIPHERTEXT: [u8; 114] = [
+        0xd3, 0x1a, 0x8d, 0x34, 0x64, 0x8e, 0x60, 0xdb, 0x7b, 0x86, 0xaf, 0xbc, 0x53, 0xef, 0x7e, 0xc2,
+        0xa4, 0xad, 0xed, 0x51, 0x29, 0x6e, 0x08, 0xfe, 0xa9, 0xe2, 0xb5, 0xa7, 0x36, 0xee, 0x62, 0xd6,
+        0x3d, 0xbe, 0xa4, 0x5e, 0x8c, 0xa9, 0x67, 0x12, 0x82, 0xfa, 0xfb, 0x69, 0xda, 0x92, 0x72, 0x8b,
+        0x1a, 0x71, 0xde, 0x0a, 0x9e, 0x06, 0x0b, 0x29, 0x05, 0xd6, 0xa5, 0xb6, 0x7e, 0xcd, 0x3b, 0x36,
+        0x92, 0xdd, 0xbd, 0x7f, 0x2d, 0x77, 0x8b, 0x8c, 0x98, 0x03, 0xae, 0xe3, 0x28, 0x09, 0x1b, 0x58,
+        0xfa, 0xb3, 0x24, 0xe4, 0xfa, 0xd6, 0x75, 0x94, 0x55, 0x85, 0x80, 0x8b, 0x48, 0x31, 0xd7, 0xbc,
+        0x3f, 0xf4, 0xde, 0xf0, 0x8e, 0x4b, 0x7a, 0x9d, 0xe5, 0x76, 0xd2, 0x65, 0x86, 0xce, 0xc6, 0x4b,
+        0x61, 0x16,
+    ];
+    const TAG: [u8; 16] = [0x1a, 0xe1, 0x0b, 0x59, 0x4f, 0x09, 0xe2, 0x6a, 0x7e, 0x90, 0x2e, 0xcb, 0xd0, 0x60, 0x06, 0x91];
+
+    fn key() -> [u8; 32] {
+        let mut key = [0_u8; 32];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = 0x80 + i as u8;
+        }
+        key
+    }
+
+    // RFC 8439 2.8.2
+    #[test]
+    fn seal() {
+        let mut data = [0_u8; 114];
+        data.copy_from_slice(PLAINTEXT);
+        let tag = chacha20poly1305::seal(&key(), &NONCE, &AAD, &mut data);
+        assert_eq!(data, CIPHERTEXT);
+        assert_eq!(tag, TAG);
+    }
+
+    #[test]
+    fn open() {
+        let mut data = CIPHERTEXT;
+        chacha20poly1305::open(&key(), &NONCE, &AAD, &mut data, &TAG).unwrap();
+        assert_eq!(data, PLAINTEXT);
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let mut data = CIPHERTEXT;
+        data[10] ^= 0x01;
+        assert!(chacha20poly1305::open(&key(), &NONCE, &AAD, &mut data, &TAG).is_err());
+        // Nothing is decrypted when the tag doesn't match
+        assert_eq!(data[11..], CIPHERTEXT[11..]);
+
+        let mut data = CIPHERTEXT;
+        let mut aad = AAD;
+        aad[0] ^= 0x01;
+        assert!(chacha20poly1305::open(&key(), &NONCE, &aad, &mut data, &TAG).is_err());
+
+        let mut nonce = NONCE;
+        nonce[11] ^= 0x01;
+        assert!(chacha20poly1305::open(&key(), &nonce, &AAD, &mut data, &TAG).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use crate::sha1::{self, OtpError, DIGEST_SIZE};
+
+// HMAC-SHA1 challenge-response, as used by the YubiKey challenge-response slots and the
+// password managers that unlock with them (e.g. KeePassXC)
+
+// Challenges are sent to the token as a fixed 64-byte block
+pub const CHALLENGE_SIZE: usize = 64;
+// Room for the 20-byte secrets used by YubiKeys, with some to spare
+pub const SECRET_SIZE: usize = 30;
+
+// In variable-length mode a short challenge is padded to 64 bytes by repeating a byte
+// different from its last one (YubiKey `HMAC_LT64`). All trailing bytes equal to the
+// final byte are removed before the HMAC is calculated.
+// Return: the challenge with any padding removed
+pub fn strip_padding(challenge: &[u8]) -> &[u8] {
+    if challenge.len() != CHALLENGE_SIZE {
+        return challenge;
+    }
+
+    let last = challenge[CHALLENGE_SIZE - 1];
+    let length = challenge.iter().rposition(|byte| *byte != last).map_or(0, |index| index + 1);
+    &challenge[0..length]
+}
+
+pub fn respond(secret: &[u8], challenge: &[u8], variable_length: bool) -> Result<[u8; DIGEST_SIZE], OtpError> {
+    if challenge.len() > CHALLENGE_SIZE {
+        return Err(OtpError::InputSizeError);
+    }
+
+    let challenge = match variable_length {
+        true => strip_padding(challenge),
+        false => challenge,
+    };
+    sha1::gen_sha1_hmac(secret, challenge)
+}
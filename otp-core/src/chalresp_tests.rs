@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod challenge_response {
+    use crate::chalresp;
+    use crate::sha1;
+
+    #[test]
+    fn rfc_2202_response() {
+        let response = chalresp::respond(&[0x0b; 20], b"Hi There", false).unwrap();
+        assert_eq!(response, [
+            0xb6, 0x17, 0x31, 0x86, 0x55, 0x05, 0x72, 0x64, 0xe2, 0x8b,
+            0xc0, 0xb6, 0xfb, 0x37, 0x8c, 0x8e, 0xf1, 0x46, 0xbe, 0x00,
+        ]);
+    }
+
+    #[test]
+    fn variable_length_padding_is_stripped() {
+        let mut challenge = [0x00_u8; 64];
+        challenge[0..8].copy_from_slice(b"Hi There");
+        assert_eq!(chalresp::strip_padding(&challenge), b"Hi There");
+
+        let padded = chalresp::respond(&[0x0b; 20], &challenge, true).unwrap();
+        assert_eq!(padded, sha1::gen_sha1_hmac(&[0x0b; 20], b"Hi There").unwrap());
+
+        // Fixed mode hashes the whole block
+        let fixed = chalresp::respond(&[0x0b; 20], &challenge, false).unwrap();
+        assert_eq!(fixed, sha1::gen_sha1_hmac(&[0x0b; 20], &challenge).unwrap());
+    }
+
+    #[test]
+    fn padding_differs_from_last_byte() {
+        let mut challenge = [0xff_u8; 64];
+        challenge[0..3].copy_from_slice(&[0x01, 0x02, 0x00]);
+        assert_eq!(chalresp::strip_padding(&challenge), &[0x01, 0x02, 0x00]);
+
+        // Short challenges are never stripped
+        assert_eq!(chalresp::strip_padding(&[0x01, 0x00, 0x00]), &[0x01, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn oversized_challenge() {
+        assert!(chalresp::respond(&[0x0b; 20], &[0; 65], true).is_err());
+    }
+}
@@ -0,0 +1,43 @@
+// The time source behind the shell: the DS3231 on the Arduino, or a stand-in on other hosts
+use ufmt::uDebug;
+
+use crate::alarm::{self, Alarm, Schedule};
+use crate::datetime::Datetime;
+use crate::sqw::{self, Rate};
+
+pub trait Clock {
+    type Error: uDebug;
+
+    fn now(&mut self) -> Result<Datetime, Self::Error>;
+
+    // Setting the time also clears `lost_time`
+    fn set(&mut self, time: Datetime) -> Result<(), Self::Error>;
+
+    // Return: true if the clock stopped at some point since the time was last set, e.g. when
+    // its battery ran out, so the time is wrong
+    fn lost_time(&mut self) -> Result<bool, Self::Error>;
+
+    fn alarm(&mut self, alarm: Alarm) -> Result<alarm::State, Self::Error>;
+
+    // Arm the alarm: write its schedule, clear its flag and enable its interrupt on INT/SQW
+    fn set_alarm(&mut self, alarm: Alarm, schedule: Schedule) -> Result<(), Self::Error>;
+
+    // Disarm the alarm: disable its interrupt and clear its flag
+    fn clear_alarm(&mut self, alarm: Alarm) -> Result<(), Self::Error>;
+
+    fn square_wave(&mut self) -> Result<sqw::State, Self::Error>;
+
+    // Put a square wave out on INT/SQW, or with None hand the pin back to the alarms
+    fn set_square_wave(&mut self, rate: Option<Rate>) -> Result<(), Self::Error>;
+
+    fn set_32khz_output(&mut self, enabled: bool) -> Result<(), Self::Error>;
+
+    // Return: (whole numbers, 0.25 resolution value) in Celsius
+    fn read_temperature(&mut self) -> Result<(i8, u8), Self::Error>;
+
+    // Return: true if an update was started, false if one is already in progress
+    fn update_temperature(&mut self) -> Result<bool, Self::Error>;
+
+    // Block for a while, e.g. between failed PIN attempts
+    fn delay_ms(&mut self, ms: u16);
+}
@@ -0,0 +1,165 @@
+use crate::byte_helper;
+
+// Moving the clock far enough to reach codes from another time needs the console PIN, given
+// with `time` or `sync`. Corrections for drift go through without it.
+pub const MAX_FORWARD_SECONDS: u64 = 300;
+// Going back a TOTP step would hand out codes that may have already been used
+pub const MAX_BACKWARD_SECONDS: u64 = 30;
+
+// Small changes add up, so together they are held to the same limits, plus this much for every
+// hour the clock has run since it was last set with the PIN (or with none set)
+pub const DRIFT_SECONDS_PER_HOUR: u64 = 2;
+
+// How far small changes have moved the clock since the last authorized change
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Drift {
+    // Net seconds, positive if the clock was moved forward
+    pub seconds: i32,
+    // The time the last authorized change set, or None if the log has none
+    pub since: Option<u64>,
+}
+
+impl Drift {
+    // Return: the seconds the clock has run from `since` until `now`, leaving out the small changes
+    pub fn elapsed(&self, now: u64) -> u64 {
+        let moved = match self.since {
+            Some(since) => now.saturating_sub(since),
+            None => return 0,
+        };
+        match self.seconds >= 0 {
+            true => moved.saturating_sub(self.seconds as u64),
+            false => moved.saturating_add(self.seconds.unsigned_abs() as u64),
+        }
+    }
+
+    // Return: the drift after a small change from `previous` to `new`
+    pub fn after(&self, previous: u64, new: u64) -> i32 {
+        self.seconds.saturating_add(new.wrapping_sub(previous) as i64 as i32)
+    }
+}
+
+// Return: true if moving the clock from `previous` to `new` needs the PIN, on its own or on top
+// of the small changes that were made without it
+pub fn needs_authorization(previous: u64, new: u64, drift: &Drift) -> bool {
+    let too_large = match new >= previous {
+        true => new - previous > MAX_FORWARD_SECONDS,
+        false => previous - new > MAX_BACKWARD_SECONDS,
+    };
+    if too_large {
+        return true;
+    }
+
+    let allowance = drift.elapsed(previous) / 3600 * DRIFT_SECONDS_PER_HOUR;
+    let total = drift.after(previous, new);
+    match total >= 0 {
+        true => total as u64 > MAX_FORWARD_SECONDS + allowance,
+        false => total.unsigned_abs() as u64 > MAX_BACKWARD_SECONDS + allowance,
+    }
+}
+
+// Every clock change is logged in a ring of pages, one entry per page, and the sequence runs on
+// across both logs
+// 0x00..0x04 => sequence: u32
+// 0x04..0x08 => !sequence: u32
+// 0x08..0x10 => previous timestamp: u64
+// 0x10..0x18 => new timestamp: u64
+// 0x18 => source: u8
+// 0x19 => authorization: u8
+// 0x1a..0x1e => drift since the last authorized change: i32 (0 for authorized changes)
+// An entry is only valid if its complement matches, like a counter record, and the entry
+// with the highest sequence is the latest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Time,
+    Sync,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Authorization {
+    // Within the limits for drift
+    Small,
+    // Without a PIN there is nothing to check against
+    NoPin,
+    Pin,
+}
+
+// Authorized changes are logged apart from the small ones, so no run of small changes can push
+// them out of the log
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Log {
+    Small,
+    Authorized,
+}
+
+impl Log {
+    pub fn of(authorization: Authorization) -> Self {
+        match authorization {
+            Authorization::Small => Log::Small,
+            _ => Log::Authorized,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockChange {
+    pub sequence: u32,
+    pub previous: u64,
+    pub new: u64,
+    pub source: Source,
+    pub authorization: Authorization,
+    pub drift: i32,
+}
+
+impl ClockChange {
+    pub fn from_page(page: &[u8; 32]) -> Option<Self> {
+        let sequence = byte_helper::bytes_to_u32([page[0], page[1], page[2], page[3]]);
+        let check = byte_helper::bytes_to_u32([page[4], page[5], page[6], page[7]]);
+        if check != !sequence {
+            return None;
+        }
+
+        let mut previous = [0_u8; 8];
+        previous.copy_from_slice(&page[0x08..0x10]);
+        let mut new = [0_u8; 8];
+        new.copy_from_slice(&page[0x10..0x18]);
+        let source = match page[0x18] {
+            0 => Source::Time,
+            1 => Source::Sync,
+            _ => return None,
+        };
+        let authorization = match page[0x19] {
+            0 => Authorization::Small,
+            1 => Authorization::NoPin,
+            2 => Authorization::Pin,
+            _ => return None,
+        };
+        let drift = byte_helper::bytes_to_u32([page[0x1a], page[0x1b], page[0x1c], page[0x1d]]) as i32;
+
+        Some(ClockChange {
+            sequence,
+            previous: byte_helper::bytes_to_u64(previous),
+            new: byte_helper::bytes_to_u64(new),
+            source, authorization, drift,
+        })
+    }
+
+    pub fn to_page(&self) -> [u8; 32] {
+        let mut page = [0xff_u8; 32];
+        page[0..4].copy_from_slice(&byte_helper::u32_to_bytes(self.sequence));
+        page[4..8].copy_from_slice(&byte_helper::u32_to_bytes(!self.sequence));
+        page[0x08..0x10].copy_from_slice(&byte_helper::u64_to_bytes(self.previous));
+        page[0x10..0x18].copy_from_slice(&byte_helper::u64_to_bytes(self.new));
+        page[0x18] = match self.source {
+            Source::Time => 0,
+            Source::Sync => 1,
+        };
+        page[0x19] = match self.authorization {
+            Authorization::Small => 0,
+            Authorization::NoPin => 1,
+            Authorization::Pin => 2,
+        };
+        page[0x1a..0x1e].copy_from_slice(&byte_helper::u32_to_bytes(self.drift as u32));
+
+        page
+    }
+}
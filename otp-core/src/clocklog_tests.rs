@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod policy {
+    use crate::clocklog::{self, Drift};
+
+    const NONE: Drift = Drift { seconds: 0, since: None };
+
+    #[test]
+    fn drift_corrections_are_allowed() {
+        assert!(!clocklog::needs_authorization(1_669_714_637, 1_669_714_637, &NONE));
+        assert!(!clocklog::needs_authorization(1_669_714_637, 1_669_714_637 + 300, &NONE));
+        assert!(!clocklog::needs_authorization(1_669_714_637, 1_669_714_637 - 30, &NONE));
+    }
+
+    #[test]
+    fn large_jumps_need_the_pin() {
+        assert!(clocklog::needs_authorization(1_669_714_637, 1_669_714_637 + 301, &NONE));
+        assert!(clocklog::needs_authorization(1_669_714_637, 1_669_714_637 - 31, &NONE));
+        // Setting the clock after a power loss is a jump forward of over a century
+        assert!(clocklog::needs_authorization(86_494, 1_669_714_637, &NONE));
+    }
+
+    #[test]
+    fn small_changes_add_up() {
+        let ahead = Drift { seconds: 290, since: Some(1_669_714_000) };
+        assert!(!clocklog::needs_authorization(1_669_714_637, 1_669_714_637 + 10, &ahead));
+        assert!(clocklog::needs_authorization(1_669_714_637, 1_669_714_637 + 11, &ahead));
+        // Going back undoes the drift
+        assert!(!clocklog::needs_authorization(1_669_714_637, 1_669_714_637 - 30, &ahead));
+
+        let behind = Drift { seconds: -25, since: Some(1_669_714_000) };
+        assert!(!clocklog::needs_authorization(1_669_714_637, 1_669_714_637 - 5, &behind));
+        assert!(clocklog::needs_authorization(1_669_714_637, 1_669_714_637 - 6, &behind));
+    }
+
+    #[test]
+    fn the_allowance_grows_by_the_hour() {
+        // Two hours have passed once the 290 seconds the clock was moved are taken off
+        let ahead = Drift { seconds: 290, since: Some(1_669_714_000) };
+        let now = 1_669_714_000 + 290 + 7_200;
+        assert!(!clocklog::needs_authorization(now, now + 14, &ahead));
+        assert!(clocklog::needs_authorization(now, now + 15, &ahead));
+
+        assert_eq!(ahead.elapsed(now), 7_200);
+        assert_eq!(NONE.elapsed(now), 0);
+    }
+}
+
+#[cfg(test)]
+mod records {
+    use crate::clocklog::{Authorization, ClockChange, Source};
+
+    fn change() -> ClockChange {
+        ClockChange {
+            sequence: 7,
+            previous: 86_494,
+            new: 1_669_714_637,
+            source: Source::Sync,
+            authorization: Authorization::Pin,
+            drift: -42,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(ClockChange::from_page(&change().to_page()), Some(change()));
+
+        let time = ClockChange { source: Source::Time, authorization: Authorization::NoPin, ..change() };
+        assert_eq!(ClockChange::from_page(&time.to_page()), Some(time));
+    }
+
+    #[test]
+    fn erased_and_torn_entries_are_ignored() {
+        assert_eq!(ClockChange::from_page(&[0xff; 32]), None);
+
+        let mut page = change().to_page();
+        page[3] = 8;
+        assert_eq!(ClockChange::from_page(&page), None);
+
+        let mut page = change().to_page();
+        page[0x19] = 0xff;
+        assert_eq!(ClockChange::from_page(&page), None);
+    }
+}
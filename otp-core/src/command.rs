@@ -0,0 +1,56 @@
+// Console commands are looked up by name in a table. Everything after the first space of a
+// line is passed to the command as its parameter.
+pub const NAME_SIZE: usize = 8;
+
+pub struct Command<T> {
+    pub name: [u8; NAME_SIZE],
+    pub name_length: usize,
+    pub function: fn(&mut T, Option<&[u8]>),
+}
+
+// command!(b"name    ", 4, function) - names are padded to NAME_SIZE
+#[macro_export]
+macro_rules! command {
+    ($n:tt, $l:tt, $f:tt) => {
+        $crate::command::Command {
+            name: *$n,
+            name_length: $l,
+            function: $f,
+        }
+    };
+}
+
+impl<T> Command<T> {
+    pub fn name(&self) -> &[u8] {
+        &self.name[0..self.name_length]
+    }
+}
+
+// Split a line into the command name and its parameter, if there is one
+pub fn split_line(line: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let mut args = line.splitn(2, |byte| *byte == b' ');
+    (args.next().unwrap_or(&[]), args.next())
+}
+
+pub fn find<'a, T>(commands: &'a [Command<T>], name: &[u8]) -> Option<&'a Command<T>> {
+    commands.iter().find(|command| command.name() == name)
+}
+
+// Parse exactly two hex digits
+pub fn parse_hex_byte(digits: &[u8]) -> Option<u8> {
+    match digits {
+        [high, low] if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+            Some(crate::byte_helper::hex_to_byte([*high, *low]))
+        },
+        _ => None,
+    }
+}
+
+// Parse a 2-byte EEPROM address from exactly four hex digits
+pub fn parse_address(digits: &[u8]) -> Option<[u8; 2]> {
+    if digits.len() != 4 {
+        return None;
+    }
+
+    Some([parse_hex_byte(&digits[0..2])?, parse_hex_byte(&digits[2..4])?])
+}
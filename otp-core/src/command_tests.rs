@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod command_lines {
+    use crate::command::{self, Command};
+
+    fn first(count: &mut usize, _: Option<&[u8]>) {
+        *count += 1;
+    }
+
+    fn second(count: &mut usize, _: Option<&[u8]>) {
+        *count += 10;
+    }
+
+    const COMMANDS: [Command<usize>; 2] = [
+        crate::command!(b"hotp    ", 4, first),
+        crate::command!(b"hotpnext", 8, second),
+    ];
+
+    #[test]
+    fn split_line() {
+        assert_eq!(command::split_line(b"hotp 1 mail"), (&b"hotp"[..], Some(&b"1 mail"[..])));
+        assert_eq!(command::split_line(b"help"), (&b"help"[..], None));
+        assert_eq!(command::split_line(b""), (&b""[..], None));
+    }
+
+    #[test]
+    fn find_by_whole_name() {
+        let mut count = 0;
+        (command::find(&COMMANDS, b"hotp").unwrap().function)(&mut count, None);
+        (command::find(&COMMANDS, b"hotpnext").unwrap().function)(&mut count, None);
+        assert_eq!(count, 11);
+
+        assert!(command::find(&COMMANDS, b"hot").is_none());
+        assert!(command::find(&COMMANDS, b"hotp    ").is_none());
+        assert!(command::find(&COMMANDS, b"").is_none());
+    }
+
+    #[test]
+    fn parse_address() {
+        assert_eq!(command::parse_address(b"0020"), Some([0x00, 0x20]));
+        assert_eq!(command::parse_address(b"0bC0"), Some([0x0b, 0xc0]));
+        assert_eq!(command::parse_address(b"020"), None);
+        assert_eq!(command::parse_address(b"00200"), None);
+        assert_eq!(command::parse_address(b"00g0"), None);
+    }
+
+    #[test]
+    fn parse_hex_byte() {
+        assert_eq!(command::parse_hex_byte(b"ff"), Some(0xff));
+        assert_eq!(command::parse_hex_byte(b"0A"), Some(0x0a));
+        assert_eq!(command::parse_hex_byte(b"f"), None);
+        assert_eq!(command::parse_hex_byte(b"-1"), None);
+    }
+}
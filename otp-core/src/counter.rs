@@ -0,0 +1,37 @@
+use crate::byte_helper;
+
+// Counters that must survive a power loss are kept in two alternating 32-byte records
+// 0x00..0x08 => counter: u64
+// 0x08..0x10 => !counter: u64
+// A record is only valid if its complement matches, so a torn write is ignored and the
+// other record still holds the previous value. The highest valid record wins.
+pub fn counter_record(counter: u64) -> [u8; 32] {
+    let mut page = [0xff_u8; 32];
+    page[0..8].copy_from_slice(&byte_helper::u64_to_bytes(counter));
+    page[8..16].copy_from_slice(&byte_helper::u64_to_bytes(!counter));
+
+    page
+}
+
+pub fn parse_counter_record(page: &[u8; 32]) -> Option<u64> {
+    let mut counter_bytes = [0_u8; 8];
+    counter_bytes.copy_from_slice(&page[0..8]);
+    let mut check_bytes = [0_u8; 8];
+    check_bytes.copy_from_slice(&page[8..16]);
+
+    let counter = byte_helper::bytes_to_u64(counter_bytes);
+    match byte_helper::bytes_to_u64(check_bytes) == !counter {
+        true => Some(counter),
+        false => None,
+    }
+}
+
+// Return: (index of the record holding the current counter, current counter)
+pub fn latest_counter_record(records: [Option<u64>; 2]) -> Option<(usize, u64)> {
+    match records {
+        [Some(first), Some(second)] if second > first => Some((1, second)),
+        [Some(first), _] => Some((0, first)),
+        [None, Some(second)] => Some((1, second)),
+        [None, None] => None,
+    }
+}
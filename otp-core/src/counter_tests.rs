@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod counter_records {
+    use crate::counter;
+
+    #[test]
+    fn round_trip() {
+        let page = counter::counter_record(0x0123_4567_89ab_cdef);
+        assert_eq!(counter::parse_counter_record(&page), Some(0x0123_4567_89ab_cdef));
+    }
+
+    #[test]
+    fn erased_and_torn_records_are_ignored() {
+        assert_eq!(counter::parse_counter_record(&[0xff; 32]), None);
+
+        let mut page = counter::counter_record(41);
+        page[7] = 42;
+        assert_eq!(counter::parse_counter_record(&page), None);
+    }
+
+    #[test]
+    fn highest_valid_record_wins() {
+        assert_eq!(counter::latest_counter_record([Some(4), Some(5)]), Some((1, 5)));
+        assert_eq!(counter::latest_counter_record([Some(6), Some(5)]), Some((0, 6)));
+        assert_eq!(counter::latest_counter_record([Some(4), None]), Some((0, 4)));
+        assert_eq!(counter::latest_counter_record([None, Some(5)]), Some((1, 5)));
+        assert_eq!(counter::latest_counter_record([None, None]), None);
+    }
+}
@@ -0,0 +1,169 @@
+use ufmt::derive::uDebug;
+
+#[derive(uDebug)]
+pub struct Datetime {
+    pub seconds: u8,
+    pub minutes: u8,
+    pub hours: u8,
+    pub date: u8,
+    pub month: u8,
+    pub year: u32,
+}
+
+const SECONDS_PER_MINUTE: u8 = 60;
+const SECONDS_PER_HOUR: u32 = 3_600;
+const SECONDS_PER_DAY: u32 = 86_400;
+
+const DAYS_PER_YEAR: u16 = 365;
+const DAYS_PER_LEAP_YEAR: u16 = DAYS_PER_YEAR + 1;
+const DAYS_PER_MONTH: [u8; 12] = [
+    31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31
+];
+fn days_this_month(month: u8, year: u32) -> u8 {
+    match month {
+        2 if is_leap_year(year) => DAYS_PER_MONTH[1] + 1,
+        _ => DAYS_PER_MONTH[month as usize - 1],
+    }
+}
+
+fn days_in_year(year: u32) -> u32 {
+    match is_leap_year(year) {
+        true => DAYS_PER_LEAP_YEAR as u32,
+        false => DAYS_PER_YEAR as u32,
+    }
+}
+
+const EPOCH: Datetime = Datetime {
+    seconds: 0, minutes: 0, hours: 0,
+    date: 1, month: 1, year: 1970,
+};
+
+impl Datetime {
+    pub fn unix_timestamp(self) -> u64 {
+        let mut days: u32 = 0;
+        days += days_since_epoch(self.year);
+        days += days_this_year(self.year, self.month, self.date);
+
+        let mut seconds: u64 = 0;
+        seconds += days as u64 * SECONDS_PER_DAY as u64;
+        seconds += seconds_this_day(self.hours, self.minutes, self.seconds) as u64;
+
+        seconds
+    }
+
+    pub fn from_timestamp(timestamp: u64) -> Self {
+        let mut days: u32 = (timestamp / SECONDS_PER_DAY as u64) as u32;
+        let mut seconds: u32 = (timestamp % SECONDS_PER_DAY as u64) as u32;
+
+        // Calculate years since Epoch
+        let mut current_year: u32 = EPOCH.year;
+        while days >= days_in_year(current_year) {
+            days -= days_in_year(current_year);
+            current_year += 1;
+        }
+
+        // Calculate months from the start of this year, leaving the days into the month
+        let mut months: u8 = 1;
+        while days >= days_this_month(months, current_year) as u32 {
+            days -= days_this_month(months, current_year) as u32;
+            months += 1;
+        }
+
+        // Calculate hours from the start of the day
+        let hours: u32 = seconds / SECONDS_PER_HOUR;
+        seconds %= SECONDS_PER_HOUR;
+        
+
+        // Calculate minutes from the start of the hour
+        let minutes: u32 = seconds / SECONDS_PER_MINUTE as u32;
+        seconds %= SECONDS_PER_MINUTE as u32;
+
+        Datetime { seconds: seconds as u8, minutes: minutes as u8, hours: hours as u8, date: days as u8 + 1, month: months, year: current_year }
+    }
+
+    pub fn from_bytes(bytes: [u8; 7]) -> Self {
+        let [seconds_byte, minutes_byte, 
+            hours_byte, _, date_byte, 
+            month_byte, year_byte] = bytes;
+        
+        let bcd_seconds = [seconds_byte >> 4, seconds_byte & 0b1111];
+        let bcd_minutes = [minutes_byte >> 4, minutes_byte & 0b1111];
+        // Bit 6 selects 12-hour time, where bit 5 is the PM flag
+        let military_time = (hours_byte & 0b100_0000) == 0;
+        let bcd_hours = [(hours_byte & 0b11_0000) >> 4, hours_byte & 0b1111];
+        let bcd_date = [date_byte >> 4, date_byte & 0b1111];
+        let bcd_month = [(month_byte & 0b1_0000) >> 4, month_byte & 0b1111];
+        let century = ((month_byte & 0b1000_0000) >> 7) * 100;
+        let bcd_year = [year_byte >> 4, year_byte & 0b1111];
+
+        Datetime { 
+            seconds: bcd_seconds[0] * 10 + bcd_seconds[1],
+            minutes: bcd_minutes[0] * 10 + bcd_minutes[1],
+            hours: match military_time {
+                true => bcd_hours[0] * 10 + bcd_hours[1],
+                false => {
+                    let pm = (bcd_hours[0] & 0b0010) == 0b0010;
+                    match pm {
+                        true => ((bcd_hours[0] & 0b0001) * 10 + bcd_hours[1]) % 12 + 12,
+                        false => ((bcd_hours[0] & 0b0001) * 10 + bcd_hours[1]) % 12,
+                    }
+                },
+            },
+            date: bcd_date[0] * 10 + bcd_date[1],
+            month: bcd_month[0] * 10 + bcd_month[1],
+            year: 1900 + century as u32 + bcd_year[0] as u32 * 10 + bcd_year[1] as u32,
+        }
+    }
+
+    // Return: 1 (Monday) to 7, counting from the Thursday that started 1970
+    pub fn weekday(&self) -> u8 {
+        let days = days_since_epoch(self.year) + days_this_year(self.year, self.month, self.date);
+
+        ((days + 3) % 7 + 1) as u8
+    }
+
+    pub fn to_bytes(self) -> [u8; 8] {
+        [
+        0x00, // Destination register on the DS3231
+        (((self.seconds / 10) << 4) & 0b0111_0000) | ((self.seconds % 10) & 0b1111),
+        (((self.minutes / 10) << 4) & 0b0111_0000) | ((self.minutes % 10) & 0b1111),
+        (((self.hours / 10) << 4) & 0b0011_0000) | ((self.hours % 10) & 0b1111), // Bit 6 clear sets military time
+        self.weekday(), // Alarms can match the day of the week
+        (((self.date / 10) << 4) & 0b0011_0000) | ((self.date % 10) & 0b1111),
+        //Set the century marker if the year is in the 2001's
+        if self.year >= 2000 {0b1000_0000} else {0b0000_0000} | (((self.month / 10) << 4) & 0b0001_0000) | ((self.month % 10) & 0b1111),
+        (((((self.year % 100) / 10) << 4) & 0b1111_0000) | ((self.year % 10) & 0b1111)) as u8,
+        ]
+    }
+
+}
+
+fn days_since_epoch(year: u32) -> u32 {
+    let mut days: u32 = 0;
+    for y in EPOCH.year..year {
+        days += days_in_year(y);
+    }
+
+    days 
+}
+
+fn days_this_year(year: u32, month: u8, day: u8) -> u32 {
+    let mut days: u32 = 0;
+    for m in 1..month {
+        days += days_this_month(m, year) as u32;
+    }
+
+    // Dates start from 1
+    days + day as u32 - 1
+}
+
+fn seconds_this_day(hours: u8, minutes: u8, seconds: u8) -> u32 {
+    hours as u32 * SECONDS_PER_HOUR + minutes as u32 * SECONDS_PER_MINUTE as u32 + seconds as u32
+}
+
+// https://en.wikipedia.org/wiki/File:Leap_Year_Algorithm.png
+fn is_leap_year(year: u32) -> bool {
+    (year % 4 == 0 && year % 100 != 0)
+    ||
+    year % 400 == 0
+}
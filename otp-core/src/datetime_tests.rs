@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod calendar {
+    use crate::datetime::Datetime;
+
+    fn datetime(year: u32, month: u8, date: u8, hours: u8, minutes: u8, seconds: u8) -> Datetime {
+        Datetime { seconds, minutes, hours, date, month, year }
+    }
+
+    fn assert_date(timestamp: u64, year: u32, month: u8, date: u8) {
+        let datetime = Datetime::from_timestamp(timestamp);
+        assert_eq!((datetime.year, datetime.month, datetime.date), (year, month, date));
+    }
+
+    #[test]
+    fn epoch() {
+        assert_eq!(datetime(1970, 1, 1, 0, 0, 0).unix_timestamp(), 0);
+        assert_date(0, 1970, 1, 1);
+    }
+
+    #[test]
+    fn known_timestamps() {
+        assert_eq!(datetime(2001, 9, 9, 1, 46, 40).unix_timestamp(), 1_000_000_000);
+        assert_eq!(datetime(2023, 11, 14, 22, 13, 20).unix_timestamp(), 1_700_000_000);
+        assert_eq!(datetime(2033, 5, 18, 3, 33, 20).unix_timestamp(), 2_000_000_000);
+
+        let datetime = Datetime::from_timestamp(1_700_000_000);
+        assert_eq!((datetime.hours, datetime.minutes, datetime.seconds), (22, 13, 20));
+        assert_date(1_700_000_000, 2023, 11, 14);
+    }
+
+    #[test]
+    fn month_and_year_ends() {
+        assert_date(2_678_399, 1970, 1, 31);
+        assert_date(2_678_400, 1970, 2, 1);
+        assert_date(946_684_799, 1999, 12, 31);
+        assert_date(946_684_800, 2000, 1, 1);
+        assert_date(978_220_800, 2000, 12, 31);
+    }
+
+    #[test]
+    fn leap_days() {
+        // 2000 is a leap year, 2100 is not
+        assert_date(951_782_400, 2000, 2, 29);
+        assert_date(951_868_800, 2000, 3, 1);
+        assert_date(1_709_164_800, 2024, 2, 29);
+        assert_date(4_107_542_400, 2100, 3, 1);
+        assert_eq!(datetime(2024, 2, 29, 0, 0, 0).unix_timestamp(), 1_709_164_800);
+    }
+
+    #[test]
+    fn weekdays() {
+        // 1970 started on a Thursday, and 2000 on a Saturday
+        assert_eq!(datetime(1970, 1, 1, 0, 0, 0).weekday(), 4);
+        assert_eq!(datetime(2000, 1, 1, 0, 0, 0).weekday(), 6);
+        assert_eq!(datetime(2023, 11, 13, 23, 59, 59).weekday(), 1);
+        assert_eq!(datetime(2023, 11, 19, 12, 0, 0).weekday(), 7);
+        assert_eq!(datetime(2024, 2, 29, 0, 0, 0).weekday(), 4);
+    }
+
+    #[test]
+    fn round_trip_every_day() {
+        for day in 0..(130 * 365) {
+            let timestamp = day * 86_400 + 45_296;
+            assert_eq!(Datetime::from_timestamp(timestamp).unix_timestamp(), timestamp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod ds3231_registers {
+    use crate::datetime::Datetime;
+
+    #[test]
+    fn military_time() {
+        // 23:59:58 on 31/12/2099, century bit set
+        let datetime = Datetime::from_bytes([0x58, 0x59, 0x23, 0x04, 0x31, 0x92, 0x99]);
+        assert_eq!(datetime.year, 2099);
+        assert_eq!(datetime.month, 12);
+        assert_eq!(datetime.date, 31);
+        assert_eq!((datetime.hours, datetime.minutes, datetime.seconds), (23, 59, 58));
+    }
+
+    #[test]
+    fn twelve_hour_time() {
+        // 12-hour mode is bit 6, PM is bit 5
+        let pm = Datetime::from_bytes([0x00, 0x30, 0b0110_0001, 0x01, 0x01, 0x01, 0x70]);
+        assert_eq!(pm.hours, 13);
+        let am = Datetime::from_bytes([0x00, 0x30, 0b0101_0001, 0x01, 0x01, 0x01, 0x70]);
+        assert_eq!(am.hours, 11);
+        assert_eq!(am.year, 1970);
+        let midnight = Datetime::from_bytes([0x00, 0x00, 0b0101_0010, 0x01, 0x01, 0x01, 0x70]);
+        assert_eq!(midnight.hours, 0);
+        let noon = Datetime::from_bytes([0x00, 0x00, 0b0111_0010, 0x01, 0x01, 0x01, 0x70]);
+        assert_eq!(noon.hours, 12);
+    }
+
+    #[test]
+    fn to_bytes_round_trip() {
+        let bytes = Datetime::from_timestamp(1_700_000_000).to_bytes();
+        assert_eq!(bytes, [0x00, 0x20, 0x13, 0x22, 0x02, 0x14, 0x91, 0x23]);
+
+        let mut registers = [0_u8; 7];
+        registers.copy_from_slice(&bytes[1..8]);
+        assert_eq!(Datetime::from_bytes(registers).unix_timestamp(), 1_700_000_000);
+    }
+}
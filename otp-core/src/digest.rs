@@ -0,0 +1,23 @@
+// Streaming hash interface shared by the SHA implementations, so HMAC and the
+// OTP algorithms only have to be written once.
+pub trait Digest: Clone {
+    // Size of one compression block in bytes
+    const BLOCK_SIZE: usize;
+    // Size of the finished digest in bytes
+    const OUTPUT_SIZE: usize;
+
+    type Output: AsRef<[u8]> + Copy;
+
+    fn new() -> Self;
+    fn update(&mut self, message: &[u8]);
+    fn finalize(self) -> Self::Output;
+}
+
+// Largest `BLOCK_SIZE` of any implemented digest (SHA-512)
+pub const MAX_BLOCK_SIZE: usize = 128;
+
+pub fn gen_digest<D: Digest>(message: &[u8]) -> D::Output {
+    let mut hasher = D::new();
+    hasher.update(message);
+    hasher.finalize()
+}
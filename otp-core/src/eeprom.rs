@@ -0,0 +1,454 @@
+// The layout of the records kept in the 4KB EEPROM, on top of any storage that can read and
+// write it. On the Arduino that is the AT24C32 behind `rtc::Rtc`.
+use ufmt::derive::uDebug;
+use ufmt::uDebug;
+
+use crate::byte_helper;
+use crate::chalresp::SECRET_SIZE;
+use crate::clocklog::{Authorization, ClockChange, Drift, Log, Source};
+use crate::counter::{counter_record, latest_counter_record, parse_counter_record};
+use crate::pin::PIN_CONFIG_SIZE;
+use crate::slot::SLOT_SIZE;
+use crate::wrap::{Record, Seal, SEAL_PAGE_SIZE};
+
+pub const PAGE_SIZE: usize = 32;
+
+// Key is stored starting at address 0x0000 of the RTC EEPROM
+// 0x00_00 => length: u8
+// 0x00_20..0x01_20 => key_byte: u8
+//   (start key at 0x00_20 to ensure we only write within 32-byte page boundaries)
+const KEY_LENGTH_ADDRESS: u16 = 0x00_00;
+const KEY_ADDRESS: u16 = 0x00_20;
+
+// TOTP settings are stored in the page after the key
+// 0x01_20..0x01_28 => period: u64
+// 0x01_28..0x01_30 => T0: u64
+// An erased EEPROM reads back as 0xff, which is treated as "not configured"
+const TOTP_CONFIG_ADDRESS: u16 = 0x01_20;
+
+// The HOTP counter is kept in two alternating records at 0x01_40 and 0x01_60
+// (see `counter` for the record format).
+// Key slots keep their own pair of records from 0x08_00, 64 bytes per slot.
+const COUNTER_ADDRESS: u16 = 0x01_40;
+const SLOT_COUNTER_ADDRESS: u16 = 0x08_00;
+
+// The challenge-response secret has its own page, apart from the OTP keys
+// 0x01_80 => secret length: u8 (0xff when not set)
+// 0x01_81 => variable-length challenges: u8 (0 for fixed 64-byte challenges)
+// 0x01_82..0x01_a0 => secret: [u8; 30]
+const CHALRESP_ADDRESS: u16 = 0x01_80;
+
+// Key slots are stored from 0x02_00 to 0x08_00, 128 bytes each (see `slot::Slot` for the layout)
+pub const SLOT_COUNT: usize = 12;
+const SLOT_TABLE_ADDRESS: u16 = 0x02_00;
+
+// The PIN verifier is stored in two pages from 0x0b_00 (see `pin::PinConfig` for the layout)
+// Failed PIN attempts are kept in two alternating records like the HOTP counter,
+// at 0x0b_40 and 0x0b_60
+pub const PIN_CONFIG_ADDRESS: u16 = 0x0b_00;
+const FAILED_ATTEMPTS_ADDRESS: u16 = 0x0b_40;
+
+// The nonce counter for sealing secrets is kept in two alternating records like the HOTP
+// counter, at 0x0b_80 and 0x0b_a0. Seals follow, one page per record (see `wrap::Seal`):
+// 0x0b_c0 => saved key
+// 0x0b_e0 => challenge-response secret
+// 0x0c_00..0x0d_80 => key slots
+const NONCE_COUNTER_ADDRESS: u16 = 0x0b_80;
+const SEAL_TABLE_ADDRESS: u16 = 0x0b_c0;
+
+// The clock log takes the last 16 pages, from 0x0e_00, and authorized changes are logged in the
+// 4 pages before it, from 0x0d_80 (see `clocklog::ClockChange` for the layout)
+pub const CLOCK_LOG_SIZE: usize = 16;
+pub const AUTHORIZED_LOG_SIZE: usize = 4;
+const CLOCK_LOG_ADDRESS: u16 = 0x0e_00;
+const AUTHORIZED_LOG_ADDRESS: u16 = 0x0d_80;
+
+// Return: the addresses of both counter records for a slot, or for the saved key if `slot` is None
+fn counter_addresses(slot: Option<usize>) -> [u16; 2] {
+    let address = match slot {
+        Some(index) => SLOT_COUNTER_ADDRESS + index as u16 * 64,
+        None => COUNTER_ADDRESS,
+    };
+
+    [address, address + 32]
+}
+
+fn slot_address(index: usize) -> u16 {
+    SLOT_TABLE_ADDRESS + (index * SLOT_SIZE) as u16
+}
+
+fn seal_address(record: Record) -> u16 {
+    let page = match record {
+        Record::Key => 0,
+        Record::ChallengeResponse => 1,
+        Record::Slot(index) => 2 + index as u16,
+    };
+
+    SEAL_TABLE_ADDRESS + page * SEAL_PAGE_SIZE as u16
+}
+
+// Everything from the PIN verifier to the end, i.e. the failed attempts, the nonce counter, the
+// seals and both clock logs, is only written by the firmware while a PIN is set, so the raw
+// `write` command can't undo the PIN or the clock log
+const PROTECTED_ADDRESS: u16 = PIN_CONFIG_ADDRESS;
+
+pub fn is_protected(address: [u8; 2]) -> bool {
+    u16::from_be_bytes(address) >= PROTECTED_ADDRESS
+}
+
+pub fn clock_log_size(log: Log) -> usize {
+    match log {
+        Log::Small => CLOCK_LOG_SIZE,
+        Log::Authorized => AUTHORIZED_LOG_SIZE,
+    }
+}
+
+fn clock_log_address(log: Log, index: usize) -> u16 {
+    let address = match log {
+        Log::Small => CLOCK_LOG_ADDRESS,
+        Log::Authorized => AUTHORIZED_LOG_ADDRESS,
+    };
+
+    address + (index * PAGE_SIZE) as u16
+}
+
+// The address is not on a page boundary, or the slot does not exist
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub struct InvalidAddress;
+
+pub trait Storage {
+    type Error: uDebug + From<InvalidAddress>;
+
+    // Read from `address` onwards, continuing across pages
+    fn read_eeprom(&mut self, address: [u8; 2], buffer: &mut [u8]) -> Result<(), Self::Error>;
+
+    // These may return before the EEPROM has finished writing, see `wait_for_write`.
+    // Pages must start on a 32-byte boundary, or the write fails with `InvalidAddress`
+    fn write_byte_eeprom(&mut self, address: [u8; 2], input: u8) -> Result<(), Self::Error>;
+    fn write_page_eeprom(&mut self, address: [u8; 2], input: [u8; PAGE_SIZE]) -> Result<(), Self::Error>;
+
+    // Wait for the last write to finish
+    fn wait_for_write(&mut self);
+
+    // Read a single byte from the EEPROM
+    fn read_byte_eeprom(&mut self, address: [u8; 2]) -> Result<[u8; 1], Self::Error> {
+        let mut buffer = [0_u8; 1];
+        self.read_eeprom(address, &mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    // Read a 32-byte page from the EEPROM
+    fn read_page_eeprom(&mut self, address: [u8; 2]) -> Result<[u8; PAGE_SIZE], Self::Error> {
+        let mut buffer = [0_u8; PAGE_SIZE];
+        self.read_eeprom(address, &mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    fn read_key_eeprom(&mut self) -> Result<(usize, [u8; 256]), Self::Error> {
+        let mut length = [0_u8; 1];
+        self.read_eeprom(byte_helper::u16_to_bytes(KEY_LENGTH_ADDRESS), &mut length)?;
+
+        let mut key = [0_u8; 256];
+        self.read_eeprom(byte_helper::u16_to_bytes(KEY_ADDRESS), &mut key)?;
+
+        Ok((length[0] as usize, key))
+    }
+
+    fn write_key_eeprom(&mut self, length: usize, key: [u8; 256], seal: Option<Seal>) -> Result<(), Self::Error> {
+        write_sealed_eeprom(self, Record::Key, seal, |storage| write_key_pages(storage, length, key))
+    }
+
+    fn read_totp_config_eeprom(&mut self) -> Result<Option<(u64, u64)>, Self::Error> {
+        let page = self.read_page_eeprom(byte_helper::u16_to_bytes(TOTP_CONFIG_ADDRESS))?;
+
+        let mut period_bytes = [0_u8; 8];
+        period_bytes.copy_from_slice(&page[0..8]);
+        let mut t0_bytes = [0_u8; 8];
+        t0_bytes.copy_from_slice(&page[8..16]);
+
+        let period = byte_helper::bytes_to_u64(period_bytes);
+        let t0 = byte_helper::bytes_to_u64(t0_bytes);
+        if period == 0 || period == u64::MAX || t0 == u64::MAX {
+            return Ok(None);
+        }
+
+        Ok(Some((period, t0)))
+    }
+
+    fn write_totp_config_eeprom(&mut self, period: u64, t0: u64) -> Result<(), Self::Error> {
+        let mut page = [0xff_u8; 32];
+        page[0..8].copy_from_slice(&byte_helper::u64_to_bytes(period));
+        page[8..16].copy_from_slice(&byte_helper::u64_to_bytes(t0));
+
+        self.write_page_eeprom(byte_helper::u16_to_bytes(TOTP_CONFIG_ADDRESS), page)?;
+        self.wait_for_write();
+
+        Ok(())
+    }
+
+    fn read_counter_eeprom(&mut self, slot: Option<usize>) -> Result<Option<u64>, Self::Error> {
+        read_latest_counter(self, counter_addresses(slot))
+    }
+
+    fn write_counter_eeprom(&mut self, slot: Option<usize>, counter: u64) -> Result<(), Self::Error> {
+        write_counter_records(self, counter_addresses(slot), counter)
+    }
+
+    // Return: (variable-length challenges, secret length, secret), or None if no secret is set
+    #[allow(clippy::type_complexity)]
+    fn read_chalresp_eeprom(&mut self) -> Result<Option<(bool, usize, [u8; SECRET_SIZE])>, Self::Error> {
+        let page = self.read_page_eeprom(byte_helper::u16_to_bytes(CHALRESP_ADDRESS))?;
+
+        let length = page[0] as usize;
+        if length > SECRET_SIZE {
+            return Ok(None);
+        }
+        let mut secret = [0_u8; SECRET_SIZE];
+        secret.copy_from_slice(&page[2..32]);
+
+        Ok(Some((page[1] != 0, length, secret)))
+    }
+
+    fn write_chalresp_eeprom(&mut self, variable_length: bool, length: usize, secret: [u8; SECRET_SIZE], seal: Option<Seal>) -> Result<(), Self::Error> {
+        let mut page = [0_u8; 32];
+        page[0] = length as u8;
+        page[1] = variable_length as u8;
+        page[2..32].copy_from_slice(&secret);
+
+        write_sealed_eeprom(self, Record::ChallengeResponse, seal, |storage| {
+            storage.write_page_eeprom(byte_helper::u16_to_bytes(CHALRESP_ADDRESS), page)?;
+            storage.wait_for_write();
+            Ok(())
+        })
+    }
+
+    fn read_slot_eeprom(&mut self, index: usize) -> Result<[u8; SLOT_SIZE], Self::Error> {
+        if index >= SLOT_COUNT {
+            return Err(InvalidAddress.into());
+        }
+
+        let mut slot = [0_u8; SLOT_SIZE];
+        self.read_eeprom(byte_helper::u16_to_bytes(slot_address(index)), &mut slot)?;
+
+        Ok(slot)
+    }
+
+    fn write_slot_eeprom(&mut self, index: usize, slot: [u8; SLOT_SIZE], seal: Option<Seal>) -> Result<(), Self::Error> {
+        if index >= SLOT_COUNT {
+            return Err(InvalidAddress.into());
+        }
+
+        write_sealed_eeprom(self, Record::Slot(index), seal, |storage| {
+            // Write the in-use marker page last, so a slot is never listed before its secret is complete
+            for (page_index, slot_page) in slot.chunks(32).enumerate().rev() {
+                let address = slot_address(index) + (page_index * 32) as u16;
+
+                let mut page = [0u8; 32];
+                page.copy_from_slice(slot_page);
+
+                storage.write_page_eeprom(byte_helper::u16_to_bytes(address), page)?;
+                storage.wait_for_write();
+            }
+
+            Ok(())
+        })
+    }
+
+    // Erase a slot, including its secret. The in-use marker page goes first.
+    fn erase_slot_eeprom(&mut self, index: usize) -> Result<(), Self::Error> {
+        if index >= SLOT_COUNT {
+            return Err(InvalidAddress.into());
+        }
+
+        for address in (slot_address(index)..slot_address(index + 1)).step_by(32) {
+            self.write_page_eeprom(byte_helper::u16_to_bytes(address), [0xff; 32])?;
+            self.wait_for_write();
+        }
+
+        write_seal_eeprom(self, Record::Slot(index), None)
+    }
+
+    fn read_pin_eeprom(&mut self) -> Result<[u8; PIN_CONFIG_SIZE], Self::Error> {
+        let mut config = [0_u8; PIN_CONFIG_SIZE];
+        self.read_eeprom(byte_helper::u16_to_bytes(PIN_CONFIG_ADDRESS), &mut config)?;
+
+        Ok(config)
+    }
+
+    fn write_pin_eeprom(&mut self, config: [u8; PIN_CONFIG_SIZE]) -> Result<(), Self::Error> {
+        for (address, config_page) in (PIN_CONFIG_ADDRESS..PIN_CONFIG_ADDRESS + PIN_CONFIG_SIZE as u16).step_by(32).zip(config.chunks(32)) {
+            let mut page = [0u8; 32];
+            page.copy_from_slice(config_page);
+
+            self.write_page_eeprom(byte_helper::u16_to_bytes(address), page)?;
+            self.wait_for_write();
+        }
+
+        Ok(())
+    }
+
+    fn read_failed_attempts_eeprom(&mut self) -> Result<u64, Self::Error> {
+        let addresses = [FAILED_ATTEMPTS_ADDRESS, FAILED_ATTEMPTS_ADDRESS + 32];
+        Ok(read_latest_counter(self, addresses)?.unwrap_or(0))
+    }
+
+    fn write_failed_attempts_eeprom(&mut self, failed_attempts: u64) -> Result<(), Self::Error> {
+        let addresses = [FAILED_ATTEMPTS_ADDRESS, FAILED_ATTEMPTS_ADDRESS + 32];
+        write_counter_records(self, addresses, failed_attempts)
+    }
+
+    // Reserve a nonce counter that has never been used, saving it before it is handed out
+    fn next_nonce_eeprom(&mut self) -> Result<u64, Self::Error> {
+        let addresses = [NONCE_COUNTER_ADDRESS, NONCE_COUNTER_ADDRESS + 32];
+        let counter = read_latest_counter(self, addresses)?.map_or(0, |counter| counter + 1);
+        write_counter_records(self, addresses, counter)?;
+
+        Ok(counter)
+    }
+
+    // Return: the entry at `index` in the clock log, or None if it is erased or torn
+    fn read_clock_change_eeprom(&mut self, log: Log, index: usize) -> Result<Option<ClockChange>, Self::Error> {
+        if index >= clock_log_size(log) {
+            return Err(InvalidAddress.into());
+        }
+
+        let page = self.read_page_eeprom(byte_helper::u16_to_bytes(clock_log_address(log, index)))?;
+
+        Ok(ClockChange::from_page(&page))
+    }
+
+    // Return: (index, entry) of the latest change in the clock log, or None if it is empty
+    fn latest_clock_change_eeprom(&mut self, log: Log) -> Result<Option<(usize, ClockChange)>, Self::Error> {
+        let mut latest: Option<(usize, ClockChange)> = None;
+        for index in 0..clock_log_size(log) {
+            if let Some(change) = self.read_clock_change_eeprom(log, index)? {
+                if latest.map_or(true, |(_, latest)| change.sequence > latest.sequence) {
+                    latest = Some((index, change));
+                }
+            }
+        }
+
+        Ok(latest)
+    }
+
+    // Return: the drift logged with the latest change, and the time the latest authorized change set
+    fn clock_drift_eeprom(&mut self) -> Result<Drift, Self::Error> {
+        let small = self.latest_clock_change_eeprom(Log::Small)?.map(|(_, change)| change);
+        let authorized = self.latest_clock_change_eeprom(Log::Authorized)?.map(|(_, change)| change);
+
+        let seconds = match (small, authorized) {
+            (Some(small), Some(authorized)) if small.sequence < authorized.sequence => 0,
+            (Some(small), _) => small.drift,
+            (None, _) => 0,
+        };
+
+        Ok(Drift { seconds, since: authorized.map(|authorized| authorized.new) })
+    }
+
+    // Add a change to its log, overwriting the oldest entry there once it is full
+    fn log_clock_change_eeprom(&mut self, previous: u64, new: u64, source: Source, authorization: Authorization, drift: i32) -> Result<(), Self::Error> {
+        let log = Log::of(authorization);
+        let mut sequence = 0;
+        for other in [Log::Small, Log::Authorized] {
+            if let Some((_, latest)) = self.latest_clock_change_eeprom(other)? {
+                sequence = sequence.max(latest.sequence + 1);
+            }
+        }
+        let index = match self.latest_clock_change_eeprom(log)? {
+            Some((index, _)) => (index + 1) % clock_log_size(log),
+            None => 0,
+        };
+        let change = ClockChange { sequence, previous, new, source, authorization, drift };
+
+        self.write_page_eeprom(byte_helper::u16_to_bytes(clock_log_address(log, index)), change.to_page())?;
+        self.wait_for_write();
+
+        Ok(())
+    }
+
+    // Return: None if the record is stored as plain text
+    fn read_seal_eeprom(&mut self, record: Record) -> Result<Option<Seal>, Self::Error> {
+        let page = self.read_page_eeprom(byte_helper::u16_to_bytes(seal_address(record)))?;
+
+        Ok(Seal::from_page(&page))
+    }
+}
+
+fn write_key_pages<S: Storage + ?Sized>(storage: &mut S, length: usize, key: [u8; 256]) -> Result<(), S::Error> {
+    storage.write_byte_eeprom(byte_helper::u16_to_bytes(KEY_LENGTH_ADDRESS), length as u8)?;
+    storage.wait_for_write();
+
+    // Write key in 32-byte pages from 0x00_20 to 0x01_20
+    for (address, key_page) in (KEY_ADDRESS..KEY_ADDRESS + 0x01_00).step_by(32).zip(key.chunks(32)) {
+        let address_bytes = byte_helper::u16_to_bytes(address);
+
+        let mut page = [0u8; 32];
+        page.copy_from_slice(key_page);
+
+        storage.write_page_eeprom(address_bytes, page)?;
+        storage.wait_for_write();
+    }
+
+    Ok(())
+}
+
+fn read_counter_records<S: Storage + ?Sized>(storage: &mut S, addresses: [u16; 2]) -> Result<[Option<u64>; 2], S::Error> {
+    let mut records = [None; 2];
+    for (record, address) in records.iter_mut().zip(addresses) {
+        *record = parse_counter_record(&storage.read_page_eeprom(byte_helper::u16_to_bytes(address))?);
+    }
+
+    Ok(records)
+}
+
+fn read_latest_counter<S: Storage + ?Sized>(storage: &mut S, addresses: [u16; 2]) -> Result<Option<u64>, S::Error> {
+    let records = read_counter_records(storage, addresses)?;
+
+    Ok(latest_counter_record(records).map(|(_, counter)| counter))
+}
+
+fn write_counter_records<S: Storage + ?Sized>(storage: &mut S, addresses: [u16; 2], counter: u64) -> Result<(), S::Error> {
+    let records = read_counter_records(storage, addresses)?;
+
+    // Overwrite the stale record first so the current one survives a failed write
+    let (current_index, current) = latest_counter_record(records).unwrap_or((1, 0));
+    let stale_index = 1 - current_index;
+    storage.write_page_eeprom(byte_helper::u16_to_bytes(addresses[stale_index]), counter_record(counter))?;
+    storage.wait_for_write();
+
+    // Moving the counter backwards has to replace the current record as well
+    if counter < current {
+        storage.write_page_eeprom(byte_helper::u16_to_bytes(addresses[current_index]), counter_record(counter))?;
+        storage.wait_for_write();
+    }
+
+    Ok(())
+}
+
+fn write_seal_eeprom<S: Storage + ?Sized>(storage: &mut S, record: Record, seal: Option<Seal>) -> Result<(), S::Error> {
+    let page = seal.map_or([0xff; SEAL_PAGE_SIZE], |seal| seal.to_page());
+    storage.write_page_eeprom(byte_helper::u16_to_bytes(seal_address(record)), page)?;
+    storage.wait_for_write();
+
+    Ok(())
+}
+
+// A seal is written before its sealed record and only cleared after the record is plain text
+// again, so a torn write fails to open instead of reading ciphertext as a secret
+fn write_sealed_eeprom<S: Storage + ?Sized, F>(storage: &mut S, record: Record, seal: Option<Seal>, write_record: F) -> Result<(), S::Error>
+where
+    F: FnOnce(&mut S) -> Result<(), S::Error>,
+{
+    match seal {
+        Some(_) => {
+            write_seal_eeprom(storage, record, seal)?;
+            write_record(storage)
+        },
+        None => {
+            write_record(storage)?;
+            write_seal_eeprom(storage, record, None)
+        },
+    }
+}
@@ -0,0 +1,56 @@
+#![no_std]
+// `progmem` reads text and tables back out of program memory with inline assembly on AVR
+#![cfg_attr(target_arch = "avr", feature(asm_experimental_arch))]
+// The digests and byte helpers follow the notation of their specs
+#![allow(clippy::identity_op, clippy::erasing_op, clippy::precedence, clippy::needless_range_loop, clippy::new_without_default)]
+
+mod sha1_tests;
+mod sha2_tests;
+mod otp_tests;
+mod byte_helper_tests;
+mod otpauth_tests;
+mod counter_tests;
+mod datetime_tests;
+mod command_tests;
+mod slot_tests;
+mod ocra_tests;
+mod chalresp_tests;
+mod pin_tests;
+mod chacha20poly1305_tests;
+mod wrap_tests;
+mod rtc_tests;
+mod sim_tests;
+mod tty_tests;
+mod sync_tests;
+mod clocklog_tests;
+mod alarm_tests;
+mod sqw_tests;
+
+pub mod byte_helper;
+pub mod sha1;
+pub mod sha256;
+pub mod sha512;
+pub mod digest;
+pub mod otp;
+pub mod otpauth;
+pub mod slot;
+pub mod ocra;
+pub mod chalresp;
+pub mod pin;
+pub mod chacha20poly1305;
+pub mod wrap;
+pub mod datetime;
+pub mod counter;
+pub mod command;
+pub mod rtc;
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
+pub mod clock;
+pub mod eeprom;
+pub mod text;
+pub mod progmem;
+pub mod tty;
+pub mod sync;
+pub mod clocklog;
+pub mod alarm;
+pub mod sqw;
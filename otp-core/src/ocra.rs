@@ -0,0 +1,256 @@
+use ufmt::derive::uDebug;
+
+use crate::byte_helper;
+use crate::digest::{self, Digest};
+use crate::otp::{self, Algorithm, Hmac};
+use crate::sha1::Sha1;
+use crate::sha256::Sha256;
+use crate::sha512::Sha512;
+
+// OCRA - OATH Challenge-Response Algorithm (RFC 6287)
+// OCRASuite: OCRA-1:HOTP-<SHA1|SHA256|SHA512>-<digits>:<DataInput>
+// DataInput: [C-]Q<A|N|H><length>[-P<SHA1|SHA256|SHA512>][-S<length>][-T<step><S|M|H>]
+
+#[derive(Debug, uDebug)]
+pub enum OcraError {
+    InvalidSuite,
+    InvalidChallenge,
+    MissingPin,
+    InvalidSession,
+    ProcessingError,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ChallengeFormat {
+    Alphanumeric,
+    Numeric,
+    Hex,
+}
+
+pub struct OcraSuite<'a> {
+    // The suite string itself is the start of the DataInput
+    pub suite: &'a [u8],
+    pub algorithm: Algorithm,
+    pub digits: u8,
+    pub counter: bool,
+    pub challenge_format: ChallengeFormat,
+    pub challenge_length: usize,
+    pub pin_algorithm: Option<Algorithm>,
+    // 0 when the suite has no session information
+    pub session_length: usize,
+    // Seconds per time step, 0 when the suite has no timestamp
+    pub time_step: u64,
+}
+
+pub struct OcraInput<'a> {
+    pub counter: u64,
+    pub challenge: &'a [u8],
+    // The PIN itself - it is hashed with the suite's PIN algorithm
+    pub pin: &'a [u8],
+    pub session: &'a [u8],
+    // Unix time in seconds
+    pub timestamp: u64,
+}
+
+// The challenge question always takes 128 bytes of the DataInput
+const CHALLENGE_SIZE: usize = 128;
+const MAX_CHALLENGE_LENGTH: usize = CHALLENGE_SIZE;
+const MAX_SESSION_LENGTH: usize = 512;
+
+pub fn parse_suite(suite: &[u8]) -> Result<OcraSuite<'_>, OcraError> {
+    let mut fields = suite.split(|byte| *byte == b':');
+    let (version, crypto_function, data_input) = match (fields.next(), fields.next(), fields.next(), fields.next()) {
+        (Some(version), Some(crypto_function), Some(data_input), None) => (version, crypto_function, data_input),
+        _ => return Err(OcraError::InvalidSuite),
+    };
+    if !version.eq_ignore_ascii_case(b"OCRA-1") {
+        return Err(OcraError::InvalidSuite);
+    }
+
+    // CryptoFunction: HOTP-<hash>-<digits>
+    // Truncation to 0 digits (the full HMAC) is allowed by the RFC but not supported here
+    let mut crypto_fields = crypto_function.split(|byte| *byte == b'-');
+    let (algorithm, digits) = match (crypto_fields.next(), crypto_fields.next(), crypto_fields.next(), crypto_fields.next()) {
+        (Some(hotp), Some(hash), Some(digits), None) if hotp.eq_ignore_ascii_case(b"HOTP") => {
+            let algorithm = Algorithm::from_name(hash).ok_or(OcraError::InvalidSuite)?;
+            match byte_helper::parse_u64(digits) {
+                Ok(digits @ 4..=10) => (algorithm, digits as u8),
+                _ => return Err(OcraError::InvalidSuite),
+            }
+        },
+        _ => return Err(OcraError::InvalidSuite),
+    };
+
+    let mut parsed = OcraSuite {
+        suite, algorithm, digits,
+        counter: false,
+        challenge_format: ChallengeFormat::Numeric,
+        challenge_length: 0,
+        pin_algorithm: None,
+        session_length: 0,
+        time_step: 0,
+    };
+
+    for (i, field) in data_input.split(|byte| *byte == b'-').enumerate() {
+        let (kind, value) = match field.split_first() {
+            Some((kind, value)) => (kind.to_ascii_uppercase(), value),
+            None => return Err(OcraError::InvalidSuite),
+        };
+        match kind {
+            b'C' if i == 0 && value.is_empty() => parsed.counter = true,
+            b'Q' if parsed.challenge_length == 0 => {
+                let (format, length) = value.split_first().ok_or(OcraError::InvalidSuite)?;
+                parsed.challenge_format = match format.to_ascii_uppercase() {
+                    b'A' => ChallengeFormat::Alphanumeric,
+                    b'N' => ChallengeFormat::Numeric,
+                    b'H' => ChallengeFormat::Hex,
+                    _ => return Err(OcraError::InvalidSuite),
+                };
+                parsed.challenge_length = match byte_helper::parse_u64(length) {
+                    Ok(length @ 4..=64) => length as usize,
+                    _ => return Err(OcraError::InvalidSuite),
+                };
+            },
+            b'P' => {
+                parsed.pin_algorithm = Some(Algorithm::from_name(value).ok_or(OcraError::InvalidSuite)?);
+            },
+            b'S' => {
+                parsed.session_length = match byte_helper::parse_u64(value) {
+                    Ok(length @ 1..=512) => length as usize,
+                    _ => return Err(OcraError::InvalidSuite),
+                };
+            },
+            b'T' => {
+                let (unit, steps) = value.split_last().ok_or(OcraError::InvalidSuite)?;
+                let seconds = match unit.to_ascii_uppercase() {
+                    b'S' => 1,
+                    b'M' => 60,
+                    b'H' => 3_600,
+                    _ => return Err(OcraError::InvalidSuite),
+                };
+                parsed.time_step = match byte_helper::parse_u64(steps) {
+                    Ok(steps @ 1..=59) => steps * seconds,
+                    _ => return Err(OcraError::InvalidSuite),
+                };
+            },
+            _ => return Err(OcraError::InvalidSuite),
+        }
+    }
+
+    // The challenge question is the only mandatory input
+    if parsed.challenge_length == 0 {
+        return Err(OcraError::InvalidSuite);
+    }
+
+    Ok(parsed)
+}
+
+pub fn gen_ocra(suite: &OcraSuite, key: &[u8], input: &OcraInput) -> Result<u32, OcraError> {
+    match suite.algorithm {
+        Algorithm::Sha1 => gen_ocra_with::<Sha1>(suite, key, input),
+        Algorithm::Sha256 => gen_ocra_with::<Sha256>(suite, key, input),
+        Algorithm::Sha512 => gen_ocra_with::<Sha512>(suite, key, input),
+    }
+}
+
+// The DataInput is fed to the HMAC as it is built, so the (up to 700-byte) message never
+// has to fit in RAM at once
+fn gen_ocra_with<D: Digest>(suite: &OcraSuite, key: &[u8], input: &OcraInput) -> Result<u32, OcraError> {
+    let mut hmac = Hmac::<D>::new(key);
+
+    // OCRASuite, followed by a 0x00 separator
+    hmac.update(suite.suite);
+    hmac.update(&[0x00]);
+
+    // C - 8-byte counter
+    if suite.counter {
+        hmac.update(&byte_helper::u64_to_bytes(input.counter));
+    }
+
+    // Q - 128-byte challenge question
+    hmac.update(&encode_challenge(suite, input.challenge)?);
+
+    // P - hash of the PIN
+    if let Some(pin_algorithm) = suite.pin_algorithm {
+        if input.pin.is_empty() {
+            return Err(OcraError::MissingPin);
+        }
+        let mut pin_hash = [0_u8; 64];
+        let length = match pin_algorithm {
+            Algorithm::Sha1 => copy_digest::<Sha1>(input.pin, &mut pin_hash),
+            Algorithm::Sha256 => copy_digest::<Sha256>(input.pin, &mut pin_hash),
+            Algorithm::Sha512 => copy_digest::<Sha512>(input.pin, &mut pin_hash),
+        };
+        hmac.update(&pin_hash[0..length]);
+    }
+
+    // S - session information, right-aligned and padded with 0x00
+    if suite.session_length > 0 {
+        if input.session.len() > suite.session_length || suite.session_length > MAX_SESSION_LENGTH {
+            return Err(OcraError::InvalidSession);
+        }
+        for _ in input.session.len()..suite.session_length {
+            hmac.update(&[0x00]);
+        }
+        hmac.update(input.session);
+    }
+
+    // T - 8-byte count of time steps
+    if let Some(steps) = input.timestamp.checked_div(suite.time_step) {
+        hmac.update(&byte_helper::u64_to_bytes(steps));
+    }
+
+    let truncated_value = otp::truncate(hmac.finalize().as_ref()).map_err(|_| OcraError::ProcessingError)?;
+
+    Ok((truncated_value as u64 % 10u64.pow(suite.digits as u32)) as u32)
+}
+
+// Encode the challenge question into its field, left-aligned and padded with 0x00
+// Numeric challenges are converted to hexadecimal first, as in the RFC's reference code.
+// Mutual challenge-response concatenates the client and server questions, so only the
+// size of the field limits the length, not the suite.
+pub fn encode_challenge(suite: &OcraSuite, challenge: &[u8]) -> Result<[u8; CHALLENGE_SIZE], OcraError> {
+    if challenge.is_empty() || challenge.len() > MAX_CHALLENGE_LENGTH {
+        return Err(OcraError::InvalidChallenge);
+    }
+
+    let mut field = [0_u8; CHALLENGE_SIZE];
+    match suite.challenge_format {
+        ChallengeFormat::Alphanumeric => {
+            if !challenge.iter().all(u8::is_ascii_graphic) {
+                return Err(OcraError::InvalidChallenge);
+            }
+            field[0..challenge.len()].copy_from_slice(challenge);
+        },
+        ChallengeFormat::Hex => {
+            for (i, character) in challenge.iter().enumerate() {
+                if !character.is_ascii_hexdigit() {
+                    return Err(OcraError::InvalidChallenge);
+                }
+                set_nibble(&mut field, i, byte_helper::hex_to_byte([b'0', *character]));
+            }
+        },
+        ChallengeFormat::Numeric => {
+            // Challenges beyond 19 digits don't fit in a u64
+            let value = byte_helper::parse_u64(challenge).map_err(|_| OcraError::InvalidChallenge)?;
+            let nibbles = ((64 - value.leading_zeros() as usize + 3) / 4).max(1);
+            for i in 0..nibbles {
+                set_nibble(&mut field, i, ((value >> (4 * (nibbles - 1 - i))) & 0xf) as u8);
+            }
+        },
+    }
+
+    Ok(field)
+}
+
+fn set_nibble(field: &mut [u8], index: usize, nibble: u8) {
+    match index % 2 {
+        0 => field[index / 2] |= nibble << 4,
+        _ => field[index / 2] |= nibble,
+    }
+}
+
+fn copy_digest<D: Digest>(message: &[u8], output: &mut [u8]) -> usize {
+    output[0..D::OUTPUT_SIZE].copy_from_slice(digest::gen_digest::<D>(message).as_ref());
+    D::OUTPUT_SIZE
+}
@@ -0,0 +1,111 @@
+#[cfg(test)]
+fn key(length: usize) -> [u8; 64] {
+    // RFC 6287 Appendix C keys are "1234567890" repeated
+    let mut key = [0_u8; 64];
+    for (i, byte) in key.iter_mut().enumerate().take(length) {
+        *byte = b"1234567890"[i % 10];
+    }
+    key
+}
+
+#[cfg(test)]
+mod rfc_6287_vectors {
+    use super::key;
+    use crate::ocra::{self, OcraInput};
+
+    fn input<'a>(counter: u64, challenge: &'a [u8], timestamp: u64) -> OcraInput<'a> {
+        OcraInput { counter, challenge, pin: b"1234", session: &[], timestamp }
+    }
+
+    #[test]
+    fn one_way_sha1() {
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA1-6:QN08").unwrap();
+        let key = key(20);
+        let validators = [237653, 243178, 653583, 740991, 608993, 388898, 816933, 224598, 750600, 294470];
+        for (i, validator) in validators.iter().enumerate() {
+            let challenge = [b'0' + i as u8; 8];
+            assert_eq!(ocra::gen_ocra(&suite, &key[0..20], &input(0, &challenge, 0)).unwrap(), *validator);
+        }
+    }
+
+    #[test]
+    fn counter_and_pin_sha256() {
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA256-8:C-QN08-PSHA1").unwrap();
+        let key = key(32);
+        let validators = [65347737, 86775851, 78192410, 71565254, 10104329, 65983500, 70069104, 91771096, 75011558, 8522129];
+        for (counter, validator) in validators.iter().enumerate() {
+            assert_eq!(ocra::gen_ocra(&suite, &key[0..32], &input(counter as u64, b"12345678", 0)).unwrap(), *validator);
+        }
+    }
+
+    #[test]
+    fn counter_sha512() {
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA512-8:C-QN08").unwrap();
+        let key = key(64);
+        let validators = [7016083, 63947962, 70123924, 25341727, 33203315, 34205738, 44343969, 51946085, 20403879, 31409299];
+        for (counter, validator) in validators.iter().enumerate() {
+            let challenge = [b'0' + counter as u8; 8];
+            assert_eq!(ocra::gen_ocra(&suite, &key, &input(counter as u64, &challenge, 0)).unwrap(), *validator);
+        }
+    }
+
+    #[test]
+    fn timestamp_sha512() {
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA512-8:QN08-T1M").unwrap();
+        let key = key(64);
+        // T = 0x132d0b6 minutes
+        let timestamp = 0x132d0b6 * 60 + 59;
+        let validators = [95209754, 55907591, 22048402, 24218844, 36209546];
+        for (i, validator) in validators.iter().enumerate() {
+            let challenge = [b'0' + i as u8; 8];
+            assert_eq!(ocra::gen_ocra(&suite, &key, &input(0, &challenge, timestamp)).unwrap(), *validator);
+        }
+    }
+
+    #[test]
+    fn mutual_and_signature() {
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA256-8:QA08").unwrap();
+        assert_eq!(ocra::gen_ocra(&suite, &key(32)[0..32], &input(0, b"CLI22220SRV11110", 0)).unwrap(), 28247970);
+
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA512-8:QA08").unwrap();
+        assert_eq!(ocra::gen_ocra(&suite, &key(64), &input(0, b"SRV11110CLI22220", 0)).unwrap(), 5872105);
+
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA512-8:QA08-PSHA1").unwrap();
+        assert_eq!(ocra::gen_ocra(&suite, &key(64), &input(0, b"SRV11110CLI22220", 0)).unwrap(), 18806276);
+
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA256-8:QA08").unwrap();
+        assert_eq!(ocra::gen_ocra(&suite, &key(32)[0..32], &input(0, b"SIG10000", 0)).unwrap(), 53095496);
+
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA512-8:QA10-T1M").unwrap();
+        assert_eq!(ocra::gen_ocra(&suite, &key(64), &input(0, b"SIG1000000", 0x132d0b6 * 60)).unwrap(), 77537423);
+    }
+}
+
+#[cfg(test)]
+mod parsing_suites {
+    use crate::ocra::{self, ChallengeFormat};
+    use crate::otp::Algorithm;
+
+    #[test]
+    fn all_fields() {
+        let suite = ocra::parse_suite(b"OCRA-1:HOTP-SHA256-8:C-QH40-PSHA512-S064-T30S").unwrap();
+        assert!(suite.algorithm == Algorithm::Sha256);
+        assert_eq!(suite.digits, 8);
+        assert!(suite.counter);
+        assert!(suite.challenge_format == ChallengeFormat::Hex);
+        assert_eq!(suite.challenge_length, 40);
+        assert!(suite.pin_algorithm == Some(Algorithm::Sha512));
+        assert_eq!(suite.session_length, 64);
+        assert_eq!(suite.time_step, 30);
+    }
+
+    #[test]
+    fn invalid_suites() {
+        assert!(ocra::parse_suite(b"OCRA-2:HOTP-SHA1-6:QN08").is_err());
+        assert!(ocra::parse_suite(b"OCRA-1:HOTP-MD5-6:QN08").is_err());
+        assert!(ocra::parse_suite(b"OCRA-1:HOTP-SHA1-0:QN08").is_err());
+        assert!(ocra::parse_suite(b"OCRA-1:HOTP-SHA1-6:C").is_err());
+        assert!(ocra::parse_suite(b"OCRA-1:HOTP-SHA1-6:QN08-C").is_err());
+        assert!(ocra::parse_suite(b"OCRA-1:HOTP-SHA1-6:QX08").is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use ufmt::derive::uDebug;
+
+use crate::byte_helper;
+use crate::digest::{self, Digest, MAX_BLOCK_SIZE};
+use crate::sha1::Sha1;
+use crate::sha256::Sha256;
+use crate::sha512::Sha512;
+
+#[derive(Debug, uDebug)]
+pub enum OtpError {
+    InputSizeError,
+    ProcessingError,
+}
+
+// Hash algorithms allowed for HOTP/TOTP by RFC 6238
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    // Parse an algorithm name, ignoring case and an optional dash (e.g. `sha256` or `SHA-256`)
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        let mut normalized = [0_u8; 6];
+        let mut length = 0;
+        for byte in name.iter().filter(|byte| **byte != b'-') {
+            if length == normalized.len() {
+                return None;
+            }
+            normalized[length] = byte.to_ascii_uppercase();
+            length += 1;
+        }
+
+        match &normalized[0..length] {
+            b"SHA1" => Some(Algorithm::Sha1),
+            b"SHA256" => Some(Algorithm::Sha256),
+            b"SHA512" => Some(Algorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+        }
+    }
+
+    // Truncated 31-bit HOTP value, before it is turned into a code
+    pub fn hotp_value(&self, key: &[u8], counter: u64) -> Result<u32, OtpError> {
+        match self {
+            Algorithm::Sha1 => gen_hotp_value::<Sha1>(key, counter),
+            Algorithm::Sha256 => gen_hotp_value::<Sha256>(key, counter),
+            Algorithm::Sha512 => gen_hotp_value::<Sha512>(key, counter),
+        }
+    }
+
+    // Write the HOTP code for `counter` in the given format
+    // Return: number of characters written to `output`
+    pub fn code(&self, key: &[u8], counter: u64, digits: u8, format: Format, output: &mut [u8; MAX_CODE_LENGTH]) -> Result<usize, OtpError> {
+        format_code(self.hotp_value(key, counter)?, digits, format, output)
+    }
+
+    pub fn hotp(&self, key: &[u8], counter: u64, digits: u32) -> Result<u32, OtpError> {
+        match self {
+            Algorithm::Sha1 => gen_hotp::<Sha1>(key, counter, digits),
+            Algorithm::Sha256 => gen_hotp::<Sha256>(key, counter, digits),
+            Algorithm::Sha512 => gen_hotp::<Sha512>(key, counter, digits),
+        }
+    }
+
+    pub fn totp(&self, key: &[u8], timestamp: u64, t0: u64, period: u64, digits: u32) -> Result<u32, OtpError> {
+        match self {
+            Algorithm::Sha1 => gen_totp::<Sha1>(key, timestamp, t0, period, digits),
+            Algorithm::Sha256 => gen_totp::<Sha256>(key, timestamp, t0, period, digits),
+            Algorithm::Sha512 => gen_totp::<Sha512>(key, timestamp, t0, period, digits),
+        }
+    }
+}
+
+// How the truncated HOTP value is shown
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // `digits` decimal digits, as in RFC 4226
+    Decimal,
+    // Five characters from `STEAM_ALPHABET`, as used by Steam Guard
+    Steam,
+}
+
+impl Format {
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        if name.eq_ignore_ascii_case(b"decimal") {
+            Some(Format::Decimal)
+        }
+        else if name.eq_ignore_ascii_case(b"steam") {
+            Some(Format::Steam)
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Decimal => "decimal",
+            Format::Steam => "steam",
+        }
+    }
+}
+
+// A 31-bit value never has more than 10 digits
+pub const MAX_CODE_LENGTH: usize = 10;
+
+pub const STEAM_ALPHABET: &[u8; 26] = b"23456789BCDFGHJKMNPQRTVWXY";
+pub const STEAM_CODE_LENGTH: usize = 5;
+
+// Turn a truncated HOTP value into the characters of a code
+// Return: number of characters written to `output`
+pub fn format_code(value: u32, digits: u8, format: Format, output: &mut [u8; MAX_CODE_LENGTH]) -> Result<usize, OtpError> {
+    match format {
+        Format::Decimal => {
+            if digits == 0 || digits as usize > MAX_CODE_LENGTH {
+                return Err(OtpError::InputSizeError);
+            }
+
+            // Fill from the right to keep leading zeros
+            let mut value = value;
+            for character in output[0..digits as usize].iter_mut().rev() {
+                *character = b'0' + (value % 10) as u8;
+                value /= 10;
+            }
+            Ok(digits as usize)
+        },
+        Format::Steam => {
+            // Least significant character first
+            let mut value = value;
+            for character in output[0..STEAM_CODE_LENGTH].iter_mut() {
+                *character = STEAM_ALPHABET[(value % STEAM_ALPHABET.len() as u32) as usize];
+                value /= STEAM_ALPHABET.len() as u32;
+            }
+            Ok(STEAM_CODE_LENGTH)
+        },
+    }
+}
+
+// Incremental HMAC over any `Digest`. The key is only needed when creating it.
+#[derive(Clone)]
+pub struct Hmac<D: Digest> {
+    inner: D,
+    outer: D,
+}
+
+impl<D: Digest> Hmac<D> {
+    pub fn new(key: &[u8]) -> Self {
+        // Keys longer than a block are hashed first
+        let mut pad = [0_u8; MAX_BLOCK_SIZE];
+        if key.len() > D::BLOCK_SIZE {
+            pad[0..D::OUTPUT_SIZE].copy_from_slice(digest::gen_digest::<D>(key).as_ref());
+        }
+        else {
+            pad[0..key.len()].copy_from_slice(key);
+        }
+
+        // Turn the key into the inner pad, then the inner pad into the outer one, in the same
+        // block to save SRAM
+        for byte in pad.iter_mut() {
+            *byte ^= b'6';
+        }
+        let mut inner = D::new();
+        inner.update(&pad[0..D::BLOCK_SIZE]);
+        for byte in pad.iter_mut() {
+            *byte ^= b'6' ^ b'\\';
+        }
+        let mut outer = D::new();
+        outer.update(&pad[0..D::BLOCK_SIZE]);
+
+        Self { inner, outer }
+    }
+
+    pub fn update(&mut self, message: &[u8]) {
+        self.inner.update(message);
+    }
+
+    pub fn finalize(self) -> D::Output {
+        // Inner Hash
+        let inner_hash = self.inner.finalize();
+
+        // Outer Hash and Result
+        let mut outer = self.outer;
+        outer.update(inner_hash.as_ref());
+        outer.finalize()
+    }
+}
+
+pub fn gen_hmac<D: Digest>(key: &[u8], message: &[u8]) -> D::Output {
+    let mut hmac = Hmac::<D>::new(key);
+    hmac.update(message);
+    hmac.finalize()
+}
+
+// RFC 4226 Dynamic Truncation - returns the 31-bit value before it is reduced to digits
+pub fn truncate(hmac: &[u8]) -> Result<u32, OtpError> {
+    // 1. Determine offset based on lower 4-bits of the last byte
+    let offset = match hmac.last() {
+        Some(byte) => (*byte & 0x0f) as usize,
+        None => return Err(OtpError::ProcessingError),
+    };
+    // 2. Parse 4-bytes starting from the offset as a u32 and ignore the first bit
+    let mut offset_bytes = [0_u8; 4];
+    match hmac.get(offset..offset+4) {
+        Some(bytes) => offset_bytes.copy_from_slice(bytes),
+        None => return Err(OtpError::ProcessingError),
+    }
+
+    Ok(byte_helper::bytes_to_u32(offset_bytes) & 0x7fffffff)
+}
+
+// HOTP up to and including Dynamic Truncation, shared by every code format
+pub fn gen_hotp_value<D: Digest>(key: &[u8], counter: u64) -> Result<u32, OtpError> {
+    // Calculate HMAC(key, counter)
+    let hmac = gen_hmac::<D>(key, &byte_helper::u64_to_bytes(counter));
+
+    // Dynamic Truncation
+    truncate(hmac.as_ref())
+}
+
+pub fn gen_hotp<D: Digest>(key: &[u8], counter: u64, digits: u32) -> Result<u32, OtpError> {
+    // A 31-bit value never has more than 10 digits
+    if digits == 0 || digits > 10 {
+        return Err(OtpError::InputSizeError);
+    }
+
+    let truncated_value = gen_hotp_value::<D>(key, counter)?;
+
+    // Return final value with specified number of digits
+    Ok((truncated_value as u64 % 10u64.pow(digits)) as u32)
+}
+
+// RFC 6238 time step: T = (Current Unix time - T0) / X
+// Return: (counter value, seconds left until the next step)
+pub fn totp_counter(timestamp: u64, t0: u64, period: u64) -> Result<(u64, u64), OtpError> {
+    if period == 0 || timestamp < t0 {
+        return Err(OtpError::InputSizeError);
+    }
+
+    let elapsed = timestamp - t0;
+    Ok((elapsed / period, period - elapsed % period))
+}
+
+pub fn gen_totp<D: Digest>(key: &[u8], timestamp: u64, t0: u64, period: u64, digits: u32) -> Result<u32, OtpError> {
+    let (counter, _) = totp_counter(timestamp, t0, period)?;
+    gen_hotp::<D>(key, counter, digits)
+}
+
+// Compare two byte strings without exiting early on the first difference
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let difference = a.iter().zip(b).fold(0_u8, |difference, (x, y)| difference | (x ^ y));
+    difference == 0
+}
+
+// Check `code` against the TOTP steps within `window` steps either side of the current one.
+// Every step is always calculated, so the time taken does not depend on which one matched.
+// Return: the offset of the matching step
+#[allow(clippy::too_many_arguments)]
+pub fn verify_totp(algorithm: Algorithm, key: &[u8], timestamp: u64, t0: u64, period: u64, digits: u32, code: u32, window: u8) -> Result<Option<i64>, OtpError> {
+    let (counter, _) = totp_counter(timestamp, t0, period)?;
+
+    let mut matched = None;
+    for offset in -(window as i64)..=(window as i64) {
+        let step = match offset < 0 {
+            true => counter.checked_sub(offset.unsigned_abs()),
+            false => counter.checked_add(offset as u64),
+        };
+        let step = match step {
+            Some(step) => step,
+            None => continue,
+        };
+        let expected = algorithm.hotp(key, step, digits)?;
+        // Prefer the match closest to the current step
+        let closer = match matched {
+            Some(previous) => offset.abs() < i64::abs(previous),
+            None => true,
+        };
+        if constant_time_eq(&byte_helper::u32_to_bytes(expected), &byte_helper::u32_to_bytes(code)) && closer {
+            matched = Some(offset);
+        }
+    }
+
+    Ok(matched)
+}
+
+// Check `code` against the HOTP counters from `counter` to `counter + look_ahead`
+// Return: the matching counter value
+pub fn verify_hotp(algorithm: Algorithm, key: &[u8], counter: u64, digits: u32, code: u32, look_ahead: u8) -> Result<Option<u64>, OtpError> {
+    let mut matched = None;
+    for step in counter..=counter.saturating_add(look_ahead as u64) {
+        let expected = algorithm.hotp(key, step, digits)?;
+        // Keep the first match, without stopping the scan
+        if constant_time_eq(&byte_helper::u32_to_bytes(expected), &byte_helper::u32_to_bytes(code)) && matched.is_none() {
+            matched = Some(step);
+        }
+    }
+
+    Ok(matched)
+}
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod creating_hmac {
+    use crate::byte_helper;
+    use crate::otp;
+    use crate::sha1::Sha1;
+    use crate::sha256::Sha256;
+
+    #[test]
+    fn streamed_in_pieces() {
+        let key = b"Jefe";
+        let validator = byte_helper::hexstring_to_digest("effcdf6ae5eb2fa2d27416d5f184df9c259a7c79").unwrap();
+        let mut hmac = otp::Hmac::<Sha1>::new(key);
+        for piece in [&b"what do "[..], b"ya want ", b"for nothing?"] {
+            hmac.update(piece);
+        }
+        assert_eq!(hmac.finalize(), validator);
+    }
+
+    #[test]
+    fn matches_hash_specific_functions() {
+        let key = [0x0b_u8; 20];
+        let msg = b"Hi There";
+        assert_eq!(otp::gen_hmac::<Sha256>(&key, msg), crate::sha256::gen_sha256_hmac(&key, msg).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod creating_totp {
+    use crate::otp;
+    use crate::sha1::Sha1;
+    use crate::sha256::Sha256;
+    use crate::sha512::Sha512;
+
+    // RFC 6238 Appendix B test vectors (8 digits, 30 second step)
+    const TIMESTAMPS: [u64; 6] = [59, 1111111109, 1111111111, 1234567890, 2000000000, 20000000000];
+
+    #[test]
+    fn sha1() {
+        let key = b"12345678901234567890";
+        let validator: [u32; 6] = [94287082, 7081804, 14050471, 89005924, 69279037, 65353130];
+        for (timestamp, expected) in TIMESTAMPS.iter().zip(validator) {
+            let otp = otp::gen_totp::<Sha1>(key, *timestamp, 0, 30, 8).unwrap();
+            assert_eq!(otp, expected, "invalid OTP at timestamp {}", timestamp);
+        }
+    }
+
+    #[test]
+    fn sha256() {
+        let key = b"12345678901234567890123456789012";
+        let validator: [u32; 6] = [46119246, 68084774, 67062674, 91819424, 90698825, 77737706];
+        for (timestamp, expected) in TIMESTAMPS.iter().zip(validator) {
+            let otp = otp::gen_totp::<Sha256>(key, *timestamp, 0, 30, 8).unwrap();
+            assert_eq!(otp, expected, "invalid OTP at timestamp {}", timestamp);
+        }
+    }
+
+    #[test]
+    fn sha512() {
+        let key = b"1234567890123456789012345678901234567890123456789012345678901234";
+        let validator: [u32; 6] = [90693936, 25091201, 99943326, 93441116, 38618901, 47863826];
+        for (timestamp, expected) in TIMESTAMPS.iter().zip(validator) {
+            let otp = otp::gen_totp::<Sha512>(key, *timestamp, 0, 30, 8).unwrap();
+            assert_eq!(otp, expected, "invalid OTP at timestamp {}", timestamp);
+        }
+    }
+
+    #[test]
+    fn algorithm_dispatch() {
+        let key = b"12345678901234567890123456789012";
+        assert_eq!(otp::Algorithm::Sha256.totp(key, 59, 0, 30, 8).unwrap(), 46119246);
+        assert!(otp::Algorithm::Sha1.totp(key, 59, 0, 0, 8).is_err());
+    }
+
+    #[test]
+    fn time_steps() {
+        assert_eq!(otp::totp_counter(59, 0, 30).unwrap(), (1, 1));
+        assert_eq!(otp::totp_counter(60, 0, 30).unwrap(), (2, 30));
+        // Shifting T0 moves the step boundaries
+        assert_eq!(otp::totp_counter(1000, 100, 60).unwrap(), (15, 60));
+        assert_eq!(otp::totp_counter(1010, 100, 60).unwrap(), (15, 50));
+        assert!(otp::totp_counter(99, 100, 60).is_err());
+        // A non-zero T0 gives the same code as an equally shifted timestamp
+        let key = b"12345678901234567890";
+        assert_eq!(
+            otp::gen_totp::<crate::sha1::Sha1>(key, 1111111109 + 500, 500, 30, 8).unwrap(),
+            otp::gen_totp::<crate::sha1::Sha1>(key, 1111111109, 0, 30, 8).unwrap(),
+        );
+    }
+
+    #[test]
+    fn algorithm_names() {
+        assert!(otp::Algorithm::from_name(b"sha-256") == Some(otp::Algorithm::Sha256));
+        assert!(otp::Algorithm::from_name(b"SHA512") == Some(otp::Algorithm::Sha512));
+        assert!(otp::Algorithm::from_name(b"md5").is_none());
+    }
+}
+
+#[cfg(test)]
+mod truncation {
+    use crate::otp;
+
+    #[test]
+    fn rfc4226_example() {
+        // RFC 4226 Section 5.4
+        let hmac = [
+            0x1f, 0x86, 0x98, 0x69, 0x0e, 0x02, 0xca, 0x16, 0x61, 0x85,
+            0x50, 0xef, 0x7f, 0x19, 0xda, 0x8e, 0x94, 0x5b, 0x55, 0x5a,
+        ];
+        assert_eq!(otp::truncate(&hmac).unwrap(), 0x50ef7f19);
+    }
+
+    #[test]
+    fn invalid_digits() {
+        assert!(otp::gen_hotp::<crate::sha1::Sha1>(b"key", 0, 0).is_err());
+        assert!(otp::gen_hotp::<crate::sha1::Sha1>(b"key", 0, 11).is_err());
+    }
+}
+
+#[cfg(test)]
+mod verification {
+    use crate::otp::{self, Algorithm};
+
+    const KEY: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn constant_time_eq() {
+        assert!(otp::constant_time_eq(b"755224", b"755224"));
+        assert!(!otp::constant_time_eq(b"755224", b"755225"));
+        assert!(!otp::constant_time_eq(b"755224", b"75522"));
+    }
+
+    #[test]
+    fn totp_window() {
+        // 94287082 is the code for the step containing timestamp 59
+        assert_eq!(otp::verify_totp(Algorithm::Sha1, KEY, 59, 0, 30, 8, 94287082, 1).unwrap(), Some(0));
+        assert_eq!(otp::verify_totp(Algorithm::Sha1, KEY, 89, 0, 30, 8, 94287082, 1).unwrap(), Some(-1));
+        assert_eq!(otp::verify_totp(Algorithm::Sha1, KEY, 29, 0, 30, 8, 94287082, 1).unwrap(), Some(1));
+        assert_eq!(otp::verify_totp(Algorithm::Sha1, KEY, 119, 0, 30, 8, 94287082, 1).unwrap(), None);
+        assert_eq!(otp::verify_totp(Algorithm::Sha1, KEY, 119, 0, 30, 8, 94287082, 2).unwrap(), Some(-2));
+    }
+
+    #[test]
+    fn hotp_look_ahead() {
+        // RFC 4226 Appendix D - counter 5 gives 254676
+        assert_eq!(otp::verify_hotp(Algorithm::Sha1, KEY, 0, 6, 254676, 10).unwrap(), Some(5));
+        assert_eq!(otp::verify_hotp(Algorithm::Sha1, KEY, 0, 6, 254676, 4).unwrap(), None);
+        assert_eq!(otp::verify_hotp(Algorithm::Sha1, KEY, 6, 6, 254676, 10).unwrap(), None);
+    }
+}
+
+#[cfg(test)]
+mod code_formats {
+    use crate::otp::{self, Algorithm, Format, MAX_CODE_LENGTH};
+
+    const KEY: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn decimal_keeps_leading_zeros() {
+        let mut code = [0_u8; MAX_CODE_LENGTH];
+        let length = otp::format_code(1234, 6, Format::Decimal, &mut code).unwrap();
+        assert_eq!(&code[0..length], b"001234");
+    }
+
+    #[test]
+    fn decimal_matches_hotp() {
+        // RFC 4226 Appendix D - counter 1 gives 287082
+        let mut code = [0_u8; MAX_CODE_LENGTH];
+        let length = Algorithm::Sha1.code(KEY, 1, 6, Format::Decimal, &mut code).unwrap();
+        assert_eq!(&code[0..length], b"287082");
+    }
+
+    #[test]
+    fn steam() {
+        let mut code = [0_u8; MAX_CODE_LENGTH];
+        let length = Algorithm::Sha1.code(KEY, 0, 6, Format::Steam, &mut code).unwrap();
+        assert_eq!(&code[0..length], b"GG5F5");
+        let length = Algorithm::Sha1.code(KEY, 1, 6, Format::Steam, &mut code).unwrap();
+        assert_eq!(&code[0..length], b"PV9M4");
+    }
+
+    #[test]
+    fn format_names() {
+        assert!(Format::from_name(b"Steam") == Some(Format::Steam));
+        assert!(Format::from_name(b"decimal") == Some(Format::Decimal));
+        assert!(Format::from_name(b"hex").is_none());
+    }
+}
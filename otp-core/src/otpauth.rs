@@ -0,0 +1,287 @@
+use ufmt::{derive::uDebug, uWrite};
+
+use crate::byte_helper;
+use crate::otp::Algorithm;
+
+// Key URI format used by Google Authenticator and most services:
+// otpauth://TYPE/LABEL?PARAMETERS
+// https://github.com/google/google-authenticator/wiki/Key-Uri-Format
+
+#[derive(Debug, uDebug)]
+pub enum OtpAuthError {
+    InvalidScheme,
+    InvalidType,
+    InvalidPercentEncoding,
+    InvalidSecret,
+    MissingSecret,
+    InvalidAlgorithm,
+    InvalidNumber,
+    BufferTooSmall,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OtpKind {
+    Totp,
+    Hotp,
+}
+
+// Decoded URI contents - the slices point into the caller's buffers
+pub struct OtpAuth<'a> {
+    pub kind: OtpKind,
+    pub issuer: &'a [u8],
+    pub account: &'a [u8],
+    pub secret: &'a [u8],
+    pub algorithm: Algorithm,
+    pub digits: u8,
+    pub period: u64,
+    pub counter: u64,
+}
+
+const SCHEME: &[u8] = b"otpauth://";
+
+// Parse an otpauth URI, decoding the issuer, account name and secret into the buffers for them.
+// The secret is only written once the rest of the URI has been checked.
+pub fn parse<'a>(uri: &[u8], issuer: &'a mut [u8], account: &'a mut [u8], secret: &'a mut [u8]) -> Result<OtpAuth<'a>, OtpAuthError> {
+    if uri.len() < SCHEME.len() || !uri[0..SCHEME.len()].eq_ignore_ascii_case(SCHEME) {
+        return Err(OtpAuthError::InvalidScheme);
+    }
+    let uri = &uri[SCHEME.len()..];
+
+    // otpauth://TYPE/LABEL?PARAMETERS
+    let (path, query) = split_once(uri, b'?');
+    let (kind, label) = split_once(path, b'/');
+    let kind = if kind.eq_ignore_ascii_case(b"totp") {
+        OtpKind::Totp
+    }
+    else if kind.eq_ignore_ascii_case(b"hotp") {
+        OtpKind::Hotp
+    }
+    else {
+        return Err(OtpAuthError::InvalidType);
+    };
+
+    let mut parsed = OtpAuth {
+        kind,
+        issuer: &[],
+        account: &[],
+        secret: &[],
+        algorithm: Algorithm::Sha1,
+        digits: 6,
+        period: 30,
+        counter: 0,
+    };
+
+    // PARAMETERS are `&` separated `name=value` pairs - unknown names are ignored
+    let mut secret_value = None;
+    let mut issuer_value = None;
+    for parameter in query.split(|byte| *byte == b'&') {
+        let (name, value) = split_once(parameter, b'=');
+        match name {
+            b"secret" => secret_value = Some(value),
+            b"issuer" => issuer_value = Some(value),
+            b"algorithm" => {
+                parsed.algorithm = Algorithm::from_name(value).ok_or(OtpAuthError::InvalidAlgorithm)?;
+            },
+            b"digits" => {
+                parsed.digits = match byte_helper::parse_u64(value) {
+                    Ok(digits @ 1..=10) => digits as u8,
+                    _ => return Err(OtpAuthError::InvalidNumber),
+                };
+            },
+            b"period" => {
+                parsed.period = match byte_helper::parse_u64(value) {
+                    Ok(period) if period > 0 => period,
+                    _ => return Err(OtpAuthError::InvalidNumber),
+                };
+            },
+            b"counter" => {
+                parsed.counter = byte_helper::parse_u64(value).map_err(|_| OtpAuthError::InvalidNumber)?;
+            },
+            _ => {},
+        }
+    }
+
+    // LABEL is `accountname` or `issuer:accountname`, and the `issuer` parameter takes
+    // precedence over the label prefix
+    let (label_issuer, label_account) = split_label(label);
+    parsed.issuer = decode_into(issuer_value.unwrap_or(label_issuer), issuer)?;
+    parsed.account = trim_start(decode_into(label_account, account)?);
+
+    // Check the whole secret before any of it is written, so a bad one leaves `secret` as it was
+    let secret_value = secret_value.ok_or(OtpAuthError::MissingSecret)?;
+    let length = match decode_secret(secret_value, None)? {
+        0 => return Err(OtpAuthError::MissingSecret),
+        length if length > secret.len() => return Err(OtpAuthError::BufferTooSmall),
+        length => length,
+    };
+    decode_secret(secret_value, Some(&mut *secret))?;
+    parsed.secret = &secret[0..length];
+
+    Ok(parsed)
+}
+
+// Write the URI describing `otp`, e.g. for importing it into a phone app
+pub fn write_uri<W: uWrite>(writer: &mut W, otp: &OtpAuth) -> Result<(), W::Error> {
+    writer.write_str(match otp.kind {
+        OtpKind::Totp => "otpauth://totp/",
+        OtpKind::Hotp => "otpauth://hotp/",
+    })?;
+    if !otp.issuer.is_empty() {
+        write_percent_encoded(writer, otp.issuer)?;
+        writer.write_char(':')?;
+    }
+    write_percent_encoded(writer, otp.account)?;
+
+    writer.write_str("?secret=")?;
+    for chunk in otp.secret.chunks(5) {
+        let mut encoded = [0_u8; 8];
+        let length = byte_helper::base32_encode(chunk, &mut encoded).unwrap_or(0);
+        for byte in &encoded[0..length] {
+            writer.write_char(*byte as char)?;
+        }
+    }
+
+    if !otp.issuer.is_empty() {
+        writer.write_str("&issuer=")?;
+        write_percent_encoded(writer, otp.issuer)?;
+    }
+    ufmt::uwrite!(writer, "&algorithm={}&digits={}", otp.algorithm.name(), otp.digits)?;
+    match otp.kind {
+        OtpKind::Totp => ufmt::uwrite!(writer, "&period={}", otp.period),
+        OtpKind::Hotp => ufmt::uwrite!(writer, "&counter={}", otp.counter),
+    }
+}
+
+// Decode `%XX` escapes (and `+` as a space) into `output`
+// Return: number of bytes written to `output`
+pub fn percent_decode(input: &[u8], output: &mut [u8]) -> Result<usize, OtpAuthError> {
+    let mut length = 0;
+    for byte in (PercentDecoded { input }) {
+        *output.get_mut(length).ok_or(OtpAuthError::BufferTooSmall)? = byte?;
+        length += 1;
+    }
+
+    Ok(length)
+}
+
+// The bytes of a percent-encoded value, decoded as they are read
+struct PercentDecoded<'a> {
+    input: &'a [u8],
+}
+
+impl Iterator for PercentDecoded<'_> {
+    type Item = Result<u8, OtpAuthError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (byte, length) = match *self.input.first()? {
+            b'%' => {
+                let mut decoded = [0_u8; 1];
+                match self.input.get(1..3).map(|hex| byte_helper::hex_decode(hex, &mut decoded)) {
+                    Some(Ok(_)) => (decoded[0], 3),
+                    _ => {
+                        self.input = &[];
+                        return Some(Err(OtpAuthError::InvalidPercentEncoding));
+                    },
+                }
+            },
+            b'+' => (b' ', 1),
+            byte => (byte, 1),
+        };
+        self.input = &self.input[length..];
+
+        Some(Ok(byte))
+    }
+}
+
+// Decode a percent-encoded base32 secret into `output`, or only measure it if there is none.
+// Every 8 characters are 5 whole bytes, so it is decoded a group at a time without a copy of
+// the text.
+// Return: the length of the secret
+fn decode_secret(value: &[u8], mut output: Option<&mut [u8]>) -> Result<usize, OtpAuthError> {
+    let mut characters = PercentDecoded { input: value };
+    let mut group = [0_u8; 8];
+    let mut group_length = 0;
+    let mut length = 0;
+    let mut padded = false;
+
+    loop {
+        let character = characters.next().transpose()?;
+        match character {
+            Some(b' ') => continue,
+            Some(character) => {
+                group[group_length] = character;
+                group_length += 1;
+                if group_length < group.len() {
+                    continue;
+                }
+            },
+            None if group_length == 0 => return Ok(length),
+            None => {},
+        }
+
+        // Only padding can follow a group with padding in it
+        if padded {
+            return Err(OtpAuthError::InvalidSecret);
+        }
+        padded = group[0..group_length].contains(&b'=');
+
+        let mut bytes = [0_u8; 5];
+        let decoded = byte_helper::base32_decode(&group[0..group_length], &mut bytes).map_err(|_| OtpAuthError::InvalidSecret)?;
+        if let Some(output) = output.as_deref_mut() {
+            output.get_mut(length..length + decoded).ok_or(OtpAuthError::BufferTooSmall)?.copy_from_slice(&bytes[0..decoded]);
+        }
+        length += decoded;
+        group_length = 0;
+
+        if character.is_none() {
+            return Ok(length);
+        }
+    }
+}
+
+fn write_percent_encoded<W: uWrite>(writer: &mut W, input: &[u8]) -> Result<(), W::Error> {
+    for byte in input {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => writer.write_char(*byte as char)?,
+            _ => {
+                let hex = byte_helper::byte_to_hex(*byte);
+                writer.write_char('%')?;
+                writer.write_char(hex[0].to_ascii_uppercase() as char)?;
+                writer.write_char(hex[1].to_ascii_uppercase() as char)?;
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn split_once(input: &[u8], separator: u8) -> (&[u8], &[u8]) {
+    match input.iter().position(|byte| *byte == separator) {
+        Some(index) => (&input[0..index], &input[index+1..]),
+        None => (input, &input[input.len()..]),
+    }
+}
+
+// The colon between the issuer and account name may be percent-encoded
+fn split_label(label: &[u8]) -> (&[u8], &[u8]) {
+    let colon = (0..label.len()).find_map(|i| match &label[i..] {
+        [b':', ..] => Some((i, 1)),
+        [b'%', b'3', b'a' | b'A', ..] => Some((i, 3)),
+        _ => None,
+    });
+
+    match colon {
+        Some((index, length)) => (&label[0..index], &label[index+length..]),
+        None => (&label[0..0], label),
+    }
+}
+
+fn decode_into<'a>(input: &[u8], output: &'a mut [u8]) -> Result<&'a [u8], OtpAuthError> {
+    let length = percent_decode(input, output)?;
+    Ok(&output[0..length])
+}
+
+fn trim_start(input: &[u8]) -> &[u8] {
+    let start = input.iter().position(|byte| *byte != b' ').unwrap_or(input.len());
+    &input[start..]
+}
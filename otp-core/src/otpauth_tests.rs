@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod parsing {
+    use crate::otp::Algorithm;
+    use crate::otpauth::{self, OtpKind};
+
+    #[test]
+    fn all_parameters() {
+        let (mut issuer, mut account, mut secret) = ([0_u8; 32], [0_u8; 32], [0_u8; 64]);
+        let uri = b"otpauth://totp/Issuer:acct?secret=JBSWY3DPEHPK3PXP&digits=8&period=60&algorithm=SHA256";
+        let parsed = otpauth::parse(uri, &mut issuer, &mut account, &mut secret).unwrap();
+        assert!(parsed.kind == OtpKind::Totp);
+        assert_eq!(parsed.issuer, b"Issuer");
+        assert_eq!(parsed.account, b"acct");
+        assert_eq!(parsed.secret, b"Hello!\xde\xad\xbe\xef");
+        assert!(parsed.algorithm == Algorithm::Sha256);
+        assert_eq!(parsed.digits, 8);
+        assert_eq!(parsed.period, 60);
+    }
+
+    #[test]
+    fn defaults_and_percent_encoding() {
+        let (mut issuer, mut account, mut secret) = ([0_u8; 32], [0_u8; 32], [0_u8; 64]);
+        let uri = b"otpauth://hotp/Example%20Co%3A%20alice%40example.com?secret=jbswy3dpehpk3pxp&issuer=Example%20Co&counter=42&image=x";
+        let parsed = otpauth::parse(uri, &mut issuer, &mut account, &mut secret).unwrap();
+        assert!(parsed.kind == OtpKind::Hotp);
+        assert_eq!(parsed.issuer, b"Example Co");
+        assert_eq!(parsed.account, b"alice@example.com");
+        assert!(parsed.algorithm == Algorithm::Sha1);
+        assert_eq!(parsed.digits, 6);
+        assert_eq!(parsed.period, 30);
+        assert_eq!(parsed.counter, 42);
+    }
+
+    #[test]
+    fn invalid_uris() {
+        let (mut issuer, mut account, mut secret) = ([0_u8; 32], [0_u8; 32], [0_u8; 64]);
+        assert!(otpauth::parse(b"http://totp/a?secret=JBSWY3DP", &mut issuer, &mut account, &mut secret).is_err());
+        assert!(otpauth::parse(b"otpauth://motp/a?secret=JBSWY3DP", &mut issuer, &mut account, &mut secret).is_err());
+        assert!(otpauth::parse(b"otpauth://totp/a?digits=6", &mut issuer, &mut account, &mut secret).is_err());
+        assert!(otpauth::parse(b"otpauth://totp/a?secret=JBSW1", &mut issuer, &mut account, &mut secret).is_err());
+        assert!(otpauth::parse(b"otpauth://totp/a?secret=JBSWY3DP&algorithm=MD5", &mut issuer, &mut account, &mut secret).is_err());
+        assert!(otpauth::parse(b"otpauth://totp/a?secret=JBSWY3DP&period=0", &mut issuer, &mut account, &mut secret).is_err());
+        assert!(otpauth::parse(b"otpauth://totp/a%2?secret=JBSWY3DP", &mut issuer, &mut account, &mut secret).is_err());
+    }
+
+    #[test]
+    fn percent_encoded_secret() {
+        let (mut issuer, mut account, mut secret) = ([0_u8; 32], [0_u8; 32], [0_u8; 64]);
+        let uri = b"otpauth://totp/acct?secret=%4ABSWY3DPEHPK3PXP%3D%3D%3D%3D%3D%3D";
+        let parsed = otpauth::parse(uri, &mut issuer, &mut account, &mut secret).unwrap();
+        assert_eq!(parsed.secret, b"Hello!\xde\xad\xbe\xef");
+        let parsed = otpauth::parse(b"otpauth://totp/acct?secret=MZXW6%3D%3D%3D", &mut issuer, &mut account, &mut secret).unwrap();
+        assert_eq!(parsed.secret, b"foo");
+        // Padding can only come at the end
+        assert!(otpauth::parse(b"otpauth://totp/acct?secret=MY%3D%3D%3D%3D%3D%3DMZXW6%3D%3D%3D", &mut issuer, &mut account, &mut secret).is_err());
+    }
+
+    #[test]
+    fn bad_secret_is_not_written() {
+        let (mut issuer, mut account) = ([0_u8; 32], [0_u8; 32]);
+        let mut secret = [0xa5_u8; 64];
+        assert!(otpauth::parse(b"otpauth://totp/acct?secret=JBSWY3DPEHPK3PX1", &mut issuer, &mut account, &mut secret).is_err());
+        assert!(otpauth::parse(b"otpauth://totp/acct?secret=JBSWY3DPEHPK3PXP", &mut issuer, &mut account, &mut secret[0..8]).is_err());
+        assert_eq!(secret, [0xa5; 64]);
+    }
+
+    #[test]
+    fn labels_too_long() {
+        let (mut issuer, mut account, mut secret) = ([0_u8; 8], [0_u8; 8], [0_u8; 64]);
+        assert!(otpauth::parse(b"otpauth://totp/Issuer:account?secret=JBSWY3DP", &mut issuer, &mut account, &mut secret).is_ok());
+        assert!(otpauth::parse(b"otpauth://totp/Issuer:account12?secret=JBSWY3DP", &mut issuer, &mut account, &mut secret).is_err());
+        assert!(otpauth::parse(b"otpauth://totp/account?secret=JBSWY3DP&issuer=Example%20Co", &mut issuer, &mut account, &mut secret).is_err());
+    }
+}
+
+#[cfg(test)]
+mod exporting {
+    use crate::otp::Algorithm;
+    use crate::otpauth::{self, OtpAuth, OtpKind};
+
+    struct Output {
+        buffer: [u8; 256],
+        length: usize,
+    }
+
+    impl ufmt::uWrite for Output {
+        type Error = ();
+        fn write_str(&mut self, s: &str) -> Result<(), ()> {
+            self.buffer[self.length..self.length + s.len()].copy_from_slice(s.as_bytes());
+            self.length += s.len();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let otp = OtpAuth {
+            kind: OtpKind::Totp,
+            issuer: b"Example Co",
+            account: b"alice@example.com",
+            secret: b"Hello!\xde\xad\xbe\xef",
+            algorithm: Algorithm::Sha512,
+            digits: 8,
+            period: 60,
+            counter: 0,
+        };
+        let mut output = Output { buffer: [0; 256], length: 0 };
+        otpauth::write_uri(&mut output, &otp).unwrap();
+        let uri = &output.buffer[0..output.length];
+        assert_eq!(uri, &b"otpauth://totp/Example%20Co:alice%40example.com?secret=JBSWY3DPEHPK3PXP&issuer=Example%20Co&algorithm=SHA512&digits=8&period=60"[..]);
+
+        let (mut issuer, mut account, mut secret) = ([0_u8; 32], [0_u8; 32], [0_u8; 64]);
+        let parsed = otpauth::parse(uri, &mut issuer, &mut account, &mut secret).unwrap();
+        assert_eq!(parsed.issuer, otp.issuer);
+        assert_eq!(parsed.account, otp.account);
+        assert_eq!(parsed.secret, otp.secret);
+        assert!(parsed.algorithm == otp.algorithm);
+        assert_eq!(parsed.digits, otp.digits);
+        assert_eq!(parsed.period, otp.period);
+    }
+
+    #[test]
+    fn hotp_round_trip() {
+        let otp = OtpAuth {
+            kind: OtpKind::Hotp,
+            issuer: b"",
+            account: b"bob",
+            secret: b"Hello!\xde\xad\xbe\xef",
+            algorithm: Algorithm::Sha1,
+            digits: 6,
+            period: 30,
+            counter: 42,
+        };
+        let mut output = Output { buffer: [0; 256], length: 0 };
+        otpauth::write_uri(&mut output, &otp).unwrap();
+        let uri = &output.buffer[0..output.length];
+        assert_eq!(uri, &b"otpauth://hotp/bob?secret=JBSWY3DPEHPK3PXP&algorithm=SHA1&digits=6&counter=42"[..]);
+
+        let (mut issuer, mut account, mut secret) = ([0_u8; 32], [0_u8; 32], [0_u8; 64]);
+        let parsed = otpauth::parse(uri, &mut issuer, &mut account, &mut secret).unwrap();
+        assert!(parsed.kind == OtpKind::Hotp);
+        assert_eq!(parsed.account, otp.account);
+        assert_eq!(parsed.counter, otp.counter);
+    }
+}
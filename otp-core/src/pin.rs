@@ -0,0 +1,157 @@
+use crate::byte_helper;
+use crate::digest;
+use crate::chacha20poly1305::KEY_SIZE;
+use crate::otp::{self, Hmac};
+use crate::sha1::{Sha1, DIGEST_SIZE};
+use crate::sha256::Sha256;
+
+// The console PIN is never stored. PBKDF2-HMAC-SHA1 (RFC 8018) derives a key from it, and only
+// a verifier hashed from that key is saved. The same key derives the key that seals secrets.
+// 0x00 => version: u8 (0xff when no PIN is set)
+// 0x02..0x04 => PBKDF2 iterations: u16
+// 0x04..0x08 => idle timeout in seconds: u32 (0 never locks)
+// 0x10..0x20 => salt: [u8; 16]
+// 0x20..0x34 => verifier: [u8; 20]
+pub const PIN_CONFIG_SIZE: usize = 64;
+pub const SALT_SIZE: usize = 16;
+pub const VERIFIER_SIZE: usize = DIGEST_SIZE;
+pub const MAX_PIN_LENGTH: usize = 32;
+// Anyone holding the 24C32 can guess PINs against the verifier offline, as fast as their own
+// hardware allows, so only the length of the PIN (a passphrase, really) keeps them out
+pub const MIN_PIN_LENGTH: usize = 8;
+
+// Around two seconds of hashing on the ATmega328P
+pub const DEFAULT_ITERATIONS: u16 = 2_000;
+pub const DEFAULT_TIMEOUT: u32 = 300;
+
+const VERSION: u8 = 2;
+
+// Failed attempts allowed before each further attempt is delayed
+const FREE_ATTEMPTS: u64 = 3;
+pub const MAX_LOCKOUT_SECONDS: u32 = 3_600;
+
+#[derive(Clone, Copy)]
+pub struct PinConfig {
+    pub iterations: u16,
+    pub timeout: u32,
+    pub salt: [u8; SALT_SIZE],
+    pub verifier: [u8; VERIFIER_SIZE],
+}
+
+impl PinConfig {
+    pub fn new(pin: &[u8], salt: [u8; SALT_SIZE], iterations: u16, timeout: u32) -> Self {
+        Self::with_wrap_key(pin, salt, iterations, timeout).0
+    }
+
+    // Return: the new config and the key that seals stored secrets under it
+    pub fn with_wrap_key(pin: &[u8], salt: [u8; SALT_SIZE], iterations: u16, timeout: u32) -> (Self, [u8; KEY_SIZE]) {
+        let derived_key = derive_key(pin, &salt, iterations);
+        let config = PinConfig {
+            iterations, timeout, salt,
+            verifier: gen_verifier(&derived_key),
+        };
+
+        (config, gen_wrap_key(&derived_key))
+    }
+
+    // Return: the key that seals stored secrets, or None if the PIN is wrong
+    pub fn unlock(&self, pin: &[u8]) -> Option<[u8; KEY_SIZE]> {
+        let derived_key = derive_key(pin, &self.salt, self.iterations);
+        match otp::constant_time_eq(&gen_verifier(&derived_key), &self.verifier) {
+            true => Some(gen_wrap_key(&derived_key)),
+            false => None,
+        }
+    }
+
+    pub fn check(&self, pin: &[u8]) -> bool {
+        self.unlock(pin).is_some()
+    }
+
+    // Return: None when no PIN is set
+    pub fn from_bytes(bytes: &[u8; PIN_CONFIG_SIZE]) -> Option<Self> {
+        if bytes[0] != VERSION {
+            return None;
+        }
+
+        let mut config = PinConfig {
+            iterations: u16::from_be_bytes([bytes[2], bytes[3]]),
+            timeout: byte_helper::bytes_to_u32([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            salt: [0; SALT_SIZE],
+            verifier: [0; VERIFIER_SIZE],
+        };
+        config.salt.copy_from_slice(&bytes[0x10..0x20]);
+        config.verifier.copy_from_slice(&bytes[0x20..0x20 + VERIFIER_SIZE]);
+
+        Some(config)
+    }
+
+    pub fn to_bytes(&self) -> [u8; PIN_CONFIG_SIZE] {
+        let mut bytes = [0xff_u8; PIN_CONFIG_SIZE];
+        bytes[0] = VERSION;
+        bytes[2..4].copy_from_slice(&byte_helper::u16_to_bytes(self.iterations));
+        bytes[4..8].copy_from_slice(&byte_helper::u32_to_bytes(self.timeout));
+        bytes[0x10..0x20].copy_from_slice(&self.salt);
+        bytes[0x20..0x20 + VERIFIER_SIZE].copy_from_slice(&self.verifier);
+
+        bytes
+    }
+}
+
+pub fn derive_key(pin: &[u8], salt: &[u8], iterations: u16) -> [u8; DIGEST_SIZE] {
+    let mut derived_key = [0_u8; DIGEST_SIZE];
+    pbkdf2_hmac_sha1(pin, salt, iterations as u32, &mut derived_key);
+    derived_key
+}
+
+// The verifier and the wrap key are both hashed from the derived key with a different label,
+// so the saved verifier says nothing about the wrap key
+pub fn gen_verifier(derived_key: &[u8]) -> [u8; VERIFIER_SIZE] {
+    otp::gen_hmac::<Sha1>(derived_key, b"verify")
+}
+
+pub fn gen_wrap_key(derived_key: &[u8]) -> [u8; KEY_SIZE] {
+    otp::gen_hmac::<Sha256>(derived_key, b"wrap")
+}
+
+// PBKDF2 (RFC 8018 5.2) with the same HMAC as `gen_sha1_hmac`. The keyed HMAC state is
+// cloned for every iteration instead of hashing the password pads again each time.
+pub fn pbkdf2_hmac_sha1(password: &[u8], salt: &[u8], iterations: u32, output: &mut [u8]) {
+    let keyed = Hmac::<Sha1>::new(password);
+
+    for (block_index, block) in output.chunks_mut(DIGEST_SIZE).enumerate() {
+        // U_1 = PRF(P, S || INT(i))
+        let mut hmac = keyed.clone();
+        hmac.update(salt);
+        hmac.update(&byte_helper::u32_to_bytes(block_index as u32 + 1));
+        let mut u = hmac.finalize();
+        let mut t = u;
+
+        // U_c = PRF(P, U_{c-1}), T_i = U_1 ^ U_2 ^ ... ^ U_c
+        for _ in 1..iterations {
+            let mut hmac = keyed.clone();
+            hmac.update(&u);
+            u = hmac.finalize();
+            for (t_byte, u_byte) in t.iter_mut().zip(u) {
+                *t_byte ^= u_byte;
+            }
+        }
+
+        block.copy_from_slice(&t[0..block.len()]);
+    }
+}
+
+// Seconds to wait before checking a PIN, doubling with every failed attempt beyond the free ones
+pub fn lockout_delay(failed_attempts: u64) -> u32 {
+    match failed_attempts.checked_sub(FREE_ATTEMPTS) {
+        None => 0,
+        Some(extra) => (1_u32 << extra.min(31)).min(MAX_LOCKOUT_SECONDS),
+    }
+}
+
+// The salt only has to be unique, not secret, so it is hashed from whatever changes
+// between calls (e.g. the time, temperature and previous salt)
+pub fn gen_salt(seed: &[u8]) -> [u8; SALT_SIZE] {
+    let mut salt = [0_u8; SALT_SIZE];
+    salt.copy_from_slice(&digest::gen_digest::<Sha1>(seed)[0..SALT_SIZE]);
+    salt
+}
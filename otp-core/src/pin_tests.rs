@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod pbkdf2 {
+    use crate::pin;
+
+    fn derive<const N: usize>(password: &[u8], salt: &[u8], iterations: u32) -> [u8; N] {
+        let mut output = [0_u8; N];
+        pin::pbkdf2_hmac_sha1(password, salt, iterations, &mut output);
+        output
+    }
+
+    // RFC 6070 test vectors
+    #[test]
+    fn one_iteration() {
+        assert_eq!(derive::<20>(b"password", b"salt", 1), [
+            0x0c, 0x60, 0xc8, 0x0f, 0x96, 0x1f, 0x0e, 0x71, 0xf3, 0xa9,
+            0xb5, 0x24, 0xaf, 0x60, 0x12, 0x06, 0x2f, 0xe0, 0x37, 0xa6,
+        ]);
+    }
+
+    #[test]
+    fn two_iterations() {
+        assert_eq!(derive::<20>(b"password", b"salt", 2), [
+            0xea, 0x6c, 0x01, 0x4d, 0xc7, 0x2d, 0x6f, 0x8c, 0xcd, 0x1e,
+            0xd9, 0x2a, 0xce, 0x1d, 0x41, 0xf0, 0xd8, 0xde, 0x89, 0x57,
+        ]);
+    }
+
+    #[test]
+    fn multiple_blocks() {
+        assert_eq!(derive::<25>(b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096), [
+            0x3d, 0x2e, 0xec, 0x4f, 0xe4, 0x1c, 0x84, 0x9b, 0x80, 0xc8,
+            0xd8, 0x36, 0x62, 0xc0, 0xe4, 0x4a, 0x8b, 0x29, 0x1a, 0x96,
+            0x4c, 0xf2, 0xf0, 0x70, 0x38,
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod verifier {
+    use crate::pin::{self, PinConfig};
+
+    #[test]
+    fn check_pin() {
+        let config = PinConfig::new(b"1234", [7; pin::SALT_SIZE], 10, 300);
+        assert!(config.check(b"1234"));
+        assert!(!config.check(b"1235"));
+        assert!(!config.check(b""));
+    }
+
+    #[test]
+    fn round_trip() {
+        let config = PinConfig::new(b"1234", [7; pin::SALT_SIZE], 10, 60);
+        let decoded = PinConfig::from_bytes(&config.to_bytes()).unwrap();
+        assert_eq!(decoded.iterations, 10);
+        assert_eq!(decoded.timeout, 60);
+        assert!(decoded.check(b"1234"));
+
+        assert!(PinConfig::from_bytes(&[0xff; pin::PIN_CONFIG_SIZE]).is_none());
+    }
+
+    #[test]
+    fn unlock_gives_the_same_wrap_key() {
+        let (config, wrap_key) = PinConfig::with_wrap_key(b"1234", [7; pin::SALT_SIZE], 10, 300);
+        assert_eq!(config.unlock(b"1234"), Some(wrap_key));
+        assert_eq!(config.unlock(b"1235"), None);
+
+        // The wrap key depends on the salt, and isn't the verifier
+        let (_, other_wrap_key) = PinConfig::with_wrap_key(b"1234", [8; pin::SALT_SIZE], 10, 300);
+        assert_ne!(wrap_key, other_wrap_key);
+        assert_ne!(wrap_key[0..pin::VERIFIER_SIZE], config.verifier);
+    }
+
+    #[test]
+    fn other_versions_are_not_a_pin() {
+        let mut bytes = PinConfig::new(b"1234", [7; pin::SALT_SIZE], 10, 300).to_bytes();
+        assert!(PinConfig::from_bytes(&bytes).is_some());
+        bytes[0] = 1;
+        assert!(PinConfig::from_bytes(&bytes).is_none());
+    }
+
+    #[test]
+    fn lockout_escalates() {
+        assert_eq!(pin::lockout_delay(0), 0);
+        assert_eq!(pin::lockout_delay(2), 0);
+        assert_eq!(pin::lockout_delay(3), 1);
+        assert_eq!(pin::lockout_delay(4), 2);
+        assert_eq!(pin::lockout_delay(8), 32);
+        assert_eq!(pin::lockout_delay(100), 3_600);
+    }
+}
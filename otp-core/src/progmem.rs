@@ -0,0 +1,37 @@
+// Constant tables in program memory. The ATmega328P copies everything in `.data` into its 2KB
+// of SRAM at startup, so on AVR the text and lookup tables are placed in `.progmem.data`
+// instead and read back a byte at a time with LPM. Everywhere else they are ordinary static
+// data. Only read them through these functions, since on AVR a reference into program memory
+// does not point at SRAM.
+
+#[cfg(target_arch = "avr")]
+pub(crate) fn load(byte: *const u8) -> u8 {
+    let value: u8;
+    unsafe {
+        core::arch::asm!("lpm {}, Z", out(reg) value, in("Z") byte, options(pure, readonly, nostack, preserves_flags));
+    }
+    value
+}
+
+#[cfg(not(target_arch = "avr"))]
+pub(crate) fn load(byte: *const u8) -> u8 {
+    unsafe { *byte }
+}
+
+pub fn load_u32(word: &u32) -> u32 {
+    let mut bytes = [0_u8; 4];
+    load_bytes(word as *const u32 as *const u8, &mut bytes);
+    u32::from_ne_bytes(bytes)
+}
+
+pub fn load_u64(word: &u64) -> u64 {
+    let mut bytes = [0_u8; 8];
+    load_bytes(word as *const u64 as *const u8, &mut bytes);
+    u64::from_ne_bytes(bytes)
+}
+
+fn load_bytes(start: *const u8, bytes: &mut [u8]) {
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = load(start.wrapping_add(i));
+    }
+}
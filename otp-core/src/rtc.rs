@@ -0,0 +1,412 @@
+use embedded_hal::blocking::{delay::DelayMs, i2c::{Write, WriteRead}};
+use ufmt::derive::uDebug;
+use ufmt::uDebug;
+
+use crate::alarm::{self, Alarm, Schedule};
+use crate::clock::Clock;
+use crate::datetime::Datetime;
+use crate::eeprom::{InvalidAddress, Storage, PAGE_SIZE};
+use crate::sqw::{self, Rate};
+
+const DS3231_I2C_ADDRESS: u8 = 0x68;
+const EEPROM_I2C_ADDRESS: u8 = 0x57;
+
+const CONTROL_REGISTER: u8 = 0x0e;
+const CONTROL_CONV: u8 = 0b0010_0000;
+const CONTROL_INTCN: u8 = 0b0000_0100;
+const STATUS_REGISTER: u8 = 0x0f;
+const STATUS_OSF: u8 = 0b1000_0000;
+const STATUS_EN32KHZ: u8 = 0b0000_1000;
+const STATUS_BSY: u8 = 0b0000_0100;
+const STATUS_A2F: u8 = 0b0000_0010;
+const STATUS_A1F: u8 = 0b0000_0001;
+const STATUS_FLAGS: u8 = STATUS_OSF | STATUS_A2F | STATUS_A1F;
+
+#[derive(Debug, uDebug, PartialEq)]
+pub enum RtcError<E> {
+    // The I2C bus reported an error
+    Bus(E),
+    // The address is not on a page boundary, or the slot does not exist
+    InvalidAddress,
+    // The alarm can't fire on that schedule, e.g. every second on alarm 2
+    InvalidAlarm,
+}
+
+impl<E> From<InvalidAddress> for RtcError<E> {
+    fn from(_: InvalidAddress) -> Self {
+        RtcError::InvalidAddress
+    }
+}
+
+// The DS3231 status register
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub struct Status {
+    // OSF: the oscillator has stopped since the flag was last cleared, e.g. because the
+    // battery ran out, so the time is wrong. It is set when the DS3231 first powers up.
+    pub oscillator_stopped: bool,
+    // BSY: a temperature conversion is in progress
+    pub busy: bool,
+    // EN32kHz: the 32kHz pin is putting out a square wave
+    pub output_32khz: bool,
+    // A1F/A2F: the alarm has fired since its flag was last cleared
+    pub alarm1_fired: bool,
+    pub alarm2_fired: bool,
+}
+
+impl Status {
+    pub fn from_byte(byte: u8) -> Self {
+        Status {
+            oscillator_stopped: byte & STATUS_OSF != 0,
+            busy: byte & STATUS_BSY != 0,
+            output_32khz: byte & STATUS_EN32KHZ != 0,
+            alarm1_fired: byte & Alarm::One.bit() != 0,
+            alarm2_fired: byte & Alarm::Two.bit() != 0,
+        }
+    }
+
+    pub fn alarm_fired(&self, alarm: Alarm) -> bool {
+        match alarm {
+            Alarm::One => self.alarm1_fired,
+            Alarm::Two => self.alarm2_fired,
+        }
+    }
+}
+
+// The DS3231 control register
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub struct Control {
+    // RS2/RS1: the rate of the square wave on INT/SQW
+    pub rate: Rate,
+    // INTCN: INT/SQW is the alarm interrupt instead of the square wave
+    pub interrupt_control: bool,
+    // A1IE/A2IE: the alarm pulls INT/SQW low when it fires, as long as INTCN is set
+    pub alarm1_interrupt: bool,
+    pub alarm2_interrupt: bool,
+}
+
+impl Control {
+    pub fn from_byte(byte: u8) -> Self {
+        Control {
+            rate: Rate::from_control(byte),
+            interrupt_control: byte & CONTROL_INTCN != 0,
+            alarm1_interrupt: byte & Alarm::One.bit() != 0,
+            alarm2_interrupt: byte & Alarm::Two.bit() != 0,
+        }
+    }
+
+    // Return: None while INT/SQW is the alarm interrupt
+    pub fn square_wave(&self) -> Option<Rate> {
+        match self.interrupt_control {
+            true => None,
+            false => Some(self.rate),
+        }
+    }
+
+    // Return: true if the alarm pulls INT/SQW low when it fires
+    pub fn alarm_interrupt(&self, alarm: Alarm) -> bool {
+        let enabled = match alarm {
+            Alarm::One => self.alarm1_interrupt,
+            Alarm::Two => self.alarm2_interrupt,
+        };
+
+        self.interrupt_control && enabled
+    }
+}
+
+// DS3231 and AT24C32 driver for any blocking embedded-hal I2C bus.
+// `delay` waits out the EEPROM write cycle after each write.
+pub struct Rtc<I2C, D> {
+    i2c: I2C,
+    delay: D,
+}
+
+impl<I2C, D, E> Rtc<I2C, D>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u16>,
+{
+    pub fn new(i2c: I2C, delay: D) -> Self {
+        Self { i2c, delay }
+    }
+
+    // Give back the bus and delay, e.g. to share them with another driver
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+
+    pub fn now(&mut self) -> Result<Datetime, RtcError<E>> {
+        let mut buffer = [0_u8; 7];
+        self.i2c.write_read(DS3231_I2C_ADDRESS, &[0x00], &mut buffer).map_err(RtcError::Bus)?;
+
+        Ok(Datetime::from_bytes(buffer))
+    }
+
+    pub fn set(&mut self, new_time: [u8; 8]) -> Result<(), RtcError<E>> {
+        self.i2c.write(DS3231_I2C_ADDRESS, &new_time).map_err(RtcError::Bus)
+    }
+
+    pub fn read_status(&mut self) -> Result<Status, RtcError<E>> {
+        Ok(Status::from_byte(self.read_status_register()?))
+    }
+
+    // Clear OSF once the time has been set again
+    pub fn clear_oscillator_stop(&mut self) -> Result<(), RtcError<E>> {
+        let status = self.read_status_register()?;
+        self.write_status_register((status | STATUS_FLAGS) & !STATUS_OSF)
+    }
+
+    // Turn the square wave on the 32kHz pin on or off
+    pub fn set_32khz_output(&mut self, enabled: bool) -> Result<(), RtcError<E>> {
+        let status = self.read_status_register()? | STATUS_FLAGS;
+        match enabled {
+            true => self.write_status_register(status | STATUS_EN32KHZ),
+            false => self.write_status_register(status & !STATUS_EN32KHZ),
+        }
+    }
+
+    // Write the alarm's registers. The interrupt and flag are left as they are.
+    pub fn set_alarm(&mut self, alarm: Alarm, schedule: Schedule) -> Result<(), RtcError<E>> {
+        let registers = schedule.to_registers(alarm).ok_or(RtcError::InvalidAlarm)?;
+        let mut buffer = [alarm.register(), registers[0], registers[1], registers[2], registers[3]];
+        match alarm {
+            Alarm::One => self.i2c.write(DS3231_I2C_ADDRESS, &buffer),
+            // Alarm 2 has no seconds register
+            Alarm::Two => {
+                buffer[1] = alarm.register();
+                self.i2c.write(DS3231_I2C_ADDRESS, &buffer[1..])
+            },
+        }.map_err(RtcError::Bus)
+    }
+
+    // Return: None if the registers don't hold a schedule, e.g. since the DS3231 powered up
+    pub fn read_alarm(&mut self, alarm: Alarm) -> Result<Option<Schedule>, RtcError<E>> {
+        let mut registers = [0_u8; 4];
+        let buffer = match alarm {
+            Alarm::One => &mut registers[..],
+            Alarm::Two => &mut registers[1..],
+        };
+        self.i2c.write_read(DS3231_I2C_ADDRESS, &[alarm.register()], buffer).map_err(RtcError::Bus)?;
+
+        Ok(Schedule::from_registers(alarm, registers))
+    }
+
+    // Return: true if the alarm pulls INT/SQW low when it fires
+    pub fn alarm_interrupt(&mut self, alarm: Alarm) -> Result<bool, RtcError<E>> {
+        Ok(self.read_control()?.alarm_interrupt(alarm))
+    }
+
+    // Turn the alarm's interrupt (A1IE/A2IE) on or off. Turning it on also sets INTCN, which
+    // hands INT/SQW over to the alarms instead of the square wave.
+    pub fn set_alarm_interrupt(&mut self, alarm: Alarm, enabled: bool) -> Result<(), RtcError<E>> {
+        let control = self.read_control_register()?;
+        match enabled {
+            true => self.write_control_register(control | CONTROL_INTCN | alarm.bit()),
+            false => self.write_control_register(control & !alarm.bit()),
+        }
+    }
+
+    // Clear A1F/A2F, which lets INT/SQW go high again
+    pub fn clear_alarm_flag(&mut self, alarm: Alarm) -> Result<(), RtcError<E>> {
+        let status = self.read_status_register()?;
+        self.write_status_register((status | STATUS_FLAGS) & !alarm.bit())
+    }
+
+    pub fn read_control(&mut self) -> Result<Control, RtcError<E>> {
+        Ok(Control::from_byte(self.read_control_register()?))
+    }
+
+    // Put a square wave out on INT/SQW by clearing INTCN, or with None set INTCN to hand the
+    // pin back to the alarms
+    pub fn set_square_wave(&mut self, rate: Option<Rate>) -> Result<(), RtcError<E>> {
+        let control = self.read_control_register()?;
+        match rate {
+            Some(rate) => self.write_control_register(rate.to_control(control) & !CONTROL_INTCN),
+            None => self.write_control_register(control | CONTROL_INTCN),
+        }
+    }
+
+    fn read_control_register(&mut self) -> Result<u8, RtcError<E>> {
+        let mut buffer = [0_u8; 1];
+        self.i2c.write_read(DS3231_I2C_ADDRESS, &[CONTROL_REGISTER], &mut buffer).map_err(RtcError::Bus)?;
+
+        Ok(buffer[0])
+    }
+
+    // CONV reads as set during a conversion, and writing it back would start another
+    fn write_control_register(&mut self, control: u8) -> Result<(), RtcError<E>> {
+        self.i2c.write(DS3231_I2C_ADDRESS, &[CONTROL_REGISTER, control & !CONTROL_CONV]).map_err(RtcError::Bus)
+    }
+
+    fn read_status_register(&mut self) -> Result<u8, RtcError<E>> {
+        let mut buffer = [0_u8; 1];
+        self.i2c.write_read(DS3231_I2C_ADDRESS, &[STATUS_REGISTER], &mut buffer).map_err(RtcError::Bus)?;
+
+        Ok(buffer[0])
+    }
+
+    // The flags are cleared by writing 0 and left alone by writing 1. Writing back what was read
+    // would clear a flag that was set since, so writes set every flag but the one to clear.
+    fn write_status_register(&mut self, status: u8) -> Result<(), RtcError<E>> {
+        self.i2c.write(DS3231_I2C_ADDRESS, &[STATUS_REGISTER, status]).map_err(RtcError::Bus)
+    }
+
+    // Read the current temperature value in Celsius
+    // Return: (whole numbers, 0.25 resolution value)
+    pub fn read_temperature(&mut self) -> Result<(i8, u8), RtcError<E>> {
+        let mut buffer = [0_u8; 2];
+        self.i2c.write_read(DS3231_I2C_ADDRESS, &[0x11], &mut buffer).map_err(RtcError::Bus)?;
+
+        buffer[1] = (buffer[1] >> 6) & 0b11;
+
+        Ok((buffer[0] as i8, buffer[1]))
+    }
+
+    // Force a temperature update in the RTC
+    // Return: true if we forced an update, false if an update is already in progress
+    pub fn update_temperature(&mut self) -> Result<bool, RtcError<E>> {
+        let mut current_settings = [0_u8; 2];
+        self.i2c.write_read(DS3231_I2C_ADDRESS, &[0x0e], &mut current_settings).map_err(RtcError::Bus)?;
+
+        // Don't update if the busy flag is set
+        if (current_settings[1] & 0b0100) != 0b0100 {
+            let new_control_settings = current_settings[0] | 0b0010_0000;
+            self.i2c.write(DS3231_I2C_ADDRESS, &[0x0e, new_control_settings]).map_err(RtcError::Bus)?;
+
+            Ok(true)
+        }
+        else {
+            Ok(false)
+        }
+    }
+
+    // Read a single byte from the RTC EEPROM
+    pub fn read_byte_eeprom(&mut self, address: [u8; 2]) -> Result<[u8; 1], RtcError<E>> {
+        let mut buffer = [0_u8; 1];
+        self.i2c.write_read(EEPROM_I2C_ADDRESS, &address, &mut buffer).map_err(RtcError::Bus)?;
+
+        Ok(buffer)
+    }
+
+    // Read a 32-byte page from the RTC EEPROM
+    pub fn read_page_eeprom(&mut self, address: [u8; 2]) -> Result<[u8; 32], RtcError<E>> {
+        let mut buffer = [0_u8; 32];
+        self.i2c.write_read(EEPROM_I2C_ADDRESS, &address, &mut buffer).map_err(RtcError::Bus)?;
+
+        Ok(buffer)
+    }
+
+    pub fn write_byte_eeprom(&mut self, address: [u8; 2], input: u8) -> Result<(), RtcError<E>> {
+        let buffer = [
+            address[0], address[1],
+            input
+        ];
+        self.i2c.write(EEPROM_I2C_ADDRESS, &buffer).map_err(RtcError::Bus)
+    }
+
+    pub fn write_page_eeprom(&mut self, address: [u8; 2], input: [u8; 32]) -> Result<(), RtcError<E>> {
+        let mut buffer = [0_u8; 34];
+
+        // Address must be at the start of a 32-byte page boundary
+        if address[1] % 32 != 0 {
+            return Err(RtcError::InvalidAddress);
+        }
+
+        buffer[0..2].copy_from_slice(&address);
+        buffer[2..34].copy_from_slice(&input);
+
+        self.i2c.write(EEPROM_I2C_ADDRESS, &buffer).map_err(RtcError::Bus)
+    }
+}
+
+impl<I2C, D, E> Clock for Rtc<I2C, D>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u16>,
+    E: uDebug,
+{
+    type Error = RtcError<E>;
+
+    fn now(&mut self) -> Result<Datetime, Self::Error> {
+        Rtc::now(self)
+    }
+
+    fn set(&mut self, time: Datetime) -> Result<(), Self::Error> {
+        Rtc::set(self, time.to_bytes())?;
+        self.clear_oscillator_stop()
+    }
+
+    fn lost_time(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.read_status()?.oscillator_stopped)
+    }
+
+    fn alarm(&mut self, alarm: Alarm) -> Result<alarm::State, Self::Error> {
+        Ok(alarm::State {
+            schedule: self.read_alarm(alarm)?,
+            interrupt: self.alarm_interrupt(alarm)?,
+            fired: self.read_status()?.alarm_fired(alarm),
+        })
+    }
+
+    fn set_alarm(&mut self, alarm: Alarm, schedule: Schedule) -> Result<(), Self::Error> {
+        Rtc::set_alarm(self, alarm, schedule)?;
+        self.clear_alarm_flag(alarm)?;
+        self.set_alarm_interrupt(alarm, true)
+    }
+
+    fn clear_alarm(&mut self, alarm: Alarm) -> Result<(), Self::Error> {
+        self.set_alarm_interrupt(alarm, false)?;
+        self.clear_alarm_flag(alarm)
+    }
+
+    fn square_wave(&mut self) -> Result<sqw::State, Self::Error> {
+        Ok(sqw::State {
+            square_wave: self.read_control()?.square_wave(),
+            output_32khz: self.read_status()?.output_32khz,
+        })
+    }
+
+    fn set_square_wave(&mut self, rate: Option<Rate>) -> Result<(), Self::Error> {
+        Rtc::set_square_wave(self, rate)
+    }
+
+    fn set_32khz_output(&mut self, enabled: bool) -> Result<(), Self::Error> {
+        Rtc::set_32khz_output(self, enabled)
+    }
+
+    fn read_temperature(&mut self) -> Result<(i8, u8), Self::Error> {
+        Rtc::read_temperature(self)
+    }
+
+    fn update_temperature(&mut self) -> Result<bool, Self::Error> {
+        Rtc::update_temperature(self)
+    }
+
+    fn delay_ms(&mut self, ms: u16) {
+        self.delay.delay_ms(ms);
+    }
+}
+
+impl<I2C, D, E> Storage for Rtc<I2C, D>
+where
+    I2C: Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u16>,
+    E: uDebug,
+{
+    type Error = RtcError<E>;
+
+    fn read_eeprom(&mut self, address: [u8; 2], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c.write_read(EEPROM_I2C_ADDRESS, &address, buffer).map_err(RtcError::Bus)
+    }
+
+    fn write_byte_eeprom(&mut self, address: [u8; 2], input: u8) -> Result<(), Self::Error> {
+        Rtc::write_byte_eeprom(self, address, input)
+    }
+
+    fn write_page_eeprom(&mut self, address: [u8; 2], input: [u8; PAGE_SIZE]) -> Result<(), Self::Error> {
+        Rtc::write_page_eeprom(self, address, input)
+    }
+
+    fn wait_for_write(&mut self) {
+        self.delay.delay_ms(10); // Wait for EEPROM to finish writing
+    }
+}
@@ -0,0 +1,529 @@
+#[cfg(test)]
+mod ds3231 {
+    extern crate std;
+    use std::vec;
+    use embedded_hal_mock::eh0::delay::NoopDelay;
+    use embedded_hal_mock::eh0::i2c::{Mock, Transaction};
+    use embedded_hal_mock::eh0::MockError;
+    use crate::alarm::{Alarm, Match, Schedule};
+    use crate::rtc::{Control, Rtc, RtcError, Status};
+    use crate::sqw::Rate;
+
+    #[test]
+    fn now() {
+        let bus = Mock::new(&[
+            Transaction::write_read(0x68, vec![0x00], vec![0x20, 0x13, 0x22, 0x03, 0x14, 0x91, 0x23]),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        assert_eq!(rtc.now().unwrap().unix_timestamp(), 1_700_000_000);
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn set() {
+        let registers = [0x00, 0x20, 0x13, 0x22, 0x01, 0x14, 0x91, 0x23];
+        let bus = Mock::new(&[Transaction::write(0x68, registers.to_vec())]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        rtc.set(registers).unwrap();
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn read_temperature() {
+        let bus = Mock::new(&[
+            Transaction::write_read(0x68, vec![0x11], vec![0x19, 0b1100_0000]),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        assert_eq!(rtc.read_temperature().unwrap(), (25, 3));
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn update_temperature_waits_for_busy() {
+        let bus = Mock::new(&[
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0001_1100, 0b0000_0100]),
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0001_1100, 0b0000_0000]),
+            Transaction::write(0x68, vec![0x0e, 0b0011_1100]),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        assert_eq!(rtc.update_temperature(), Ok(false));
+        assert_eq!(rtc.update_temperature(), Ok(true));
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn status() {
+        let bus = Mock::new(&[
+            Transaction::write_read(0x68, vec![0x0f], vec![0b1000_1110]),
+            Transaction::write_read(0x68, vec![0x0f], vec![0b0000_0001]),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        assert_eq!(rtc.read_status(), Ok(Status {
+            oscillator_stopped: true, busy: true, output_32khz: true, alarm1_fired: false, alarm2_fired: true,
+        }));
+        let status = rtc.read_status().unwrap();
+        assert_eq!(status, Status {
+            oscillator_stopped: false, busy: false, output_32khz: false, alarm1_fired: true, alarm2_fired: false,
+        });
+        assert!(status.alarm_fired(Alarm::One));
+        assert!(!status.alarm_fired(Alarm::Two));
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn alarm_registers() {
+        let bus = Mock::new(&[
+            Transaction::write(0x68, vec![0x07, 0x30, 0x15, 0x08, 0x47]),
+            // Alarm 2 starts at its minutes register
+            Transaction::write(0x68, vec![0x0b, 0x80, 0x80, 0x81]),
+            Transaction::write_read(0x68, vec![0x07], vec![0x30, 0x15, 0x08, 0x47]),
+            Transaction::write_read(0x68, vec![0x0b], vec![0x00, 0x00, 0x00]),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        let weekly = Schedule { matching: Match::Day(7), hours: 8, minutes: 15, seconds: 30 };
+        let every_minute = Schedule { matching: Match::EveryMinute, hours: 0, minutes: 0, seconds: 0 };
+        rtc.set_alarm(Alarm::One, weekly).unwrap();
+        rtc.set_alarm(Alarm::Two, every_minute).unwrap();
+        assert_eq!(rtc.set_alarm(Alarm::One, every_minute), Err(RtcError::InvalidAlarm));
+        assert_eq!(rtc.read_alarm(Alarm::One), Ok(Some(weekly)));
+        assert_eq!(rtc.read_alarm(Alarm::Two), Ok(None));
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn alarm_interrupts_and_flags() {
+        let bus = Mock::new(&[
+            // Enabling an interrupt hands INT/SQW over from the square wave, and CONV is never
+            // written back
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0011_1000]),
+            Transaction::write(0x68, vec![0x0e, 0b0001_1110]),
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0001_1111]),
+            Transaction::write(0x68, vec![0x0e, 0b0001_1110]),
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0001_1110]),
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0001_1010]),
+            // Only the alarm's own flag is cleared, and the others are written as 1 even if they
+            // were clear when read
+            Transaction::write_read(0x68, vec![0x0f], vec![0b1000_1011]),
+            Transaction::write(0x68, vec![0x0f, 0b1000_1001]),
+            Transaction::write_read(0x68, vec![0x0f], vec![0b0000_1001]),
+            Transaction::write(0x68, vec![0x0f, 0b1000_1010]),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        rtc.set_alarm_interrupt(Alarm::Two, true).unwrap();
+        rtc.set_alarm_interrupt(Alarm::One, false).unwrap();
+        assert_eq!(rtc.alarm_interrupt(Alarm::Two), Ok(true));
+        // Without INTCN the alarms don't reach the pin
+        assert_eq!(rtc.alarm_interrupt(Alarm::Two), Ok(false));
+        rtc.clear_alarm_flag(Alarm::Two).unwrap();
+        rtc.clear_alarm_flag(Alarm::One).unwrap();
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn status_writes_keep_the_other_bits() {
+        let bus = Mock::new(&[
+            // The alarm flags are written as 1 whether or not they were set when read, which
+            // leaves them alone, so an alarm that fires in between is not lost
+            Transaction::write_read(0x68, vec![0x0f], vec![0b1000_1011]),
+            Transaction::write(0x68, vec![0x0f, 0b0000_1011]),
+            Transaction::write_read(0x68, vec![0x0f], vec![0b1000_1000]),
+            Transaction::write(0x68, vec![0x0f, 0b0000_1011]),
+            // Switching the 32kHz output clears none of the flags
+            Transaction::write_read(0x68, vec![0x0f], vec![0b1000_1000]),
+            Transaction::write(0x68, vec![0x0f, 0b1000_0011]),
+            Transaction::write_read(0x68, vec![0x0f], vec![0b0000_0000]),
+            Transaction::write(0x68, vec![0x0f, 0b1000_1011]),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        rtc.clear_oscillator_stop().unwrap();
+        rtc.clear_oscillator_stop().unwrap();
+        rtc.set_32khz_output(false).unwrap();
+        rtc.set_32khz_output(true).unwrap();
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn control() {
+        let bus = Mock::new(&[
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0001_1101]),
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0000_1010]),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        let control = rtc.read_control().unwrap();
+        assert_eq!(control, Control { rate: Rate::Hz8192, interrupt_control: true, alarm1_interrupt: true, alarm2_interrupt: false });
+        assert_eq!(control.square_wave(), None);
+        assert!(control.alarm_interrupt(Alarm::One));
+
+        let control = rtc.read_control().unwrap();
+        assert_eq!(control.square_wave(), Some(Rate::Hz1024));
+        // A2IE is set, but the pin is putting out the square wave
+        assert!(!control.alarm_interrupt(Alarm::Two));
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn square_wave_rates() {
+        let bus = Mock::new(&[
+            // Picking a rate clears INTCN, and turning the wave off sets it again
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0001_1101]),
+            Transaction::write(0x68, vec![0x0e, 0b0000_0001]),
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0000_0001]),
+            Transaction::write(0x68, vec![0x0e, 0b0001_0001]),
+            Transaction::write_read(0x68, vec![0x0e], vec![0b0001_0001]),
+            Transaction::write(0x68, vec![0x0e, 0b0001_0101]),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        rtc.set_square_wave(Some(Rate::Hz1)).unwrap();
+        rtc.set_square_wave(Some(Rate::Hz4096)).unwrap();
+        rtc.set_square_wave(None).unwrap();
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn bus_errors() {
+        let bus = Mock::new(&[
+            Transaction::write_read(0x68, vec![0x00], vec![0; 7]).with_error(MockError::Io(std::io::ErrorKind::Other)),
+        ]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        assert!(matches!(rtc.now(), Err(RtcError::Bus(MockError::Io(_)))));
+        rtc.release().0.done();
+    }
+}
+
+#[cfg(test)]
+mod at24c32 {
+    extern crate std;
+    use std::vec;
+    use embedded_hal_mock::eh0::delay::NoopDelay;
+    use embedded_hal_mock::eh0::i2c::{Mock, Transaction};
+    use crate::rtc::{Rtc, RtcError};
+
+    #[test]
+    fn write_page_must_be_aligned() {
+        let bus = Mock::new(&[]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        assert_eq!(rtc.write_page_eeprom([0x01, 0x21], [0; 32]), Err(RtcError::InvalidAddress));
+        rtc.release().0.done();
+    }
+
+    #[test]
+    fn write_page() {
+        let mut expected = vec![0x01, 0x20];
+        expected.extend_from_slice(&[0xab; 32]);
+        let bus = Mock::new(&[Transaction::write(0x57, expected)]);
+        let mut rtc = Rtc::new(bus, NoopDelay::new());
+
+        rtc.write_page_eeprom([0x01, 0x20], [0xab; 32]).unwrap();
+        rtc.release().0.done();
+    }
+}
+
+#[cfg(test)]
+mod simulated_module {
+    use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+    use crate::alarm::{Alarm, Match, Schedule, State};
+    use crate::clock::Clock;
+    use crate::counter::counter_record;
+    use crate::datetime::Datetime;
+    use crate::eeprom::{Storage, SLOT_COUNT};
+    use crate::otp::Algorithm;
+    use crate::rtc::{Rtc, RtcError};
+    use crate::sim::{self, Sim, SimBus, SimError};
+    use crate::sqw::{self, Rate};
+    use crate::slot::Slot;
+    use crate::wrap::{self, Record};
+
+    #[test]
+    fn set_and_read_the_time() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+
+        rtc.set(Datetime::from_timestamp(1_669_714_637).to_bytes()).unwrap();
+        sim.advance_ms(22_000);
+        assert_eq!(rtc.now().unwrap().unix_timestamp(), 1_669_714_659);
+    }
+
+    #[test]
+    fn setting_the_time_clears_a_stopped_oscillator() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+        assert_eq!(Clock::lost_time(&mut rtc), Ok(true));
+
+        // The raw register write leaves OSF for the caller
+        rtc.set(Datetime::from_timestamp(1_669_714_637).to_bytes()).unwrap();
+        assert_eq!(Clock::lost_time(&mut rtc), Ok(true));
+        Clock::set(&mut rtc, Datetime::from_timestamp(1_669_714_637)).unwrap();
+        assert_eq!(Clock::lost_time(&mut rtc), Ok(false));
+        assert!(rtc.read_status().unwrap().output_32khz);
+
+        sim.power_loss();
+        assert_eq!(Clock::lost_time(&mut rtc), Ok(true));
+    }
+
+    #[test]
+    fn alarms_fire() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+        // Tuesday 29/11/2022 08:17:17
+        Clock::set(&mut rtc, Datetime::from_timestamp(1_669_709_837)).unwrap();
+
+        let seconds = Schedule { matching: Match::Seconds, hours: 0, minutes: 0, seconds: 20 };
+        let every_minute = Schedule { matching: Match::EveryMinute, hours: 0, minutes: 0, seconds: 0 };
+        Clock::set_alarm(&mut rtc, Alarm::One, seconds).unwrap();
+        Clock::set_alarm(&mut rtc, Alarm::Two, every_minute).unwrap();
+        assert_eq!(Clock::alarm(&mut rtc, Alarm::One), Ok(State { schedule: Some(seconds), interrupt: true, fired: false }));
+
+        sim.advance_ms(2_000);
+        assert!(!sim.interrupt());
+        sim.advance_ms(1_000);
+        assert!(sim.interrupt());
+        assert!(Clock::alarm(&mut rtc, Alarm::One).unwrap().fired);
+        assert!(!Clock::alarm(&mut rtc, Alarm::Two).unwrap().fired);
+
+        // The flag holds INT/SQW low until it is cleared
+        Clock::clear_alarm(&mut rtc, Alarm::One).unwrap();
+        assert!(!sim.interrupt());
+        assert_eq!(Clock::alarm(&mut rtc, Alarm::One), Ok(State { schedule: Some(seconds), interrupt: false, fired: false }));
+
+        sim.advance_ms(39_000);
+        assert!(!sim.interrupt());
+        sim.advance_ms(1_000);
+        assert!(sim.interrupt());
+        assert!(Clock::alarm(&mut rtc, Alarm::Two).unwrap().fired);
+        // Alarm 1 still fires with its interrupt off, but only sets its flag
+        assert!(!Clock::alarm(&mut rtc, Alarm::One).unwrap().fired);
+        sim.advance_ms(20_000);
+        assert!(Clock::alarm(&mut rtc, Alarm::One).unwrap().fired);
+    }
+
+    // The simulated bus, but the clock moves on by `delay_ms` right after the first read of the
+    // status register, between the read and the write that clears a flag
+    struct SlowBus<'a> {
+        sim: &'a Sim,
+        bus: SimBus<'a>,
+        delay_ms: Option<u64>,
+    }
+
+    impl Write for SlowBus<'_> {
+        type Error = SimError;
+
+        fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), SimError> {
+            self.bus.write(address, bytes)
+        }
+    }
+
+    impl WriteRead for SlowBus<'_> {
+        type Error = SimError;
+
+        fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), SimError> {
+            self.bus.write_read(address, bytes, buffer)?;
+            if bytes == [sim::STATUS as u8] {
+                if let Some(delay_ms) = self.delay_ms.take() {
+                    self.sim.advance_ms(delay_ms);
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn clearing_a_flag_keeps_one_set_meanwhile() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+        // Tuesday 29/11/2022 08:17:58
+        Clock::set(&mut rtc, Datetime::from_timestamp(1_669_709_878)).unwrap();
+        Clock::set_alarm(&mut rtc, Alarm::One, Schedule { matching: Match::Seconds, hours: 0, minutes: 0, seconds: 59 }).unwrap();
+        Clock::set_alarm(&mut rtc, Alarm::Two, Schedule { matching: Match::EveryMinute, hours: 0, minutes: 0, seconds: 0 }).unwrap();
+        sim.advance_ms(1_000);
+        assert!(Clock::alarm(&mut rtc, Alarm::One).unwrap().fired);
+
+        // Alarm 2 fires at 08:18:00, after alarm 1's flag has been read but before it is cleared
+        let mut slow = Rtc::new(SlowBus { sim: &sim, bus: sim.bus(), delay_ms: Some(1_000) }, sim.delay());
+        slow.clear_alarm_flag(Alarm::One).unwrap();
+        assert!(!Clock::alarm(&mut rtc, Alarm::One).unwrap().fired);
+        assert!(Clock::alarm(&mut rtc, Alarm::Two).unwrap().fired);
+    }
+
+    #[test]
+    fn one_hertz_tick() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+        assert_eq!(Clock::square_wave(&mut rtc), Ok(sqw::State { square_wave: None, output_32khz: true }));
+
+        Clock::set_square_wave(&mut rtc, Some(Rate::Hz1)).unwrap();
+        Clock::set_32khz_output(&mut rtc, false).unwrap();
+        assert_eq!(Clock::square_wave(&mut rtc), Ok(sqw::State { square_wave: Some(Rate::Hz1), output_32khz: false }));
+
+        // Each falling edge is a new second
+        Clock::set(&mut rtc, Datetime::from_timestamp(1_669_714_637)).unwrap();
+        for second in 1..=3 {
+            sim.advance_ms(500);
+            assert!(sim.int_sqw());
+            sim.advance_ms(500);
+            assert!(!sim.int_sqw());
+            assert_eq!(rtc.now().unwrap().unix_timestamp(), 1_669_714_637 + second);
+        }
+
+        // Arming an alarm hands the pin back to it
+        let every_second = Schedule { matching: Match::EverySecond, hours: 0, minutes: 0, seconds: 0 };
+        Clock::set_alarm(&mut rtc, Alarm::One, every_second).unwrap();
+        assert_eq!(Clock::square_wave(&mut rtc).unwrap().square_wave, None);
+        sim.advance_ms(1000);
+        assert!(!sim.int_sqw());
+    }
+
+    #[test]
+    fn day_and_date_alarms() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+        // Monday 28/11/2022 23:59:59
+        Clock::set(&mut rtc, Datetime::from_timestamp(1_669_679_999)).unwrap();
+
+        let tuesday = Schedule { matching: Match::Day(2), hours: 0, minutes: 0, seconds: 0 };
+        let the_30th = Schedule { matching: Match::Date(30), hours: 0, minutes: 0, seconds: 0 };
+        Clock::set_alarm(&mut rtc, Alarm::One, tuesday).unwrap();
+        Clock::set_alarm(&mut rtc, Alarm::Two, the_30th).unwrap();
+
+        sim.advance_ms(1_000);
+        assert!(Clock::alarm(&mut rtc, Alarm::One).unwrap().fired);
+        assert!(!Clock::alarm(&mut rtc, Alarm::Two).unwrap().fired);
+        sim.advance_ms(86_400_000);
+        assert!(Clock::alarm(&mut rtc, Alarm::Two).unwrap().fired);
+    }
+
+    #[test]
+    fn temperature() {
+        let sim = Sim::new();
+        sim.set_temperature(25 * 4 + 3);
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+
+        assert_eq!(rtc.update_temperature(), Ok(true));
+        assert_eq!(rtc.update_temperature(), Ok(false));
+        sim.advance_ms(200);
+        assert_eq!(rtc.read_temperature(), Ok((25, 3)));
+    }
+
+    #[test]
+    fn key_round_trip() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+
+        let mut key = [0_u8; 256];
+        key[0..10].copy_from_slice(b"Hello!\xde\xad\xbe\xef");
+        rtc.write_key_eeprom(10, key, None).unwrap();
+        rtc.write_totp_config_eeprom(30, 0).unwrap();
+
+        assert_eq!(rtc.read_key_eeprom().unwrap(), (10, key));
+        assert_eq!(rtc.read_totp_config_eeprom().unwrap(), Some((30, 0)));
+        assert!(rtc.read_seal_eeprom(Record::Key).unwrap().is_none());
+        assert_eq!(sim.eeprom()[0x00..0x02], [10, 0xff]);
+    }
+
+    #[test]
+    fn erased_eeprom_is_not_configured() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+
+        assert_eq!(rtc.read_totp_config_eeprom().unwrap(), None);
+        assert_eq!(rtc.read_counter_eeprom(None).unwrap(), None);
+        assert_eq!(rtc.read_chalresp_eeprom().unwrap(), None);
+        assert_eq!(rtc.read_failed_attempts_eeprom().unwrap(), 0);
+    }
+
+    #[test]
+    fn counters_alternate_records() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+
+        for counter in 1..=3 {
+            rtc.write_counter_eeprom(Some(2), counter).unwrap();
+            assert_eq!(rtc.read_counter_eeprom(Some(2)).unwrap(), Some(counter));
+        }
+        rtc.write_counter_eeprom(Some(2), 1).unwrap();
+        assert_eq!(rtc.read_counter_eeprom(Some(2)).unwrap(), Some(1));
+        assert_eq!(rtc.read_counter_eeprom(Some(3)).unwrap(), None);
+    }
+
+    #[test]
+    fn counter_overwrites_stale_record() {
+        let sim = Sim::new();
+        let mut image = [0xff_u8; 4096];
+        image[0x140..0x160].copy_from_slice(&counter_record(5));
+        sim.load_eeprom(&image);
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+
+        rtc.write_counter_eeprom(None, 6).unwrap();
+        assert_eq!(sim.eeprom()[0x140..0x160], counter_record(5));
+        assert_eq!(sim.eeprom()[0x160..0x180], counter_record(6));
+    }
+
+    #[test]
+    fn slots_out_of_range() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+
+        assert_eq!(rtc.read_slot_eeprom(SLOT_COUNT).err(), Some(RtcError::InvalidAddress));
+        assert_eq!(rtc.erase_slot_eeprom(SLOT_COUNT), Err(RtcError::InvalidAddress));
+        assert_eq!(sim.eeprom(), [0xff; 4096]);
+    }
+
+    #[test]
+    fn nonces_are_never_reused() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+
+        assert_eq!(rtc.next_nonce_eeprom().unwrap(), 0);
+        assert_eq!(rtc.next_nonce_eeprom().unwrap(), 1);
+
+        // Survives a power cycle, since it lives in the EEPROM
+        let restarted = Sim::new();
+        restarted.load_eeprom(&sim.eeprom());
+        let mut rtc = Rtc::new(restarted.bus(), restarted.delay());
+        assert_eq!(rtc.next_nonce_eeprom().unwrap(), 2);
+    }
+
+    #[test]
+    fn sealed_slot_round_trip() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+        let wrap_key = [7_u8; 32];
+
+        let slot = Slot::new(b"mail", b"example", Algorithm::Sha1, 6, 30, 0, b"12345678901234567890").unwrap();
+        let mut bytes = slot.to_bytes();
+        let counter = rtc.next_nonce_eeprom().unwrap();
+        let seal = wrap::seal(&wrap_key, Record::Slot(SLOT_COUNT - 1), counter, &slot.header(), &mut bytes[0x40..]);
+        rtc.write_slot_eeprom(SLOT_COUNT - 1, bytes, Some(seal)).unwrap();
+
+        let mut stored = rtc.read_slot_eeprom(SLOT_COUNT - 1).unwrap();
+        let seal = rtc.read_seal_eeprom(Record::Slot(SLOT_COUNT - 1)).unwrap().unwrap();
+        wrap::open(&wrap_key, Record::Slot(SLOT_COUNT - 1), &seal, &slot.header(), &mut stored[0x40..]).unwrap();
+        assert_eq!(Slot::from_bytes(&stored).unwrap().secret(), slot.secret());
+
+        rtc.erase_slot_eeprom(SLOT_COUNT - 1).unwrap();
+        assert!(Slot::from_bytes(&rtc.read_slot_eeprom(SLOT_COUNT - 1).unwrap()).is_none());
+        assert!(rtc.read_seal_eeprom(Record::Slot(SLOT_COUNT - 1)).unwrap().is_none());
+    }
+
+    #[test]
+    fn writes_without_waiting_are_refused() {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+
+        rtc.write_byte_eeprom([0x00, 0x00], 10).unwrap();
+        assert_eq!(rtc.write_byte_eeprom([0x00, 0x01], 10), Err(RtcError::Bus(SimError::Nack(0x57))));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::byte_helper;
+use crate::digest::Digest;
+use crate::otp;
+pub use crate::otp::OtpError;
+
+const BLOCK_SIZE: usize = 64;
+const PAD_TARGET: usize = 56;
+pub const DIGEST_SIZE: usize = 20;
+
+pub fn gen_sha1_hotp(key: &[u8], counter: u64, digits: u32) -> Result<u32, OtpError> {
+    otp::gen_hotp::<Sha1>(key, counter, digits)
+}
+
+pub fn gen_sha1_hmac(key: &[u8], message: &[u8]) -> Result<[u8; DIGEST_SIZE], OtpError> {
+    Ok(otp::gen_hmac::<Sha1>(key, message))
+}
+
+pub fn gen_sha1_digest(message: &[u8]) -> Result<[u8; DIGEST_SIZE], OtpError> {
+    let mut hasher = Sha1::new();
+    hasher.update(message);
+    Ok(hasher.finalize())
+}
+
+// Incremental SHA-1 hasher - feed any number of slices with `update` and
+// read the digest with `finalize`. Only one block is ever buffered.
+#[derive(Clone)]
+pub struct Sha1 {
+    h: [u32; 5],
+    block: [u8; BLOCK_SIZE],
+    block_length: usize,
+    message_length: u64,
+}
+
+impl Sha1 {
+    pub fn new() -> Self {
+        // **********************************************
+        // *** 5.3.1 - Setting the Initial Hash Value ***
+        // **********************************************
+        Self {
+            h: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0],
+            block: [0; BLOCK_SIZE],
+            block_length: 0,
+            message_length: 0,
+        }
+    }
+
+    pub fn update(&mut self, message: &[u8]) {
+        self.message_length += message.len() as u64;
+
+        for byte in message {
+            self.block[self.block_length] = *byte;
+            self.block_length += 1;
+
+            if self.block_length == BLOCK_SIZE {
+                self.process_block();
+            }
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+        // ***********************************
+        // *** 5.1.1 - Padding the Message ***
+        // ***********************************
+        let bit_length: u64 = self.message_length * 8;
+
+        // Add the 0x80 padding byte, then 0x00 bytes until there is room for the bit-length
+        self.block[self.block_length] = 0x80;
+        self.block_length += 1;
+        if self.block_length > PAD_TARGET {
+            self.block[self.block_length..].fill(0);
+            self.process_block();
+        }
+        self.block[self.block_length..PAD_TARGET].fill(0);
+
+        // Add bit-length bytes
+        self.block[PAD_TARGET..].copy_from_slice(&byte_helper::u64_to_bytes(bit_length));
+        self.process_block();
+
+        // Compile the h-array into the 20-byte digest
+        let mut digest = [0_u8; DIGEST_SIZE];
+        for (i, h_value) in self.h.iter().enumerate() {
+            digest[i*4..i*4+4].copy_from_slice(&byte_helper::u32_to_bytes(*h_value));
+        }
+
+        digest
+    }
+
+    // **************************************
+    // *** 6.1.2 - SHA-1 Hash Computation ***
+    // **************************************
+    fn process_block(&mut self) {
+        // 1. Prepare the message schedule (W)
+        let mut w = [0_u32; 80];
+
+        // The first 16 words of W are the 32-bit word representations of the current 64-bytes
+        for (t, word_bytes) in self.block.chunks(4).enumerate() {
+            let mut m_bytes = [0_u8; 4];
+            m_bytes.copy_from_slice(word_bytes);
+            w[t] = byte_helper::bytes_to_u32(m_bytes);
+        }
+
+        // Fill out the remainder of the message schedule
+        for t in 16..80 {
+            w[t] = rotate_left(w[t-3] ^ w[t-8] ^ w[t-14] ^ w[t-16], 1);
+        }
+
+        // 2. Initialize working variables
+        let [mut a, mut b, mut c, mut d, mut e] = self.h;
+
+        // 3. Process message schedule
+        for t in 0..80 {
+            let temp: u32 = rotate_left(a, 5)
+            .wrapping_add(f(t, b, c, d))
+            .wrapping_add(e)
+            .wrapping_add(k(t))
+            .wrapping_add(w[t]);
+
+            [e, d, c, b, a] = [d, c, rotate_left(b, 30), a, temp];
+        }
+
+        // 4. Compute Intermediate Hash Value
+        for (h_value, working_value) in self.h.iter_mut().zip([a, b, c, d, e]) {
+            *h_value = h_value.wrapping_add(working_value);
+        }
+
+        self.block_length = 0;
+    }
+}
+
+impl Digest for Sha1 {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = DIGEST_SIZE;
+
+    type Output = [u8; DIGEST_SIZE];
+
+    fn new() -> Self {
+        Sha1::new()
+    }
+
+    fn update(&mut self, message: &[u8]) {
+        Sha1::update(self, message)
+    }
+
+    fn finalize(self) -> Self::Output {
+        Sha1::finalize(self)
+    }
+}
+    
+fn f(t: usize, x: u32, y: u32, z: u32) -> u32 {
+    match t {
+         0 ..= 19 => choose(x, y, z),
+        20 ..= 39 => parity(x, y, z),
+        40 ..= 59 => majority(x, y, z),
+            _     => parity(x, y, z),
+    }
+}
+
+fn choose(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (!x & z)
+}
+
+fn parity(x: u32, y: u32, z: u32) -> u32 {
+    x ^ y ^ z
+}
+
+fn majority(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (x & z) | (y & z)
+}
+
+fn rotate_left(x: u32, n: u8) -> u32 {
+    x.rotate_left(n as u32)
+}
+
+fn k(t: usize) -> u32 {
+    const K: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+    K[t / 20]
+}
\ No newline at end of file
@@ -0,0 +1,192 @@
+use crate::byte_helper;
+use crate::digest::Digest;
+use crate::otp::{self, OtpError};
+use crate::progmem;
+
+const BLOCK_SIZE: usize = 64;
+const PAD_TARGET: usize = 56;
+pub const DIGEST_SIZE: usize = 32;
+
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+    0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+pub fn gen_sha256_hotp(key: &[u8], counter: u64, digits: u32) -> Result<u32, OtpError> {
+    otp::gen_hotp::<Sha256>(key, counter, digits)
+}
+
+pub fn gen_sha256_hmac(key: &[u8], message: &[u8]) -> Result<[u8; DIGEST_SIZE], OtpError> {
+    Ok(otp::gen_hmac::<Sha256>(key, message))
+}
+
+pub fn gen_sha256_digest(message: &[u8]) -> Result<[u8; DIGEST_SIZE], OtpError> {
+    let mut hasher = Sha256::new();
+    hasher.update(message);
+    Ok(hasher.finalize())
+}
+
+// Incremental SHA-256 hasher - see `sha1::Sha1`
+#[derive(Clone)]
+pub struct Sha256 {
+    h: [u32; 8],
+    block: [u8; BLOCK_SIZE],
+    block_length: usize,
+    message_length: u64,
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        // **********************************************
+        // *** 5.3.3 - Setting the Initial Hash Value ***
+        // **********************************************
+        Self {
+            h: H0,
+            block: [0; BLOCK_SIZE],
+            block_length: 0,
+            message_length: 0,
+        }
+    }
+
+    pub fn update(&mut self, message: &[u8]) {
+        self.message_length += message.len() as u64;
+
+        for byte in message {
+            self.block[self.block_length] = *byte;
+            self.block_length += 1;
+
+            if self.block_length == BLOCK_SIZE {
+                self.process_block();
+            }
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+        // ***********************************
+        // *** 5.1.1 - Padding the Message ***
+        // ***********************************
+        let bit_length: u64 = self.message_length * 8;
+
+        // Add the 0x80 padding byte, then 0x00 bytes until there is room for the bit-length
+        self.block[self.block_length] = 0x80;
+        self.block_length += 1;
+        if self.block_length > PAD_TARGET {
+            self.block[self.block_length..].fill(0);
+            self.process_block();
+        }
+        self.block[self.block_length..BLOCK_SIZE - 8].fill(0);
+
+        // Add bit-length bytes
+        self.block[BLOCK_SIZE - 8..].copy_from_slice(&byte_helper::u64_to_bytes(bit_length));
+        self.process_block();
+
+        // Compile the h-array into the 32-byte digest
+        let mut digest = [0_u8; DIGEST_SIZE];
+        for (i, h_value) in self.h.iter().enumerate() {
+            digest[i*4..i*4+4].copy_from_slice(&byte_helper::u32_to_bytes(*h_value));
+        }
+
+        digest
+    }
+
+    // ****************************************
+    // *** 6.2.2 - SHA-256 Hash Computation ***
+    // ****************************************
+    fn process_block(&mut self) {
+        // 1. Prepare the message schedule (W), keeping only the last 16 words as in 6.1.3, and
+        // expanding the rest as they are used
+        let mut w = [0_u32; 16];
+
+        // The first 16 words of W are the 32-bit word representations of the current 64-bytes
+        for (t, word_bytes) in self.block.chunks(4).enumerate() {
+            let mut m_bytes = [0_u8; 4];
+            m_bytes.copy_from_slice(word_bytes);
+            w[t] = byte_helper::bytes_to_u32(m_bytes);
+        }
+
+
+        // 2. Initialize working variables
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h_var] = self.h;
+
+        // 3. Process message schedule
+        for t in 0..64 {
+            if t >= 16 {
+                w[t & 15] = small_sigma_1(w[(t - 2) & 15])
+                .wrapping_add(w[(t - 7) & 15])
+                .wrapping_add(small_sigma_0(w[(t - 15) & 15]))
+                .wrapping_add(w[t & 15]);
+            }
+
+            let temp_1: u32 = h_var
+            .wrapping_add(big_sigma_1(e))
+            .wrapping_add(choose(e, f, g))
+            .wrapping_add(progmem::load_u32(&K[t]))
+            .wrapping_add(w[t & 15]);
+            let temp_2: u32 = big_sigma_0(a).wrapping_add(majority(a, b, c));
+
+            [h_var, g, f, e, d, c, b, a] = [g, f, e, d.wrapping_add(temp_1), c, b, a, temp_1.wrapping_add(temp_2)];
+        }
+
+        // 4. Compute Intermediate Hash Value
+        for (h_value, working_value) in self.h.iter_mut().zip([a, b, c, d, e, f, g, h_var]) {
+            *h_value = h_value.wrapping_add(working_value);
+        }
+
+        self.block_length = 0;
+    }
+}
+
+impl Digest for Sha256 {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = DIGEST_SIZE;
+
+    type Output = [u8; DIGEST_SIZE];
+
+    fn new() -> Self {
+        Sha256::new()
+    }
+
+    fn update(&mut self, message: &[u8]) {
+        Sha256::update(self, message)
+    }
+
+    fn finalize(self) -> Self::Output {
+        Sha256::finalize(self)
+    }
+}
+
+fn choose(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) ^ (!x & z)
+}
+
+fn majority(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) ^ (x & z) ^ (y & z)
+}
+
+fn big_sigma_0(x: u32) -> u32 {
+    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+}
+
+fn big_sigma_1(x: u32) -> u32 {
+    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+}
+
+fn small_sigma_0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+fn small_sigma_1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+#[cfg_attr(target_arch = "avr", link_section = ".progmem.data")]
+static K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
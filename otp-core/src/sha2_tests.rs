@@ -0,0 +1,97 @@
+#[cfg(test)]
+fn hex<const N: usize>(hexstring: &str) -> [u8; N] {
+    let hexstring = hexstring.as_bytes();
+    let mut bytes = [0_u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = crate::byte_helper::hex_to_byte([hexstring[i*2], hexstring[i*2+1]]);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod creating_sha256_digests {
+    use super::hex;
+    use crate::sha256;
+
+    #[test]
+    fn abc() {
+        let msg = b"abc";
+        let validator = hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        let digest = sha256::gen_sha256_digest(msg).unwrap();
+        assert_eq!(digest, validator);
+    }
+
+    #[test]
+    fn padding_spills_into_next_block() {
+        let msg = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        let validator = hex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+        let digest = sha256::gen_sha256_digest(msg).unwrap();
+        assert_eq!(digest, validator);
+    }
+
+    #[test]
+    fn one_megabyte() {
+        let msg = [b'a'; 1_000_000];
+        let validator = hex("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0");
+        let digest = sha256::gen_sha256_digest(&msg).unwrap();
+        assert_eq!(digest, validator);
+    }
+}
+
+#[cfg(test)]
+mod creating_sha512_digests {
+    use super::hex;
+    use crate::sha512;
+
+    #[test]
+    fn abc() {
+        let msg = b"abc";
+        let validator = hex("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f");
+        let digest = sha512::gen_sha512_digest(msg).unwrap();
+        assert_eq!(digest, validator);
+    }
+
+    #[test]
+    fn padding_spills_into_next_block() {
+        let msg = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+        let validator = hex("8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909");
+        let digest = sha512::gen_sha512_digest(msg).unwrap();
+        assert_eq!(digest, validator);
+    }
+}
+
+#[cfg(test)]
+mod creating_hmac {
+    use super::hex;
+    use crate::{sha256, sha512};
+
+    #[test]
+    fn sha256_simple() {
+        let validator = hex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        let digest = sha256::gen_sha256_hmac(b"Jefe", b"what do ya want for nothing?").unwrap();
+        assert_eq!(digest, validator);
+    }
+
+    #[test]
+    fn sha256_key_larger_than_blocksize() {
+        let key = [0xaa_u8; 131];
+        let validator = hex("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54");
+        let digest = sha256::gen_sha256_hmac(&key, b"Test Using Larger Than Block-Size Key - Hash Key First").unwrap();
+        assert_eq!(digest, validator);
+    }
+
+    #[test]
+    fn sha512_simple() {
+        let validator = hex("164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737");
+        let digest = sha512::gen_sha512_hmac(b"Jefe", b"what do ya want for nothing?").unwrap();
+        assert_eq!(digest, validator);
+    }
+
+    #[test]
+    fn sha512_key_larger_than_blocksize() {
+        let key = [0xaa_u8; 131];
+        let validator = hex("80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598");
+        let digest = sha512::gen_sha512_hmac(&key, b"Test Using Larger Than Block-Size Key - Hash Key First").unwrap();
+        assert_eq!(digest, validator);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::byte_helper;
+use crate::digest::Digest;
+use crate::otp::{self, OtpError};
+use crate::progmem;
+
+const BLOCK_SIZE: usize = 128;
+const PAD_TARGET: usize = 112;
+pub const DIGEST_SIZE: usize = 64;
+
+const H0: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+pub fn gen_sha512_hotp(key: &[u8], counter: u64, digits: u32) -> Result<u32, OtpError> {
+    otp::gen_hotp::<Sha512>(key, counter, digits)
+}
+
+pub fn gen_sha512_hmac(key: &[u8], message: &[u8]) -> Result<[u8; DIGEST_SIZE], OtpError> {
+    Ok(otp::gen_hmac::<Sha512>(key, message))
+}
+
+pub fn gen_sha512_digest(message: &[u8]) -> Result<[u8; DIGEST_SIZE], OtpError> {
+    let mut hasher = Sha512::new();
+    hasher.update(message);
+    Ok(hasher.finalize())
+}
+
+// Incremental SHA-512 hasher - see `sha1::Sha1`
+#[derive(Clone)]
+pub struct Sha512 {
+    h: [u64; 8],
+    block: [u8; BLOCK_SIZE],
+    block_length: usize,
+    message_length: u64,
+}
+
+impl Sha512 {
+    pub fn new() -> Self {
+        // **********************************************
+        // *** 5.3.5 - Setting the Initial Hash Value ***
+        // **********************************************
+        Self {
+            h: H0,
+            block: [0; BLOCK_SIZE],
+            block_length: 0,
+            message_length: 0,
+        }
+    }
+
+    pub fn update(&mut self, message: &[u8]) {
+        self.message_length += message.len() as u64;
+
+        for byte in message {
+            self.block[self.block_length] = *byte;
+            self.block_length += 1;
+
+            if self.block_length == BLOCK_SIZE {
+                self.process_block();
+            }
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+        // ***********************************
+        // *** 5.1.2 - Padding the Message ***
+        // ***********************************
+        let bit_length: u64 = self.message_length * 8;
+
+        // Add the 0x80 padding byte, then 0x00 bytes until there is room for the bit-length
+        self.block[self.block_length] = 0x80;
+        self.block_length += 1;
+        if self.block_length > PAD_TARGET {
+            self.block[self.block_length..].fill(0);
+            self.process_block();
+        }
+        self.block[self.block_length..BLOCK_SIZE - 8].fill(0);
+
+        // Add bit-length bytes
+        // (the upper 8 bytes of the 128-bit length are always zero for our message sizes)
+        self.block[BLOCK_SIZE - 8..].copy_from_slice(&byte_helper::u64_to_bytes(bit_length));
+        self.process_block();
+
+        // Compile the h-array into the 64-byte digest
+        let mut digest = [0_u8; DIGEST_SIZE];
+        for (i, h_value) in self.h.iter().enumerate() {
+            digest[i*8..i*8+8].copy_from_slice(&byte_helper::u64_to_bytes(*h_value));
+        }
+
+        digest
+    }
+
+    // ****************************************
+    // *** 6.4.2 - SHA-512 Hash Computation ***
+    // ****************************************
+    fn process_block(&mut self) {
+        // 1. Prepare the message schedule (W), keeping only the last 16 words as in 6.1.3, and
+        // expanding the rest as they are used
+        let mut w = [0_u64; 16];
+
+        // The first 16 words of W are the 64-bit word representations of the current 128-bytes
+        for (t, word_bytes) in self.block.chunks(8).enumerate() {
+            let mut m_bytes = [0_u8; 8];
+            m_bytes.copy_from_slice(word_bytes);
+            w[t] = byte_helper::bytes_to_u64(m_bytes);
+        }
+
+
+        // 2. Initialize working variables
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h_var] = self.h;
+
+        // 3. Process message schedule
+        for t in 0..80 {
+            if t >= 16 {
+                w[t & 15] = small_sigma_1(w[(t - 2) & 15])
+                .wrapping_add(w[(t - 7) & 15])
+                .wrapping_add(small_sigma_0(w[(t - 15) & 15]))
+                .wrapping_add(w[t & 15]);
+            }
+
+            let temp_1: u64 = h_var
+            .wrapping_add(big_sigma_1(e))
+            .wrapping_add(choose(e, f, g))
+            .wrapping_add(progmem::load_u64(&K[t]))
+            .wrapping_add(w[t & 15]);
+            let temp_2: u64 = big_sigma_0(a).wrapping_add(majority(a, b, c));
+
+            [h_var, g, f, e, d, c, b, a] = [g, f, e, d.wrapping_add(temp_1), c, b, a, temp_1.wrapping_add(temp_2)];
+        }
+
+        // 4. Compute Intermediate Hash Value
+        for (h_value, working_value) in self.h.iter_mut().zip([a, b, c, d, e, f, g, h_var]) {
+            *h_value = h_value.wrapping_add(working_value);
+        }
+
+        self.block_length = 0;
+    }
+}
+
+impl Digest for Sha512 {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = DIGEST_SIZE;
+
+    type Output = [u8; DIGEST_SIZE];
+
+    fn new() -> Self {
+        Sha512::new()
+    }
+
+    fn update(&mut self, message: &[u8]) {
+        Sha512::update(self, message)
+    }
+
+    fn finalize(self) -> Self::Output {
+        Sha512::finalize(self)
+    }
+}
+
+fn choose(x: u64, y: u64, z: u64) -> u64 {
+    (x & y) ^ (!x & z)
+}
+
+fn majority(x: u64, y: u64, z: u64) -> u64 {
+    (x & y) ^ (x & z) ^ (y & z)
+}
+
+fn big_sigma_0(x: u64) -> u64 {
+    x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39)
+}
+
+fn big_sigma_1(x: u64) -> u64 {
+    x.rotate_right(14) ^ x.rotate_right(18) ^ x.rotate_right(41)
+}
+
+fn small_sigma_0(x: u64) -> u64 {
+    x.rotate_right(1) ^ x.rotate_right(8) ^ (x >> 7)
+}
+
+fn small_sigma_1(x: u64) -> u64 {
+    x.rotate_right(19) ^ x.rotate_right(61) ^ (x >> 6)
+}
+
+#[cfg_attr(target_arch = "avr", link_section = ".progmem.data")]
+static K: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
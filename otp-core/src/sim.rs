@@ -0,0 +1,456 @@
+// A simulated DS3231 and AT24C32, as found together on the common ZS-042 RTC module, for running
+// the `rtc` driver and the shell without hardware. Time only moves when the simulation is
+// advanced, either directly or through `SimDelay`.
+use core::cell::RefCell;
+use embedded_hal::blocking::{delay::DelayMs, i2c::{Read, Write, WriteRead}};
+use ufmt::derive::uDebug;
+
+pub const DS3231_I2C_ADDRESS: u8 = 0x68;
+pub const EEPROM_I2C_ADDRESS: u8 = 0x57;
+
+pub const REGISTER_COUNT: usize = 0x13;
+pub const EEPROM_SIZE: usize = 4096;
+pub const EEPROM_PAGE_SIZE: usize = 32;
+
+// AT24C32 write cycle time and DS3231 temperature conversion time, both worst case
+pub const WRITE_CYCLE_MS: u64 = 10;
+pub const CONVERSION_MS: u64 = 200;
+
+// Control register 0x0e
+pub const CONTROL: usize = 0x0e;
+pub const CONTROL_CONV: u8 = 0b0010_0000;
+pub const CONTROL_RS: u8 = 0b0001_1000;
+pub const CONTROL_INTCN: u8 = 0b0000_0100;
+pub const CONTROL_A2IE: u8 = 0b0000_0010;
+pub const CONTROL_A1IE: u8 = 0b0000_0001;
+// Status register 0x0f
+pub const STATUS: usize = 0x0f;
+pub const STATUS_OSF: u8 = 0b1000_0000;
+pub const STATUS_EN32KHZ: u8 = 0b0000_1000;
+pub const STATUS_BSY: u8 = 0b0000_0100;
+pub const STATUS_A2F: u8 = 0b0000_0010;
+pub const STATUS_A1F: u8 = 0b0000_0001;
+const TEMPERATURE_MSB: usize = 0x11;
+const TEMPERATURE_LSB: usize = 0x12;
+
+// Register values after the DS3231 loses all power: 1900/1/1 00:00:00, the oscillator
+// stop flag set and the 32kHz output on
+const POWER_ON_REGISTERS: [u8; REGISTER_COUNT] = [
+    0x00, 0x00, 0x00, 0x01, 0x01, 0x01, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0b0001_1100, STATUS_OSF | STATUS_EN32KHZ, 0x00, 0x00, 0x00,
+];
+
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub enum SimError {
+    // Nothing acknowledged the address, either because there is no device there
+    // or because the EEPROM is busy with a write cycle
+    Nack(u8),
+}
+
+struct Device {
+    registers: [u8; REGISTER_COUNT],
+    register_pointer: usize,
+    eeprom: [u8; EEPROM_SIZE],
+    eeprom_pointer: usize,
+    // Milliseconds since the simulation started, and since the last whole second
+    millis: u64,
+    sub_second: u64,
+    uptime_seconds: u64,
+    eeprom_busy_until: u64,
+    conversion_until: Option<u64>,
+    // Quarter degrees Celsius, latched into the temperature registers by a conversion
+    temperature: i16,
+}
+
+impl Device {
+    fn new() -> Self {
+        Self {
+            registers: POWER_ON_REGISTERS,
+            register_pointer: 0,
+            eeprom: [0xff; EEPROM_SIZE],
+            eeprom_pointer: 0,
+            millis: 0,
+            sub_second: 0,
+            uptime_seconds: 0,
+            eeprom_busy_until: 0,
+            conversion_until: None,
+            temperature: 25 * 4,
+        }
+    }
+
+    fn eeprom_ready(&self) -> Result<(), SimError> {
+        match self.millis < self.eeprom_busy_until {
+            true => Err(SimError::Nack(EEPROM_I2C_ADDRESS)),
+            false => Ok(()),
+        }
+    }
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), SimError> {
+        match address {
+            DS3231_I2C_ADDRESS => {
+                if let Some((pointer, data)) = bytes.split_first() {
+                    self.register_pointer = *pointer as usize % REGISTER_COUNT;
+                    for byte in data {
+                        self.write_register(self.register_pointer, *byte);
+                        self.register_pointer = (self.register_pointer + 1) % REGISTER_COUNT;
+                    }
+                }
+                Ok(())
+            },
+            EEPROM_I2C_ADDRESS => {
+                self.eeprom_ready()?;
+                if bytes.len() < 2 {
+                    return Ok(());
+                }
+
+                // Only the low 12 bits of the address are used
+                self.eeprom_pointer = ((bytes[0] as usize) << 8 | bytes[1] as usize) % EEPROM_SIZE;
+                let data = &bytes[2..];
+                if data.is_empty() {
+                    return Ok(());
+                }
+
+                // Writes wrap around within the page instead of running on into the next one
+                let page = self.eeprom_pointer - self.eeprom_pointer % EEPROM_PAGE_SIZE;
+                let mut offset = self.eeprom_pointer % EEPROM_PAGE_SIZE;
+                for byte in data {
+                    self.eeprom[page + offset] = *byte;
+                    offset = (offset + 1) % EEPROM_PAGE_SIZE;
+                }
+                self.eeprom_pointer = page + offset;
+                self.eeprom_busy_until = self.millis + WRITE_CYCLE_MS;
+
+                Ok(())
+            },
+            _ => Err(SimError::Nack(address)),
+        }
+    }
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), SimError> {
+        match address {
+            DS3231_I2C_ADDRESS => {
+                for byte in buffer.iter_mut() {
+                    *byte = self.read_register(self.register_pointer);
+                    self.register_pointer = (self.register_pointer + 1) % REGISTER_COUNT;
+                }
+                Ok(())
+            },
+            EEPROM_I2C_ADDRESS => {
+                self.eeprom_ready()?;
+                // Sequential reads roll over from the end of the memory to the start
+                for byte in buffer.iter_mut() {
+                    *byte = self.eeprom[self.eeprom_pointer];
+                    self.eeprom_pointer = (self.eeprom_pointer + 1) % EEPROM_SIZE;
+                }
+                Ok(())
+            },
+            _ => Err(SimError::Nack(address)),
+        }
+    }
+
+    fn read_register(&self, register: usize) -> u8 {
+        match register {
+            STATUS if self.conversion_until.is_some() => self.registers[STATUS] | STATUS_BSY,
+            _ => self.registers[register],
+        }
+    }
+
+    fn write_register(&mut self, register: usize, value: u8) {
+        match register {
+            // Writing the seconds restarts the countdown to the next second
+            0x00 => {
+                self.registers[0x00] = value & 0x7f;
+                self.sub_second = 0;
+            },
+            CONTROL => {
+                let forced = value & CONTROL_CONV != 0 && self.conversion_until.is_none();
+                self.registers[CONTROL] = match forced {
+                    true => value,
+                    false => (value & !CONTROL_CONV) | (self.registers[CONTROL] & CONTROL_CONV),
+                };
+                if forced {
+                    self.conversion_until = Some(self.millis + CONVERSION_MS);
+                }
+            },
+            // OSF and the alarm flags can only be cleared, and BSY is read-only
+            STATUS => {
+                let flags = STATUS_OSF | STATUS_A2F | STATUS_A1F;
+                let current = self.registers[STATUS];
+                self.registers[STATUS] = (current & flags & value) | (value & STATUS_EN32KHZ);
+            },
+            TEMPERATURE_MSB | TEMPERATURE_LSB => {},
+            _ => self.registers[register] = value,
+        }
+    }
+
+    fn advance_ms(&mut self, ms: u64) {
+        let end = self.millis + ms;
+        while self.millis < end {
+            // Step to the next second or to the end of a conversion, whichever comes first
+            let mut step = (end - self.millis).min(1000 - self.sub_second);
+            if let Some(until) = self.conversion_until {
+                step = step.min(until - self.millis);
+            }
+
+            self.millis += step;
+            self.sub_second += step;
+            if self.conversion_until == Some(self.millis) {
+                self.finish_conversion();
+            }
+            if self.sub_second == 1000 {
+                self.sub_second = 0;
+                self.tick();
+                self.check_alarms();
+            }
+        }
+    }
+
+    fn finish_conversion(&mut self) {
+        self.conversion_until = None;
+        self.registers[TEMPERATURE_MSB] = (self.temperature >> 2) as u8;
+        self.registers[TEMPERATURE_LSB] = ((self.temperature & 0b11) as u8) << 6;
+        self.registers[CONTROL] &= !CONTROL_CONV;
+    }
+
+    // Set A1F/A2F if the new time matches the alarm's unmasked registers. Alarm 2 has no
+    // seconds register, and matches at 00 seconds.
+    fn check_alarms(&mut self) {
+        let registers = &self.registers;
+        let alarm1 = [registers[0x07], registers[0x08], registers[0x09], registers[0x0a]];
+        let alarm2 = [0x00, registers[0x0b], registers[0x0c], registers[0x0d]];
+        if self.alarm_matches(alarm1) {
+            self.registers[STATUS] |= STATUS_A1F;
+        }
+        if self.alarm_matches(alarm2) {
+            self.registers[STATUS] |= STATUS_A2F;
+        }
+    }
+
+    // Bit 7 masks a register out, and bit 6 of the last picks the day of the week over the date
+    fn alarm_matches(&self, alarm: [u8; 4]) -> bool {
+        let time = &self.registers;
+        let day_or_date = match alarm[3] & 0b0100_0000 != 0 {
+            true => time[0x03],
+            false => time[0x04],
+        };
+        let current = [time[0x00], time[0x01], time[0x02], day_or_date];
+
+        alarm.iter().zip(current).zip([0x7f, 0x7f, 0x7f, 0x3f])
+            .all(|((alarm, current), bits)| alarm & 0b1000_0000 != 0 || alarm & bits == current)
+    }
+
+    // Count one second through the BCD time registers
+    fn tick(&mut self) {
+        // The DS3231 converts the temperature by itself every 64 seconds
+        self.uptime_seconds += 1;
+        if self.uptime_seconds % 64 == 0 && self.conversion_until.is_none() {
+            self.conversion_until = Some(self.millis + CONVERSION_MS);
+        }
+
+        let registers = &mut self.registers;
+        let seconds = from_bcd(registers[0x00]) + 1;
+        if seconds < 60 {
+            registers[0x00] = to_bcd(seconds);
+            return;
+        }
+        registers[0x00] = 0;
+
+        let minutes = from_bcd(registers[0x01]) + 1;
+        if minutes < 60 {
+            registers[0x01] = to_bcd(minutes);
+            return;
+        }
+        registers[0x01] = 0;
+
+        // Bit 6 selects 12-hour time, where bit 5 is the PM flag and the day starts at 12 AM
+        if registers[0x02] & 0b0100_0000 != 0 {
+            let pm = registers[0x02] & 0b0010_0000 != 0;
+            let (hours, pm) = match from_bcd(registers[0x02] & 0b0001_1111) {
+                11 => (12, !pm),
+                12 => (1, pm),
+                hours => (hours + 1, pm),
+            };
+            registers[0x02] = 0b0100_0000 | if pm { 0b0010_0000 } else { 0 } | to_bcd(hours);
+            if hours != 12 || pm {
+                return;
+            }
+        }
+        else {
+            let hours = from_bcd(registers[0x02] & 0b0011_1111) + 1;
+            if hours < 24 {
+                registers[0x02] = to_bcd(hours);
+                return;
+            }
+            registers[0x02] = 0;
+        }
+
+        registers[0x03] = registers[0x03] % 7 + 1;
+
+        // Leap years are every fourth year, which holds for the 2000s
+        let year = from_bcd(registers[0x06]);
+        let month = from_bcd(registers[0x05] & 0b0001_1111);
+        let days_in_month = match month {
+            2 if year % 4 == 0 => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        };
+        let date = from_bcd(registers[0x04]) + 1;
+        if date <= days_in_month {
+            registers[0x04] = to_bcd(date);
+            return;
+        }
+        registers[0x04] = 1;
+
+        let century = registers[0x05] & 0b1000_0000;
+        if month < 12 {
+            registers[0x05] = century | to_bcd(month + 1);
+            return;
+        }
+
+        // The century bit toggles when the years roll over from 99 to 00
+        let year = (year + 1) % 100;
+        registers[0x05] = match year {
+            0 => (century ^ 0b1000_0000) | 1,
+            _ => century | 1,
+        };
+        registers[0x06] = to_bcd(year);
+    }
+}
+
+fn from_bcd(byte: u8) -> u8 {
+    (byte >> 4) * 10 + (byte & 0b1111)
+}
+
+fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+// Owns the simulated module. Hand `bus()` and `delay()` to `rtc::Rtc`, then use the
+// other methods to inspect or disturb the devices from outside the bus.
+pub struct Sim {
+    device: RefCell<Device>,
+}
+
+impl Sim {
+    pub fn new() -> Self {
+        Self { device: RefCell::new(Device::new()) }
+    }
+
+    pub fn bus(&self) -> SimBus<'_> {
+        SimBus { device: &self.device }
+    }
+
+    pub fn delay(&self) -> SimDelay<'_> {
+        SimDelay { device: &self.device }
+    }
+
+    pub fn advance_ms(&self, ms: u64) {
+        self.device.borrow_mut().advance_ms(ms);
+    }
+
+    // Milliseconds since the simulation started
+    pub fn millis(&self) -> u64 {
+        self.device.borrow().millis
+    }
+
+    pub fn registers(&self) -> [u8; REGISTER_COUNT] {
+        let device = self.device.borrow();
+        let mut registers = device.registers;
+        registers[STATUS] = device.read_register(STATUS);
+        registers
+    }
+
+    // Return: true while an alarm that fired is pulling INT/SQW low
+    pub fn interrupt(&self) -> bool {
+        let registers = self.device.borrow().registers;
+        // A1IE and A2IE line up with A1F and A2F
+        let enabled = match registers[CONTROL] & CONTROL_INTCN != 0 {
+            true => registers[CONTROL] & (CONTROL_A1IE | CONTROL_A2IE),
+            false => 0,
+        };
+
+        registers[STATUS] & enabled != 0
+    }
+
+    // Return: the level of INT/SQW. While INTCN is set it is high until an alarm pulls it low,
+    // and otherwise it carries the square wave, which falls as each second starts. The faster
+    // waves are only sampled each millisecond, so they alias.
+    pub fn int_sqw(&self) -> bool {
+        let device = self.device.borrow();
+        let control = device.registers[CONTROL];
+        if control & CONTROL_INTCN != 0 {
+            drop(device);
+            return !self.interrupt();
+        }
+
+        let hertz = [1, 1024, 4096, 8192][((control & CONTROL_RS) >> 3) as usize];
+        let half_periods = device.sub_second * hertz * 2 / 1000;
+        half_periods % 2 == 1
+    }
+
+    pub fn eeprom(&self) -> [u8; EEPROM_SIZE] {
+        self.device.borrow().eeprom
+    }
+
+    pub fn load_eeprom(&self, image: &[u8; EEPROM_SIZE]) {
+        self.device.borrow_mut().eeprom = *image;
+    }
+
+    // Set the temperature picked up by the next conversion, in quarter degrees Celsius
+    pub fn set_temperature(&self, quarter_degrees: i16) {
+        self.device.borrow_mut().temperature = quarter_degrees;
+    }
+
+    // Lose both main and battery power. The clock restarts from its power-on state with
+    // OSF set, while the EEPROM keeps its contents.
+    pub fn power_loss(&self) {
+        let mut device = self.device.borrow_mut();
+        device.registers = POWER_ON_REGISTERS;
+        device.register_pointer = 0;
+        device.sub_second = 0;
+        device.conversion_until = None;
+    }
+}
+
+pub struct SimBus<'a> {
+    device: &'a RefCell<Device>,
+}
+
+impl Write for SimBus<'_> {
+    type Error = SimError;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), SimError> {
+        self.device.borrow_mut().write(address, bytes)
+    }
+}
+
+impl Read for SimBus<'_> {
+    type Error = SimError;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), SimError> {
+        self.device.borrow_mut().read(address, buffer)
+    }
+}
+
+impl WriteRead for SimBus<'_> {
+    type Error = SimError;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), SimError> {
+        let mut device = self.device.borrow_mut();
+        device.write(address, bytes)?;
+        device.read(address, buffer)
+    }
+}
+
+// Waiting on the simulated module moves its clock forward
+pub struct SimDelay<'a> {
+    device: &'a RefCell<Device>,
+}
+
+impl DelayMs<u16> for SimDelay<'_> {
+    fn delay_ms(&mut self, ms: u16) {
+        self.device.borrow_mut().advance_ms(ms as u64);
+    }
+}
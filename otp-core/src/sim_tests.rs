@@ -0,0 +1,223 @@
+#[cfg(test)]
+mod ds3231_registers {
+    use embedded_hal::blocking::i2c::{Write, WriteRead};
+    use crate::datetime::Datetime;
+    use crate::sim::{Sim, SimError, STATUS, STATUS_OSF, STATUS_EN32KHZ, STATUS_BSY, STATUS_A1F, CONTROL, CONTROL_CONV};
+
+    fn set_time(sim: &Sim, timestamp: u64) {
+        sim.bus().write(0x68, &Datetime::from_timestamp(timestamp).to_bytes()).unwrap();
+    }
+
+    fn timestamp(sim: &Sim) -> u64 {
+        let mut registers = [0_u8; 7];
+        sim.bus().write_read(0x68, &[0x00], &mut registers).unwrap();
+        Datetime::from_bytes(registers).unix_timestamp()
+    }
+
+    #[test]
+    fn power_on_state() {
+        let sim = Sim::new();
+        let registers = sim.registers();
+        assert_eq!(registers[0..7], [0x00, 0x00, 0x00, 0x01, 0x01, 0x01, 0x00]);
+        assert_eq!(registers[STATUS], STATUS_OSF | STATUS_EN32KHZ);
+    }
+
+    #[test]
+    fn unknown_address_nacks() {
+        let sim = Sim::new();
+        assert_eq!(sim.bus().write(0x50, &[0x00]), Err(SimError::Nack(0x50)));
+    }
+
+    #[test]
+    fn time_counts_seconds() {
+        let sim = Sim::new();
+        set_time(&sim, 1_700_000_000);
+        sim.advance_ms(999);
+        assert_eq!(timestamp(&sim), 1_700_000_000);
+        sim.advance_ms(1);
+        assert_eq!(timestamp(&sim), 1_700_000_001);
+        sim.advance_ms(3_600_000);
+        assert_eq!(timestamp(&sim), 1_700_003_601);
+    }
+
+    #[test]
+    fn writing_seconds_restarts_the_second() {
+        let sim = Sim::new();
+        set_time(&sim, 1_700_000_000);
+        sim.advance_ms(700);
+        set_time(&sim, 1_700_000_000);
+        sim.advance_ms(700);
+        assert_eq!(timestamp(&sim), 1_700_000_000);
+        sim.advance_ms(300);
+        assert_eq!(timestamp(&sim), 1_700_000_001);
+    }
+
+    #[test]
+    fn calendar_rollovers() {
+        let sim = Sim::new();
+        // 2024/02/28 23:59:59, into the leap day
+        set_time(&sim, 1_709_164_799);
+        sim.advance_ms(1000);
+        assert_eq!(timestamp(&sim), 1_709_164_800);
+        assert_eq!(sim.registers()[4..6], [0x29, 0x82]);
+
+        // Thursday 2099/12/31 23:59:59, into the next century
+        set_time(&sim, 4_102_444_799);
+        sim.advance_ms(1000);
+        assert_eq!(sim.registers()[0..7], [0x00, 0x00, 0x00, 0x05, 0x01, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn twelve_hour_time() {
+        let sim = Sim::new();
+        // 11:59:59 PM
+        sim.bus().write(0x68, &[0x00, 0x59, 0x59, 0b0111_0001, 0x07, 0x31, 0x01, 0x24]).unwrap();
+        sim.advance_ms(1000);
+        // 12:00:00 AM on the next day, which wraps back to day 1
+        assert_eq!(sim.registers()[0..7], [0x00, 0x00, 0b0101_0010, 0x01, 0x01, 0x02, 0x24]);
+
+        // 11:59:59 AM to 12:00:00 PM stays on the same day
+        sim.bus().write(0x68, &[0x00, 0x59, 0x59, 0b0101_0001]).unwrap();
+        sim.advance_ms(1000);
+        assert_eq!(sim.registers()[2..5], [0b0111_0010, 0x01, 0x01]);
+    }
+
+    #[test]
+    fn register_pointer_wraps() {
+        let sim = Sim::new();
+        let mut registers = [0_u8; 3];
+        sim.bus().write_read(0x68, &[0x12], &mut registers).unwrap();
+        assert_eq!(registers[1..3], [0x00, 0x00]);
+    }
+
+    #[test]
+    fn oscillator_stop_flag() {
+        let sim = Sim::new();
+        set_time(&sim, 1_700_000_000);
+
+        // Writing 1 leaves the flag alone, writing 0 clears it
+        sim.bus().write(0x68, &[0x0f, STATUS_OSF | STATUS_A1F]).unwrap();
+        assert_eq!(sim.registers()[STATUS], STATUS_OSF);
+        sim.bus().write(0x68, &[0x0f, 0x00]).unwrap();
+        assert_eq!(sim.registers()[STATUS], 0x00);
+
+        sim.power_loss();
+        assert_eq!(sim.registers()[STATUS] & STATUS_OSF, STATUS_OSF);
+        assert_eq!(timestamp(&sim), Datetime { seconds: 0, minutes: 0, hours: 0, date: 1, month: 1, year: 1900 }.unix_timestamp());
+    }
+
+    #[test]
+    fn forced_temperature_conversion() {
+        let sim = Sim::new();
+        sim.set_temperature(-5 * 4 + 1);
+        sim.bus().write(0x68, &[0x0e, 0b0011_1100]).unwrap();
+        assert_eq!(sim.registers()[STATUS] & STATUS_BSY, STATUS_BSY);
+        assert_eq!(sim.registers()[CONTROL] & CONTROL_CONV, CONTROL_CONV);
+
+        sim.advance_ms(200);
+        let registers = sim.registers();
+        assert_eq!(registers[STATUS] & STATUS_BSY, 0);
+        assert_eq!(registers[CONTROL] & CONTROL_CONV, 0);
+        assert_eq!(registers[0x11..0x13], [-5_i8 as u8, 0b0100_0000]);
+
+        // The temperature registers are read-only
+        sim.bus().write(0x68, &[0x11, 0x00, 0x00]).unwrap();
+        assert_eq!(sim.registers()[0x11], -5_i8 as u8);
+    }
+
+    #[test]
+    fn one_hertz_square_wave() {
+        let sim = Sim::new();
+        // Power-on has INTCN set, with nothing pulling INT/SQW low
+        assert!(sim.int_sqw());
+
+        sim.bus().write(0x68, &[0x0e, 0b0000_0000]).unwrap();
+        set_time(&sim, 1_700_000_000);
+        sim.advance_ms(499);
+        assert!(!sim.int_sqw());
+        sim.advance_ms(1);
+        assert!(sim.int_sqw());
+        // The wave falls as the seconds register counts up
+        sim.advance_ms(499);
+        assert!(sim.int_sqw());
+        sim.advance_ms(1);
+        assert!(!sim.int_sqw());
+        assert_eq!(timestamp(&sim), 1_700_000_001);
+    }
+
+    #[test]
+    fn automatic_temperature_conversion() {
+        let sim = Sim::new();
+        sim.set_temperature(30 * 4);
+        sim.advance_ms(64_000);
+        assert_eq!(sim.registers()[STATUS] & STATUS_BSY, STATUS_BSY);
+        sim.advance_ms(200);
+        assert_eq!(sim.registers()[0x11], 30);
+    }
+}
+
+#[cfg(test)]
+mod at24c32 {
+    use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+    use crate::sim::{Sim, SimError, EEPROM_SIZE, WRITE_CYCLE_MS};
+
+    #[test]
+    fn erased() {
+        let sim = Sim::new();
+        let mut page = [0_u8; 32];
+        sim.bus().write_read(0x57, &[0x00, 0x00], &mut page).unwrap();
+        assert_eq!(page, [0xff; 32]);
+    }
+
+    #[test]
+    fn busy_during_write_cycle() {
+        let sim = Sim::new();
+        sim.bus().write(0x57, &[0x00, 0x20, 0xab]).unwrap();
+
+        let mut byte = [0_u8; 1];
+        assert_eq!(sim.bus().write_read(0x57, &[0x00, 0x20], &mut byte), Err(SimError::Nack(0x57)));
+        assert_eq!(sim.bus().write(0x57, &[0x00, 0x21, 0xcd]), Err(SimError::Nack(0x57)));
+
+        sim.advance_ms(WRITE_CYCLE_MS);
+        let mut bytes = [0_u8; 2];
+        sim.bus().write_read(0x57, &[0x00, 0x20], &mut bytes).unwrap();
+        assert_eq!(bytes, [0xab, 0xff]);
+    }
+
+    #[test]
+    fn setting_the_address_is_not_a_write() {
+        let sim = Sim::new();
+        sim.bus().write(0x57, &[0x00, 0x20]).unwrap();
+        let mut byte = [0_u8; 1];
+        sim.bus().read(0x57, &mut byte).unwrap();
+        assert_eq!(byte, [0xff]);
+    }
+
+    #[test]
+    fn page_write_wraps_within_the_page() {
+        let sim = Sim::new();
+        let mut bytes = [0x00, 0x3e, 0, 0, 0, 0];
+        bytes[2..6].copy_from_slice(&[1, 2, 3, 4]);
+        sim.bus().write(0x57, &bytes).unwrap();
+        sim.advance_ms(WRITE_CYCLE_MS);
+
+        let eeprom = sim.eeprom();
+        assert_eq!(eeprom[0x3e..0x40], [1, 2]);
+        assert_eq!(eeprom[0x20..0x22], [3, 4]);
+        assert_eq!(eeprom[0x40], 0xff);
+    }
+
+    #[test]
+    fn sequential_read_rolls_over() {
+        let sim = Sim::new();
+        let mut image = [0xff; EEPROM_SIZE];
+        image[EEPROM_SIZE - 1] = 1;
+        image[0] = 2;
+        sim.load_eeprom(&image);
+
+        // The top 4 address bits are ignored
+        let mut bytes = [0_u8; 2];
+        sim.bus().write_read(0x57, &[0xff, 0xff], &mut bytes).unwrap();
+        assert_eq!(bytes, [1, 2]);
+    }
+}
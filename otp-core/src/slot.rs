@@ -0,0 +1,147 @@
+use crate::byte_helper;
+use crate::otp::{Algorithm, Format};
+
+// Each key slot takes 128 bytes (four EEPROM pages)
+// 0x00 => in-use marker: u8
+// 0x01 => algorithm: u8
+// 0x02 => digits: u8
+// 0x03 => secret length: u8
+// 0x04 => label length: u8
+// 0x05 => issuer length: u8
+// 0x06 => code format: u8 (1 for Steam, anything else is decimal)
+// 0x08..0x10 => period: u64
+// 0x10..0x18 => T0: u64
+// 0x20..0x30 => label: [u8; 16]
+// 0x30..0x40 => issuer: [u8; 16]
+// 0x40..0x80 => secret: [u8; 64]
+// Erased or deleted slots don't hold the in-use marker
+// When a PIN is set the secret is sealed, with the header (0x00..0x40) as associated data
+pub const SLOT_SIZE: usize = 128;
+pub const SLOT_HEADER_SIZE: usize = 0x40;
+pub const SLOT_LABEL_SIZE: usize = 16;
+pub const SLOT_SECRET_SIZE: usize = 64;
+
+const IN_USE: u8 = 0x5a;
+
+#[derive(Clone, Copy)]
+pub struct Slot {
+    pub label: [u8; SLOT_LABEL_SIZE],
+    pub label_length: usize,
+    pub issuer: [u8; SLOT_LABEL_SIZE],
+    pub issuer_length: usize,
+    pub algorithm: Algorithm,
+    pub digits: u8,
+    pub format: Format,
+    pub period: u64,
+    pub t0: u64,
+    pub secret: [u8; SLOT_SECRET_SIZE],
+    pub secret_length: usize,
+}
+
+impl Slot {
+    // Return: None if any field is too long to fit in a slot
+    // New slots use decimal codes - set `format` afterwards for anything else
+    pub fn new(label: &[u8], issuer: &[u8], algorithm: Algorithm, digits: u8, period: u64, t0: u64, secret: &[u8]) -> Option<Self> {
+        if label.len() > SLOT_LABEL_SIZE || issuer.len() > SLOT_LABEL_SIZE || secret.len() > SLOT_SECRET_SIZE {
+            return None;
+        }
+
+        let mut slot = Slot {
+            label: [0; SLOT_LABEL_SIZE],
+            label_length: label.len(),
+            issuer: [0; SLOT_LABEL_SIZE],
+            issuer_length: issuer.len(),
+            algorithm, digits, period, t0,
+            format: Format::Decimal,
+            secret: [0; SLOT_SECRET_SIZE],
+            secret_length: secret.len(),
+        };
+        slot.label[0..label.len()].copy_from_slice(label);
+        slot.issuer[0..issuer.len()].copy_from_slice(issuer);
+        slot.secret[0..secret.len()].copy_from_slice(secret);
+
+        Some(slot)
+    }
+
+    pub fn label(&self) -> &[u8] {
+        &self.label[0..self.label_length]
+    }
+
+    pub fn issuer(&self) -> &[u8] {
+        &self.issuer[0..self.issuer_length]
+    }
+
+    pub fn secret(&self) -> &[u8] {
+        &self.secret[0..self.secret_length]
+    }
+
+    // Everything but the secret, as it is stored
+    pub fn header(&self) -> [u8; SLOT_HEADER_SIZE] {
+        let mut header = [0_u8; SLOT_HEADER_SIZE];
+        header.copy_from_slice(&self.to_bytes()[0..SLOT_HEADER_SIZE]);
+        header
+    }
+
+    // Return: None for an empty slot or one that doesn't decode
+    pub fn from_bytes(bytes: &[u8; SLOT_SIZE]) -> Option<Self> {
+        if bytes[0] != IN_USE {
+            return None;
+        }
+
+        let algorithm = match bytes[1] {
+            0 => Algorithm::Sha1,
+            1 => Algorithm::Sha256,
+            2 => Algorithm::Sha512,
+            _ => return None,
+        };
+        let secret_length = bytes[3] as usize;
+        let label_length = bytes[4] as usize;
+        let issuer_length = bytes[5] as usize;
+
+        let mut period_bytes = [0_u8; 8];
+        period_bytes.copy_from_slice(&bytes[0x08..0x10]);
+        let mut t0_bytes = [0_u8; 8];
+        t0_bytes.copy_from_slice(&bytes[0x10..0x18]);
+
+        let mut slot = Slot::new(
+            bytes[0x20..0x30].get(0..label_length)?,
+            bytes[0x30..0x40].get(0..issuer_length)?,
+            algorithm,
+            bytes[2],
+            byte_helper::bytes_to_u64(period_bytes),
+            byte_helper::bytes_to_u64(t0_bytes),
+            bytes[0x40..0x80].get(0..secret_length)?,
+        )?;
+        // Slots written before formats existed hold 0xff here
+        if bytes[6] == 1 {
+            slot.format = Format::Steam;
+        }
+
+        Some(slot)
+    }
+
+    pub fn to_bytes(&self) -> [u8; SLOT_SIZE] {
+        let mut bytes = [0xff_u8; SLOT_SIZE];
+        bytes[0] = IN_USE;
+        bytes[1] = match self.algorithm {
+            Algorithm::Sha1 => 0,
+            Algorithm::Sha256 => 1,
+            Algorithm::Sha512 => 2,
+        };
+        bytes[2] = self.digits;
+        bytes[3] = self.secret_length as u8;
+        bytes[4] = self.label_length as u8;
+        bytes[5] = self.issuer_length as u8;
+        bytes[6] = match self.format {
+            Format::Decimal => 0,
+            Format::Steam => 1,
+        };
+        bytes[0x08..0x10].copy_from_slice(&byte_helper::u64_to_bytes(self.period));
+        bytes[0x10..0x18].copy_from_slice(&byte_helper::u64_to_bytes(self.t0));
+        bytes[0x20..0x20 + self.label_length].copy_from_slice(self.label());
+        bytes[0x30..0x30 + self.issuer_length].copy_from_slice(self.issuer());
+        bytes[0x40..0x40 + self.secret_length].copy_from_slice(self.secret());
+
+        bytes
+    }
+}
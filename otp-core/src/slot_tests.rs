@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod slot_table {
+    use crate::otp::Algorithm;
+    use crate::slot::{Slot, SLOT_SIZE};
+
+    #[test]
+    fn round_trip() {
+        let slot = Slot::new(b"mail", b"Example", Algorithm::Sha256, 8, 60, 15, b"12345678901234567890").unwrap();
+        let decoded = Slot::from_bytes(&slot.to_bytes()).unwrap();
+
+        assert_eq!(decoded.label(), b"mail");
+        assert_eq!(decoded.issuer(), b"Example");
+        assert!(decoded.algorithm == Algorithm::Sha256);
+        assert_eq!(decoded.digits, 8);
+        assert_eq!(decoded.period, 60);
+        assert_eq!(decoded.t0, 15);
+        assert_eq!(decoded.secret(), b"12345678901234567890");
+    }
+
+    #[test]
+    fn erased_slot_is_empty() {
+        assert!(Slot::from_bytes(&[0xff; SLOT_SIZE]).is_none());
+    }
+
+    #[test]
+    fn oversized_fields_are_rejected() {
+        assert!(Slot::new(b"a label that is too long", b"", Algorithm::Sha1, 6, 30, 0, b"key").is_none());
+        assert!(Slot::new(b"mail", b"", Algorithm::Sha1, 6, 30, 0, &[0; 65]).is_none());
+
+        let mut bytes = Slot::new(b"mail", b"", Algorithm::Sha1, 6, 30, 0, b"key").unwrap().to_bytes();
+        bytes[3] = 65;
+        assert!(Slot::from_bytes(&bytes).is_none());
+    }
+}
@@ -0,0 +1,68 @@
+use ufmt::derive::uDebug;
+
+// The DS3231's square wave outputs. INT/SQW puts out a square wave at the rate picked by RS2
+// and RS1 in the control register while INTCN is clear, and is the alarm interrupt while it is
+// set. The 1Hz wave falls as the seconds register counts up, so it can serve as a timebase.
+// The 32kHz pin is separate, and turned on by EN32kHz in the status register.
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub enum Rate {
+    Hz1,
+    Hz1024,
+    Hz4096,
+    Hz8192,
+}
+
+// RS2 and RS1 are bits 4 and 3 of the control register
+const RATE_SHIFT: u8 = 3;
+const RATE_BITS: u8 = 0b0001_1000;
+
+impl Rate {
+    pub fn from_control(control: u8) -> Self {
+        match (control & RATE_BITS) >> RATE_SHIFT {
+            0b00 => Rate::Hz1,
+            0b01 => Rate::Hz1024,
+            0b10 => Rate::Hz4096,
+            _ => Rate::Hz8192,
+        }
+    }
+
+    // Return: the control register with RS2 and RS1 set for this rate
+    pub fn to_control(self, control: u8) -> u8 {
+        let bits = match self {
+            Rate::Hz1 => 0b00,
+            Rate::Hz1024 => 0b01,
+            Rate::Hz4096 => 0b10,
+            Rate::Hz8192 => 0b11,
+        };
+
+        (control & !RATE_BITS) | (bits << RATE_SHIFT)
+    }
+
+    pub fn hertz(self) -> u16 {
+        match self {
+            Rate::Hz1 => 1,
+            Rate::Hz1024 => 1024,
+            Rate::Hz4096 => 4096,
+            Rate::Hz8192 => 8192,
+        }
+    }
+
+    // Return: None unless `text` is one of the rates in hertz
+    pub fn parse(text: &[u8]) -> Option<Self> {
+        match text {
+            b"1" => Some(Rate::Hz1),
+            b"1024" => Some(Rate::Hz1024),
+            b"4096" => Some(Rate::Hz4096),
+            b"8192" => Some(Rate::Hz8192),
+            _ => None,
+        }
+    }
+}
+
+// What the shell shows for the outputs
+#[derive(Debug, uDebug, Clone, Copy, PartialEq)]
+pub struct State {
+    // None while INT/SQW is the alarm interrupt
+    pub square_wave: Option<Rate>,
+    pub output_32khz: bool,
+}
@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod rates {
+    use crate::sqw::Rate;
+
+    #[test]
+    fn rate_select_bits() {
+        // RS2 and RS1 are bits 4 and 3, and the other bits are kept
+        assert_eq!(Rate::Hz1.to_control(0b0001_1100), 0b0000_0100);
+        assert_eq!(Rate::Hz1024.to_control(0b0000_0000), 0b0000_1000);
+        assert_eq!(Rate::Hz4096.to_control(0b1000_0011), 0b1001_0011);
+        assert_eq!(Rate::Hz8192.to_control(0b0000_0000), 0b0001_1000);
+
+        for rate in [Rate::Hz1, Rate::Hz1024, Rate::Hz4096, Rate::Hz8192] {
+            assert_eq!(Rate::from_control(rate.to_control(0b1110_0111)), rate);
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Rate::parse(b"1"), Some(Rate::Hz1));
+        assert_eq!(Rate::parse(b"1024"), Some(Rate::Hz1024));
+        assert_eq!(Rate::parse(b"4096"), Some(Rate::Hz4096));
+        assert_eq!(Rate::parse(b"8192"), Some(Rate::Hz8192));
+        assert_eq!(Rate::parse(b"8192").map(Rate::hertz), Some(8192));
+        assert_eq!(Rate::parse(b"2"), None);
+        assert_eq!(Rate::parse(b"1k"), None);
+        assert_eq!(Rate::parse(b""), None);
+    }
+}
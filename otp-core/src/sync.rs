@@ -0,0 +1,46 @@
+use crate::byte_helper;
+
+// The `sync` handshake sets the clock from the host's to well within a second:
+// 1. The console prints `Sync <nonce>`, with a fresh nonce as 16 hex digits
+// 2. The host replies `<nonce> <UNIX time in milliseconds>`, stamped just before it is sent
+// 3. The console times the round trip, and takes the host's time to be half of it past the stamp
+// 4. It waits for the next whole second and writes it to the DS3231, which restarts its
+//    countdown to the following second from that moment
+// The reply is not authenticated, so changes that need the PIN need it given with `sync <PIN>`
+pub const NONCE_LENGTH: usize = 16;
+// The nonce, a space and a millisecond timestamp of up to 20 digits
+pub const MAX_REPLY_LENGTH: usize = NONCE_LENGTH + 1 + 20;
+// How long the console waits for the host's reply
+pub const REPLY_TIMEOUT_MS: u16 = 2000;
+
+pub fn encode_nonce(nonce: u64) -> [u8; NONCE_LENGTH] {
+    let mut encoded = [0_u8; NONCE_LENGTH];
+    for (i, byte) in byte_helper::u64_to_bytes(nonce).iter().enumerate() {
+        encoded[i * 2..i * 2 + 2].copy_from_slice(&byte_helper::byte_to_hex(*byte));
+    }
+
+    encoded
+}
+
+// Return: the host's time in milliseconds, or None if the reply is malformed or answers
+// a different nonce
+pub fn parse_reply(reply: &[u8], nonce: u64) -> Option<u64> {
+    if reply.len() <= NONCE_LENGTH || reply[NONCE_LENGTH] != b' ' {
+        return None;
+    }
+
+    let (echoed, host_ms) = (&reply[0..NONCE_LENGTH], &reply[NONCE_LENGTH + 1..]);
+    match echoed.eq_ignore_ascii_case(&encode_nonce(nonce)) {
+        true => byte_helper::parse_u64(host_ms).ok(),
+        false => None,
+    }
+}
+
+// Work out when to set the clock, given the host's stamp and the round trip it took to arrive
+// Return: (milliseconds to wait, UNIX timestamp to set once they have passed)
+pub fn schedule(host_ms: u64, round_trip_ms: u16) -> (u16, u64) {
+    let now_ms = host_ms.saturating_add(round_trip_ms as u64 / 2);
+    let second = now_ms / 1000 + 1;
+
+    ((second * 1000 - now_ms) as u16, second)
+}
@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod handshake {
+    use crate::sync;
+
+    #[test]
+    fn nonce_is_sixteen_hex_digits() {
+        assert_eq!(&sync::encode_nonce(0), b"0000000000000000");
+        assert_eq!(&sync::encode_nonce(0x0123_4567_89ab_cdef), b"0123456789abcdef");
+    }
+
+    #[test]
+    fn reply_must_answer_the_nonce() {
+        assert_eq!(sync::parse_reply(b"000000000000002a 1669714637250", 42), Some(1_669_714_637_250));
+        assert_eq!(sync::parse_reply(b"00000000000000AB 5", 0xab), Some(5));
+        assert_eq!(sync::parse_reply(b"000000000000002b 1669714637250", 42), None);
+    }
+
+    #[test]
+    fn malformed_replies() {
+        assert_eq!(sync::parse_reply(b"", 42), None);
+        assert_eq!(sync::parse_reply(b"000000000000002a", 42), None);
+        assert_eq!(sync::parse_reply(b"000000000000002a ", 42), None);
+        assert_eq!(sync::parse_reply(b"000000000000002a-1669714637250", 42), None);
+        assert_eq!(sync::parse_reply(b"000000000000002a 16697146372x0", 42), None);
+        assert_eq!(sync::parse_reply(b"2a 1669714637250", 42), None);
+        assert_eq!(sync::parse_reply(b"000000000000002a 1669714637250 0123", 42), None);
+        assert_eq!(sync::parse_reply(b"000000000000002a 1669714637250 ", 42), None);
+    }
+
+    #[test]
+    fn waits_for_the_next_second() {
+        assert_eq!(sync::schedule(1_669_714_637_250, 0), (750, 1_669_714_638));
+        // Half the round trip has already gone by when the reply arrives
+        assert_eq!(sync::schedule(1_669_714_637_250, 100), (700, 1_669_714_638));
+        assert_eq!(sync::schedule(1_669_714_637_950, 200), (950, 1_669_714_639));
+        // A stamp right on a second still waits for the next one, rather than setting it late
+        assert_eq!(sync::schedule(1_669_714_637_000, 0), (1000, 1_669_714_638));
+    }
+}
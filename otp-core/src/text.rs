@@ -0,0 +1,81 @@
+// Fixed text for the shell. The ATmega328P only has 2KB of SRAM, so on AVR the text is kept in
+// program memory and read back a chunk at a time with LPM when it is displayed.
+// Everywhere else it is ordinary static data.
+use ufmt::{uDisplay, uWrite, Formatter};
+
+use crate::progmem::load;
+
+const CHUNK_SIZE: usize = 16;
+
+#[derive(Clone, Copy)]
+pub struct Text {
+    // On AVR this points into program memory, so it is only ever read through `load`
+    bytes: &'static [u8],
+}
+
+impl Text {
+    pub const fn new(bytes: &'static [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+impl uDisplay for Text {
+    fn fmt<W: uWrite + ?Sized>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error> {
+        let mut chunk = [0_u8; CHUNK_SIZE];
+        for start in (0..self.bytes.len()).step_by(CHUNK_SIZE) {
+            let length = CHUNK_SIZE.min(self.bytes.len() - start);
+            for (i, byte) in chunk[0..length].iter_mut().enumerate() {
+                *byte = load(self.bytes.as_ptr().wrapping_add(start + i));
+            }
+
+            // `to_bytes` only lets ASCII through, so any chunk is valid UTF-8
+            f.write_str(core::str::from_utf8(&chunk[0..length]).unwrap_or(""))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Copy a string into a byte array for `text!`, refusing anything but ASCII at compile time
+pub const fn to_bytes<const N: usize>(text: &str) -> [u8; N] {
+    let bytes = text.as_bytes();
+    let mut array = [0_u8; N];
+    let mut i = 0;
+    while i < N {
+        assert!(bytes[i].is_ascii(), "shell text must be ASCII");
+        array[i] = bytes[i];
+        i += 1;
+    }
+
+    array
+}
+
+// text!("...") gives a `Text` for a string literal.
+// text! { static NAME = "..."; } declares named text that is used in several places.
+#[macro_export]
+macro_rules! text {
+    ($(static $name:ident = $text:expr;)+) => {
+        $(
+            static $name: $crate::text::Text = {
+                const TEXT: &str = $text;
+                #[cfg_attr(target_arch = "avr", link_section = ".progmem.data")]
+                static BYTES: [u8; TEXT.len()] = $crate::text::to_bytes(TEXT);
+                $crate::text::Text::new(&BYTES)
+            };
+        )+
+    };
+    ($text:expr) => {{
+        const TEXT: &str = $text;
+        #[cfg_attr(target_arch = "avr", link_section = ".progmem.data")]
+        static BYTES: [u8; TEXT.len()] = $crate::text::to_bytes(TEXT);
+        $crate::text::Text::new(&BYTES)
+    }};
+}
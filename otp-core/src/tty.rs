@@ -0,0 +1,2161 @@
+use core::convert::Infallible;
+use embedded_hal::serial::Read;
+use ufmt::uWrite;
+
+use crate::{command, otp};
+use crate::clock::Clock;
+use crate::eeprom::Storage;
+use crate::chacha20poly1305::KEY_SIZE;
+
+// Long enough for a typical otpauth:// URI
+const BUFFER_SIZE: usize = 256;
+const LABEL_SIZE: usize = 32;
+const OCRA_SUITE_SIZE: usize = 64;
+const DEFAULT_OCRA_SUITE: &[u8] = b"OCRA-1:HOTP-SHA1-6:QN08";
+
+// The console reads bytes one at a time and writes text with ufmt, e.g. the Arduino's USART
+pub trait Serial: Read<u8> + uWrite<Error = Infallible> {}
+
+impl<T: Read<u8> + uWrite<Error = Infallible>> Serial for T {}
+
+// The clock and EEPROM behind the console, e.g. the DS3231 module
+pub trait Backend: Clock + Storage {}
+
+impl<T: Clock + Storage> Backend for T {}
+
+pub struct TTY<S, B> {
+    // The line being typed is kept apart from the rest of the shell, so a command can borrow
+    // its parameters from it without a copy of the line
+    buffer: [u8; BUFFER_SIZE],
+    cursor_position: usize,
+    context: Context<S, B>,
+}
+
+// Everything a command can see and change
+struct Context<S, B> {
+    serial: S,
+    rtc: B,
+    key: [u8; 256],
+    key_length: usize,
+    digits: u8,
+    format: otp::Format,
+    algorithm: otp::Algorithm,
+    period: u64,
+    t0: u64,
+    counter: u64,
+    slot: Option<usize>,
+    // True while the key was entered but not saved, so its counter is only kept in RAM
+    unsaved: bool,
+    window: u8,
+    look_ahead: u8,
+    issuer: [u8; LABEL_SIZE],
+    issuer_length: usize,
+    account: [u8; LABEL_SIZE],
+    account_length: usize,
+    ocra_suite: [u8; OCRA_SUITE_SIZE],
+    ocra_suite_length: usize,
+    pin_set: bool,
+    locked: bool,
+    idle_timeout: u32,
+    last_activity: u64,
+    wrap_key: Option<[u8; KEY_SIZE]>,
+    // False once the RTC has lost time, until the time is set again
+    clock_trusted: bool,
+}
+
+impl<S: Serial, B: Backend> TTY<S, B> {
+    pub fn new(serial: S, rtc: B) -> Self {
+        let mut context = Context {
+            serial,
+            rtc,
+            key: [0; 256],
+            key_length: 0,
+            digits: 6,
+            format: otp::Format::Decimal,
+            algorithm: otp::Algorithm::Sha1,
+            period: 30,
+            t0: 0,
+            counter: 0,
+            slot: None,
+            unsaved: false,
+            window: 1,
+            look_ahead: 10,
+            issuer: [0; LABEL_SIZE],
+            issuer_length: 0,
+            account: [0; LABEL_SIZE],
+            account_length: 0,
+            ocra_suite: [0; OCRA_SUITE_SIZE],
+            ocra_suite_length: DEFAULT_OCRA_SUITE.len(),
+            pin_set: false,
+            locked: false,
+            idle_timeout: 0,
+            last_activity: 0,
+            wrap_key: None,
+            clock_trusted: true,
+        };
+        context.ocra_suite[0..DEFAULT_OCRA_SUITE.len()].copy_from_slice(DEFAULT_OCRA_SUITE);
+
+        // A clock that stopped gives wrong codes, so say so before anything else
+        tty_commands::warn_clock(&mut context);
+
+        // Start locked if a PIN is set
+        tty_commands::read_pin(&mut context);
+
+        // Attempt to load a saved key and TOTP settings from the RTC EEPROM
+        // With a PIN set the key is sealed, so it is loaded once unlocked instead
+        if !context.locked {
+            tty_commands::read_key(&mut context, None);
+        }
+
+        let mut new_tty = Self {
+            buffer: [0; BUFFER_SIZE],
+            cursor_position: 0,
+            context,
+        };
+        new_tty.newline();
+
+        new_tty
+    }
+
+    fn newline(&mut self) {
+        self.cursor_position = 0;
+        self.buffer = [0; BUFFER_SIZE];
+        ufmt::uwrite!(&mut self.context.serial, "\n$ ").unwrap();
+    }
+
+    pub fn wait_for_byte(&mut self) {
+        // A byte that could not be read is dropped, the same as line noise
+        if let Ok(byte) = nb::block!(self.context.serial.read()) {
+            self.process_byte(byte);
+        }
+    }
+
+    fn process_byte(&mut self, byte: u8) {
+        match byte {
+            b'\n' => {
+                ufmt::uwrite!(&mut self.context.serial, "\n").unwrap();
+                self.process_input();
+                self.newline();
+            },
+            // Backspace
+            b'\x08' => {
+                if self.cursor_position > 0 {
+                    ufmt::uwrite!(&mut self.context.serial, "\x08 \x08").unwrap();
+                    self.cursor_position -= 1;
+                }
+
+                self.buffer[self.cursor_position] = 0;
+            },
+            b'\r' => { },
+            _ => {
+                if self.cursor_position < self.buffer.len() {
+                    self.buffer[self.cursor_position] = byte;
+                    self.cursor_position += 1;
+                    ufmt::uwrite!(&mut self.context.serial, "{}", byte as char).unwrap();
+                }
+            },
+        }
+    }
+
+    fn process_input(&mut self) {
+        let (name, params) = command::split_line(&self.buffer[0..self.cursor_position]);
+        let context = &mut self.context;
+
+        // Only `unlock` is available until the PIN is entered
+        tty_commands::check_idle(context);
+        if context.locked && name != b"unlock" {
+            ufmt::uwriteln!(&mut context.serial, "Locked - use unlock <PIN>").unwrap();
+            return;
+        }
+
+        if let Some(command) = command::find(&Context::COMMANDS, name) {
+            (command.function)(context, params);
+        }
+    }
+
+}
+
+mod tty_commands {
+    use crate::{otp, otpauth, ocra, chalresp, pin, wrap, byte_helper, clocklog, command, eeprom, sync, text};
+    use crate::alarm::{Alarm, Match, Schedule};
+    use crate::sqw::Rate;
+    use crate::clocklog::{Authorization, ClockChange, Drift, Log, Source};
+    use crate::chacha20poly1305::KEY_SIZE;
+    use crate::command::Command;
+    use crate::datetime::Datetime;
+    use crate::slot::Slot;
+    use crate::wrap::{Record, Seal};
+
+    use super::{Context, Serial, Backend, LABEL_SIZE, OCRA_SUITE_SIZE};
+
+    text! {
+        static ERROR_RTC_READ = "Error reading time from RTC - ";
+        static ERROR_EEPROM_READ = "Error reading from RTC EEPROM - ";
+        static ERROR_EEPROM_WRITE = "Error writing to RTC EEPROM - ";
+        static ERROR_SEAL_OPEN = "Error opening sealed secret, it may have been tampered with - ";
+    }
+
+    impl<S: Serial, B: Backend> Context<S, B> {
+        pub(super) const COMMANDS: [Command<Self>; 38] = [
+            command!(b"key     ", 3, key),
+            command!(b"digit   ", 5, digit),
+            command!(b"format  ", 6, format),
+            command!(b"algo    ", 4, algo),
+            command!(b"uri     ", 3, uri),
+            command!(b"period  ", 6, period),
+            command!(b"list    ", 4, list),
+            command!(b"use     ", 3, use_slot),
+            command!(b"add     ", 3, add),
+            command!(b"delete  ", 6, delete),
+            command!(b"hotp    ", 4, hotp),
+            command!(b"next    ", 4, next),
+            command!(b"counter ", 7, counter),
+            command!(b"totp    ", 4, totp),
+            command!(b"suite   ", 5, suite),
+            command!(b"ocra    ", 4, ocra_response),
+            command!(b"chalresp", 8, challenge_response),
+            command!(b"crkey   ", 5, challenge_response_key),
+            command!(b"crmode  ", 6, challenge_response_mode),
+            command!(b"verify  ", 6, verify),
+            command!(b"window  ", 6, window),
+            command!(b"time    ", 4, time_i2c),
+            command!(b"sync    ", 4, sync),
+            command!(b"timelog ", 7, timelog),
+            command!(b"alarm   ", 5, alarm),
+            command!(b"sqw     ", 3, square_wave),
+            command!(b"temp    ", 4, read_temperature),
+            command!(b"utemp   ", 5, update_temperature),
+            command!(b"read    ", 4, read_i2c),
+            command!(b"readp   ", 5, read_page_i2c),
+            command!(b"write   ", 5, write_i2c),
+            command!(b"load    ", 4, read_key),
+            command!(b"save    ", 4, write_key),
+            command!(b"unlock  ", 6, unlock),
+            command!(b"lock    ", 4, lock),
+            command!(b"pin     ", 3, set_pin),
+            command!(b"timeout ", 7, timeout),
+            command!(b"help    ", 4, help_screen),
+        ];
+    }
+
+    // Functions
+    fn key<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        // An optional `hex`, `b32` or `b64` prefix selects how the key is entered or shown
+        let (mode, value) = match param {
+            Some(param) => {
+                let mut args = param.splitn(2, |byte| *byte == b' ');
+                match args.next() {
+                    Some(mode @ (b"hex" | b"b32" | b"b64")) => (Some(mode), args.next()),
+                    _ => (None, Some(param)),
+                }
+            },
+            None => (None, None),
+        };
+
+        match (mode, value) {
+            (Some(mode), Some(encoded_key)) => {
+                let mut new_key = [0_u8; 256];
+                let decoded = match mode {
+                    b"hex" => byte_helper::hex_decode(encoded_key, &mut new_key),
+                    b"b32" => byte_helper::base32_decode(encoded_key, &mut new_key),
+                    _ => byte_helper::base64_decode(encoded_key, &mut new_key),
+                };
+                match decoded {
+                    Ok(length) => {
+                        context.key = new_key;
+                        context.key_length = length;
+                        context.slot = None;
+                        context.unsaved = true;
+                        context.counter = 0;
+                    },
+                    Err(e) => {
+                        ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error decoding key - "), e).unwrap();
+                    },
+                }
+            },
+            (None, Some(new_key)) => {
+                context.key = [0; 256];
+                context.key_length = new_key.len();
+                for (i, new_byte) in new_key.iter().enumerate() {
+                    context.key[i] = *new_byte;
+                }
+                context.slot = None;
+                context.unsaved = true;
+                context.counter = 0;
+            },
+            (Some(mode), None) => {
+                // Encode a few bytes at a time so the whole key never needs a second buffer
+                let chunk_size = match mode {
+                    b"hex" => 1,
+                    b"b32" => 5,
+                    _ => 3,
+                };
+                for chunk in context.key[0..context.key_length].chunks(chunk_size) {
+                    let mut encoded = [0_u8; 8];
+                    let length = match mode {
+                        b"hex" => {
+                            encoded[0..2].copy_from_slice(&byte_helper::byte_to_hex(chunk[0]));
+                            2
+                        },
+                        b"b32" => byte_helper::base32_encode(chunk, &mut encoded).unwrap(),
+                        _ => byte_helper::base64_encode(chunk, &mut encoded).unwrap(),
+                    };
+                    for byte in &encoded[0..length] {
+                        ufmt::uwrite!(context.serial, "{}", *byte as char).unwrap();
+                    }
+                }
+            },
+            (None, None) => {
+                for byte in &context.key[0..context.key_length] {
+                    ufmt::uwrite!(context.serial, "{}", *byte as char).unwrap();
+                }
+            },
+        }
+    }
+
+    fn digit<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        match param {
+            Some(digit_param) => {
+                context.digits = 0;
+                for (i, byte) in digit_param.iter().enumerate() {
+                    context.digits += (*byte - 0x30) * 10_u8.pow(digit_param.len() as u32 - i as u32 - 1);
+                }
+            },
+            None => {
+                ufmt::uwriteln!(context.serial, "{}", context.digits).unwrap();
+            },
+        }
+    }
+
+    fn format<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        match param {
+            Some(format_param) => {
+                match otp::Format::from_name(format_param) {
+                    Some(format) => context.format = format,
+                    None => {
+                        ufmt::uwriteln!(context.serial, "{}", text!("Unknown format - use decimal or steam")).unwrap();
+                    },
+                }
+            },
+            None => {
+                ufmt::uwriteln!(context.serial, "{}", context.format.name()).unwrap();
+            },
+        }
+    }
+
+    fn algo<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        match param {
+            Some(algo_param) => {
+                match otp::Algorithm::from_name(algo_param) {
+                    Some(algorithm) => context.algorithm = algorithm,
+                    None => {
+                        ufmt::uwriteln!(context.serial, "{}", text!("Unknown algorithm - use SHA1, SHA256 or SHA512")).unwrap();
+                    },
+                }
+            },
+            None => {
+                ufmt::uwriteln!(context.serial, "{}", context.algorithm.name()).unwrap();
+            },
+        }
+    }
+
+    // Import an otpauth:// URI, or export the current settings as a TOTP or HOTP one
+    fn uri<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let kind = match param {
+            None | Some(b"totp") => otpauth::OtpKind::Totp,
+            Some(b"hotp") => otpauth::OtpKind::Hotp,
+            Some(uri_param) => return import_uri(context, uri_param),
+        };
+
+        let account: &[u8] = match context.account_length {
+            0 => b"arduino-otp",
+            length => &context.account[0..length],
+        };
+        let otp = otpauth::OtpAuth {
+            kind,
+            issuer: &context.issuer[0..context.issuer_length],
+            account,
+            secret: &context.key[0..context.key_length],
+            algorithm: context.algorithm,
+            digits: context.digits,
+            period: context.period,
+            counter: context.counter,
+        };
+        otpauth::write_uri(&mut context.serial, &otp).unwrap();
+        ufmt::uwriteln!(context.serial, "").unwrap();
+    }
+
+    // The secret is decoded straight into the key, which is left alone if the URI is bad. Like
+    // a key that is typed in, it is kept in RAM with its settings and counter until `save` or `add`.
+    fn import_uri<S: Serial, B: Backend>(context: &mut Context<S, B>, uri: &[u8]) {
+        let mut issuer = [0_u8; LABEL_SIZE];
+        let mut account = [0_u8; LABEL_SIZE];
+        let parsed = match otpauth::parse(uri, &mut issuer, &mut account, &mut context.key) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error parsing URI - "), e).unwrap();
+                return;
+            },
+        };
+
+        let key_length = parsed.secret.len();
+        context.issuer_length = parsed.issuer.len();
+        context.account_length = parsed.account.len();
+        context.issuer[0..context.issuer_length].copy_from_slice(parsed.issuer);
+        context.account[0..context.account_length].copy_from_slice(parsed.account);
+        context.algorithm = parsed.algorithm;
+        context.digits = parsed.digits;
+        context.period = parsed.period;
+        let (kind, counter) = (parsed.kind, parsed.counter);
+
+        context.key[key_length..].fill(0);
+        context.key_length = key_length;
+        context.format = otp::Format::Decimal;
+        context.t0 = 0;
+        context.slot = None;
+        context.unsaved = true;
+        context.counter = match kind {
+            otpauth::OtpKind::Hotp => counter,
+            otpauth::OtpKind::Totp => 0,
+        };
+
+        ufmt::uwriteln!(context.serial, "Loaded {} key - {} digits, period of {}",
+            context.algorithm.name(), context.digits, context.period).unwrap();
+        if kind == otpauth::OtpKind::Hotp {
+            ufmt::uwriteln!(context.serial, "HOTP counter: {}", context.counter).unwrap();
+        }
+    }
+
+    // Show or set the TOTP time step and T0 start time, saving them to the slot in use or with
+    // the saved key. An unsaved key keeps them in RAM until `save`.
+    fn period<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(period_param) = param {
+            let mut args = period_param.split(|byte| *byte == b' ');
+            let period = args.next().map(byte_helper::parse_u64);
+            let t0 = args.next().map(byte_helper::parse_u64).unwrap_or(Ok(context.t0));
+
+            match (period, t0) {
+                (Some(Ok(period)), Ok(t0)) if period > 0 => {
+                    let saved = match context.slot {
+                        Some(index) => write_slot_period(context, index, period, t0),
+                        None if context.unsaved => true,
+                        None => match context.rtc.write_totp_config_eeprom(period, t0) {
+                            Ok(_) => true,
+                            Err(e) => {
+                                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+                                false
+                            },
+                        },
+                    };
+                    if !saved {
+                        return;
+                    }
+                    context.period = period;
+                    context.t0 = t0;
+                },
+                _ => {
+                    ufmt::uwriteln!(context.serial, "{}", text!("Period must be a positive number of seconds, T0 a UNIX timestamp")).unwrap();
+                    return;
+                },
+            }
+        }
+
+        ufmt::uwriteln!(context.serial, "Period: {}s", context.period).unwrap();
+        ufmt::uwriteln!(context.serial, "T0: {}", context.t0).unwrap();
+    }
+
+    // Change the period and T0 stored in a slot. They are part of the header the secret is
+    // sealed with, so the secret is opened and sealed again.
+    // Return: false if the slot could not be updated
+    fn write_slot_period<S: Serial, B: Backend>(context: &mut Context<S, B>, index: usize, period: u64, t0: u64) -> bool {
+        let mut slot = match context.rtc.read_slot_eeprom(index).map(|bytes| Slot::from_bytes(&bytes)) {
+            Ok(Some(slot)) => slot,
+            Ok(None) => {
+                ufmt::uwriteln!(context.serial, "{}", text!("The slot in use has been erased")).unwrap();
+                return false;
+            },
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return false;
+            },
+        };
+        if !open_slot(context, index, &mut slot) {
+            return false;
+        }
+
+        slot.period = period;
+        slot.t0 = t0;
+        let header = slot.header();
+        let wrap_key = context.wrap_key;
+        let seal = match seal_record(context, wrap_key, Record::Slot(index), &header, &mut slot.secret[0..slot.secret_length]) {
+            Some(seal) => seal,
+            None => return false,
+        };
+        match context.rtc.write_slot_eeprom(index, slot.to_bytes(), seal) {
+            Ok(_) => true,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+                false
+            },
+        }
+    }
+
+    // Calculate the HOTP code for a counter, using the current key or the slot named after the counter
+    fn hotp<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(hotp_param) = param {
+            let mut args = hotp_param.splitn(2, |byte| *byte == b' ');
+            let counter = match byte_helper::parse_u64(args.next().unwrap_or(&[])) {
+                Ok(counter) => counter,
+                Err(_) => {
+                    ufmt::uwriteln!(context.serial, "{}", text!("Counter must be a decimal number")).unwrap();
+                    return;
+                },
+            };
+            let slot = match args.next() {
+                Some(name) => match find_slot(context, name) {
+                    Some((index, mut slot)) => match open_slot(context, index, &mut slot) {
+                        true => Some(slot),
+                        false => return,
+                    },
+                    None => return,
+                },
+                None => None,
+            };
+
+            write_otp(context, slot.as_ref(), counter);
+        }
+        else {
+            ufmt::uwriteln!(context.serial, "{}", text!("Use hotp <counter> [slot]")).unwrap();
+        }
+    }
+
+    // Generate the HOTP code for the stored counter and advance it
+    fn next<S: Serial, B: Backend>(context: &mut Context<S, B>, _: Option<&[u8]>) {
+        let counter = context.counter;
+
+        // Save the next counter before showing the code, so a reset can never hand out this one again
+        if !store_counter(context, counter + 1) {
+            return;
+        }
+
+        ufmt::uwriteln!(&mut context.serial, "Counter: {}", counter).unwrap();
+        write_otp(context, None, counter);
+    }
+
+    // Show the stored HOTP counter, or resynchronise it
+    fn counter<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(counter_param) = param {
+            match byte_helper::parse_u64(counter_param) {
+                Ok(counter) => {
+                    if !store_counter(context, counter) {
+                        return;
+                    }
+                },
+                Err(_) => {
+                    ufmt::uwriteln!(context.serial, "{}", text!("Counter must be a decimal number")).unwrap();
+                    return;
+                },
+            }
+        }
+
+        ufmt::uwriteln!(context.serial, "HOTP counter: {}", context.counter).unwrap();
+    }
+
+    // Print the code for a counter using the current key and settings, or those of a slot
+    fn write_otp<S: Serial, B: Backend>(context: &mut Context<S, B>, slot: Option<&Slot>, counter: u64) {
+        let mut code = [0_u8; otp::MAX_CODE_LENGTH];
+        let result = match slot {
+            Some(slot) => slot.algorithm.code(slot.secret(), counter, slot.digits, slot.format, &mut code),
+            None => context.algorithm.code(&context.key[0..context.key_length], counter, context.digits, context.format, &mut code),
+        };
+
+        match result {
+            Ok(length) => write_text(context, &code[0..length]),
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", text!("Error calculating OTP - "), e).unwrap();
+            },
+        }
+    }
+
+    // Show all slots in use
+    fn list<S: Serial, B: Backend>(context: &mut Context<S, B>, _: Option<&[u8]>) {
+        let mut in_use = 0;
+        for index in 0..eeprom::SLOT_COUNT {
+            let slot = match context.rtc.read_slot_eeprom(index) {
+                Ok(bytes) => Slot::from_bytes(&bytes),
+                Err(e) => {
+                    ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                    return;
+                },
+            };
+
+            if let Some(slot) = slot {
+                let marker = if context.slot == Some(index) { '*' } else { ' ' };
+                ufmt::uwrite!(context.serial, "{}{}: ", marker, index).unwrap();
+                write_text(context, slot.label());
+                if slot.issuer_length > 0 {
+                    ufmt::uwrite!(context.serial, " (").unwrap();
+                    write_text(context, slot.issuer());
+                    ufmt::uwrite!(context.serial, ")").unwrap();
+                }
+                match slot.format {
+                    otp::Format::Decimal => ufmt::uwrite!(context.serial, " - {}, {} digits", slot.algorithm.name(), slot.digits).unwrap(),
+                    otp::Format::Steam => ufmt::uwrite!(context.serial, " - {}, steam", slot.algorithm.name()).unwrap(),
+                }
+                ufmt::uwriteln!(context.serial, ", period of {}", slot.period).unwrap();
+                in_use += 1;
+            }
+        }
+
+        ufmt::uwriteln!(context.serial, "{} of {} slots in use", in_use, eeprom::SLOT_COUNT).unwrap();
+    }
+
+    // Load a slot's key and settings, or show which slot is selected
+    fn use_slot<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let name = match param {
+            Some(name) => name,
+            None => {
+                match context.slot {
+                    Some(index) => ufmt::uwriteln!(context.serial, "Using slot {}", index).unwrap(),
+                    None => ufmt::uwriteln!(context.serial, "{}", text!("No slot selected")).unwrap(),
+                }
+                return;
+            },
+        };
+
+        if let Some((index, slot)) = find_slot(context, name) {
+            select_slot(context, index, slot);
+        }
+    }
+
+    // Open a slot and make its key and settings the current ones
+    // Return: false if it could not be opened
+    fn select_slot<S: Serial, B: Backend>(context: &mut Context<S, B>, index: usize, mut slot: Slot) -> bool {
+        if !open_slot(context, index, &mut slot) {
+            return false;
+        }
+        let counter = match context.rtc.read_counter_eeprom(Some(index)) {
+            Ok(counter) => counter.unwrap_or(0),
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return false;
+            },
+        };
+
+        context.key = [0; 256];
+        context.key[0..slot.secret_length].copy_from_slice(slot.secret());
+        context.key_length = slot.secret_length;
+        context.account[0..slot.label_length].copy_from_slice(slot.label());
+        context.account_length = slot.label_length;
+        context.issuer[0..slot.issuer_length].copy_from_slice(slot.issuer());
+        context.issuer_length = slot.issuer_length;
+        context.algorithm = slot.algorithm;
+        context.digits = slot.digits;
+        context.format = slot.format;
+        context.period = slot.period;
+        context.t0 = slot.t0;
+        context.counter = counter;
+        context.slot = Some(index);
+        context.unsaved = false;
+
+        ufmt::uwrite!(context.serial, "Using slot {}: ", index).unwrap();
+        write_text(context, slot.label());
+        ufmt::uwriteln!(context.serial, "").unwrap();
+        true
+    }
+
+    // Save the current key and settings into the first free slot
+    fn add<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let label = match param {
+            Some(label) => label,
+            None => {
+                ufmt::uwriteln!(context.serial, "{}", text!("A label is required")).unwrap();
+                return;
+            },
+        };
+        let mut slot = match Slot::new(label, &context.issuer[0..context.issuer_length], context.algorithm,
+            context.digits, context.period, context.t0, &context.key[0..context.key_length]) {
+            Some(slot) => slot,
+            None => {
+                ufmt::uwriteln!(context.serial, "{}", text!("Labels are limited to 16 characters and keys to 64 bytes")).unwrap();
+                return;
+            },
+        };
+
+        slot.format = context.format;
+
+        // Labels must be unique, and find the first free slot on the way
+        let mut free_index = None;
+        for index in 0..eeprom::SLOT_COUNT {
+            match context.rtc.read_slot_eeprom(index) {
+                Ok(bytes) => match Slot::from_bytes(&bytes) {
+                    Some(existing) if existing.label() == label => {
+                        ufmt::uwriteln!(context.serial, "Label already used by slot {}", index).unwrap();
+                        return;
+                    },
+                    Some(_) => { },
+                    None => free_index = free_index.or(Some(index)),
+                },
+                Err(e) => {
+                    ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                    return;
+                },
+            }
+        }
+        let index = match free_index {
+            Some(index) => index,
+            None => {
+                ufmt::uwriteln!(context.serial, "{}", text!("All slots are in use")).unwrap();
+                return;
+            },
+        };
+
+        let header = slot.header();
+        let wrap_key = context.wrap_key;
+        let seal = match seal_record(context, wrap_key, Record::Slot(index), &header, &mut slot.secret[0..slot.secret_length]) {
+            Some(seal) => seal,
+            None => return,
+        };
+
+        // The slot keeps the current HOTP counter
+        let result = context.rtc.write_counter_eeprom(Some(index), context.counter)
+            .and_then(|_| context.rtc.write_slot_eeprom(index, slot.to_bytes(), seal));
+        if let Err(e) = result {
+            ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+            return;
+        }
+
+        context.account[0..label.len()].copy_from_slice(label);
+        context.account_length = label.len();
+        context.slot = Some(index);
+        context.unsaved = false;
+        ufmt::uwriteln!(context.serial, "Added slot {}", index).unwrap();
+    }
+
+    // Erase a slot and its secret. If it was in use, the saved key is loaded again like `load`.
+    fn delete<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let name = match param {
+            Some(name) => name,
+            None => {
+                ufmt::uwriteln!(context.serial, "{}", text!("Use delete <slot number or label>")).unwrap();
+                return;
+            },
+        };
+        let index = match find_slot(context, name) {
+            Some((index, _)) => index,
+            None => return,
+        };
+
+        if let Err(e) = context.rtc.erase_slot_eeprom(index) {
+            ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+            return;
+        }
+        ufmt::uwriteln!(context.serial, "Deleted slot {}", index).unwrap();
+
+        // Go back to the saved key rather than keep using the deleted slot's key and counter
+        if context.slot == Some(index) {
+            read_key(context, None);
+        }
+    }
+
+    // Find a slot in use by its number or label, printing an error if there isn't one
+    fn find_slot<S: Serial, B: Backend>(context: &mut Context<S, B>, name: &[u8]) -> Option<(usize, Slot)> {
+        let number = byte_helper::parse_u64(name).ok();
+        for index in 0..eeprom::SLOT_COUNT {
+            let slot = match context.rtc.read_slot_eeprom(index) {
+                Ok(bytes) => Slot::from_bytes(&bytes),
+                Err(e) => {
+                    ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                    return None;
+                },
+            };
+
+            if let Some(slot) = slot {
+                if number == Some(index as u64) || slot.label() == name {
+                    return Some((index, slot));
+                }
+            }
+        }
+
+        ufmt::uwriteln!(context.serial, "{}", text!("No slot with that number or label")).unwrap();
+        None
+    }
+
+    // Decrypt a slot's secret in place. The header is checked along with it.
+    fn open_slot<S: Serial, B: Backend>(context: &mut Context<S, B>, index: usize, slot: &mut Slot) -> bool {
+        let header = slot.header();
+        open_record(context, Record::Slot(index), &header, &mut slot.secret[0..slot.secret_length])
+    }
+
+    // The challenge-response secret's length and mode are checked along with it
+    fn chalresp_header(variable_length: bool, length: usize) -> [u8; 2] {
+        [length as u8, variable_length as u8]
+    }
+
+    // Decrypt a stored secret in place with the wrap key. With a PIN set every secret has to be
+    // sealed, so one stored as plain text may have been swapped in and is refused.
+    // Return: true if `data` holds the plain text secret
+    fn open_record<S: Serial, B: Backend>(context: &mut Context<S, B>, record: Record, associated_data: &[u8], data: &mut [u8]) -> bool {
+        let seal = match context.rtc.read_seal_eeprom(record) {
+            Ok(seal) => seal,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return false;
+            },
+        };
+
+        match (context.wrap_key, seal) {
+            (Some(wrap_key), Some(seal)) => match wrap::open(&wrap_key, record, &seal, associated_data, data) {
+                Ok(_) => true,
+                Err(e) => {
+                    ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_SEAL_OPEN, e).unwrap();
+                    false
+                },
+            },
+            (None, None) => true,
+            (Some(_), None) => {
+                ufmt::uwriteln!(context.serial, "{}", text!("Secret isn't sealed, it may have been replaced")).unwrap();
+                false
+            },
+            (None, Some(_)) => {
+                ufmt::uwriteln!(context.serial, "{}", text!("Secret is sealed - unlock with the PIN first")).unwrap();
+                false
+            },
+        }
+    }
+
+    // Encrypt a secret in place under `wrap_key` with a fresh nonce, ready to be saved
+    // Return: the seal to save with it (Some(None) leaves the secret as plain text),
+    // or None if no nonce could be reserved
+    fn seal_record<S: Serial, B: Backend>(context: &mut Context<S, B>, wrap_key: Option<[u8; KEY_SIZE]>, record: Record, associated_data: &[u8], data: &mut [u8]) -> Option<Option<Seal>> {
+        let wrap_key = match wrap_key {
+            Some(wrap_key) => wrap_key,
+            None => return Some(None),
+        };
+
+        match context.rtc.next_nonce_eeprom() {
+            Ok(counter) => Some(Some(wrap::seal(&wrap_key, record, counter, associated_data, data))),
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+                None
+            },
+        }
+    }
+
+    // Open every stored secret with the current wrap key and save it again sealed under
+    // `new_key`, or as plain text if that is None. Everything is opened once before anything is
+    // rewritten, so a tampered secret stops the change while the rest can still be read.
+    // Return: true if every secret was rewritten
+    fn rewrap_secrets<S: Serial, B: Backend>(context: &mut Context<S, B>, new_key: Option<[u8; KEY_SIZE]>) -> bool {
+        for rewrite in [false, true] {
+            if !rewrap_record(context, Record::Key, new_key, rewrite) ||
+               !rewrap_record(context, Record::ChallengeResponse, new_key, rewrite) {
+                return false;
+            }
+            for index in 0..eeprom::SLOT_COUNT {
+                if !rewrap_record(context, Record::Slot(index), new_key, rewrite) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    // Return: true if the record could be opened, and was saved again if `rewrite` is set
+    fn rewrap_record<S: Serial, B: Backend>(context: &mut Context<S, B>, record: Record, new_key: Option<[u8; KEY_SIZE]>, rewrite: bool) -> bool {
+        let result = match record {
+            Record::Key => {
+                let (length, mut key) = match context.rtc.read_key_eeprom() {
+                    Ok(saved) => saved,
+                    Err(e) => {
+                        ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                        return false;
+                    },
+                };
+                if !open_record(context, record, &[length as u8], &mut key) {
+                    return false;
+                }
+                if !rewrite {
+                    return true;
+                }
+                match seal_record(context, new_key, record, &[length as u8], &mut key) {
+                    Some(seal) => context.rtc.write_key_eeprom(length, key, seal),
+                    None => return false,
+                }
+            },
+            Record::ChallengeResponse => {
+                let (variable_length, length, mut secret) = match context.rtc.read_chalresp_eeprom() {
+                    Ok(Some(saved)) => saved,
+                    Ok(None) => return true,
+                    Err(e) => {
+                        ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                        return false;
+                    },
+                };
+                let header = chalresp_header(variable_length, length);
+                if !open_record(context, record, &header, &mut secret) {
+                    return false;
+                }
+                if !rewrite {
+                    return true;
+                }
+                match seal_record(context, new_key, record, &header, &mut secret) {
+                    Some(seal) => context.rtc.write_chalresp_eeprom(variable_length, length, secret, seal),
+                    None => return false,
+                }
+            },
+            Record::Slot(index) => {
+                let mut slot = match context.rtc.read_slot_eeprom(index) {
+                    Ok(bytes) => match Slot::from_bytes(&bytes) {
+                        Some(slot) => slot,
+                        None => return true,
+                    },
+                    Err(e) => {
+                        ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                        return false;
+                    },
+                };
+                if !open_slot(context, index, &mut slot) {
+                    return false;
+                }
+                if !rewrite {
+                    return true;
+                }
+                let header = slot.header();
+                match seal_record(context, new_key, record, &header, &mut slot.secret[0..slot.secret_length]) {
+                    Some(seal) => context.rtc.write_slot_eeprom(index, slot.to_bytes(), seal),
+                    None => return false,
+                }
+            },
+        };
+
+        match result {
+            Ok(_) => true,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+                false
+            },
+        }
+    }
+
+    fn write_text<S: Serial, B: Backend>(context: &mut Context<S, B>, label: &[u8]) {
+        for byte in label {
+            ufmt::uwrite!(context.serial, "{}", *byte as char).unwrap();
+        }
+    }
+
+    // Persist a new HOTP counter, only updating it in memory once it is safely in EEPROM.
+    // An unsaved key has no records of its own, so its counter waits in RAM for `save`.
+    // Return: true if the counter was saved
+    fn store_counter<S: Serial, B: Backend>(context: &mut Context<S, B>, counter: u64) -> bool {
+        if context.unsaved {
+            context.counter = counter;
+            return true;
+        }
+
+        match context.rtc.write_counter_eeprom(context.slot, counter) {
+            Ok(_) => {
+                context.counter = counter;
+                true
+            },
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+                false
+            },
+        }
+    }
+
+    // Calculate the TOTP code for the current time, using the current key or a named slot
+    fn totp<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let slot = match param {
+            Some(name) => match find_slot(context, name) {
+                Some((index, mut slot)) => match open_slot(context, index, &mut slot) {
+                    true => Some(slot),
+                    false => return,
+                },
+                None => return,
+            },
+            None => None,
+        };
+        let (t0, period) = match &slot {
+            Some(slot) => (slot.t0, slot.period),
+            None => (context.t0, context.period),
+        };
+
+        let timestamp = match trusted_timestamp(context) {
+            Some(timestamp) => timestamp,
+            None => return,
+        };
+        let (counter, remaining) = match otp::totp_counter(timestamp, t0, period) {
+            Ok(step) => step,
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", text!("Error calculating TOTP step - "), e).unwrap();
+                return;
+            },
+        };
+
+        ufmt::uwriteln!(&mut context.serial, "Timestamp: {}", timestamp).unwrap();
+        ufmt::uwriteln!(&mut context.serial, "Counter: {}", counter).unwrap();
+        ufmt::uwriteln!(&mut context.serial, "Remaining: {}s", remaining).unwrap();
+        write_otp(context, slot.as_ref(), counter);
+    }
+
+    // Show or set the OCRA suite used by `ocra`
+    fn suite<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(suite_param) = param {
+            if suite_param.len() > OCRA_SUITE_SIZE {
+                ufmt::uwriteln!(context.serial, "{}{}{}", text!("Suite must be at most "), OCRA_SUITE_SIZE, text!(" characters")).unwrap();
+                return;
+            }
+            if let Err(e) = ocra::parse_suite(suite_param) {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error parsing OCRA suite - "), e).unwrap();
+                return;
+            }
+
+            context.ocra_suite = [0; OCRA_SUITE_SIZE];
+            context.ocra_suite[0..suite_param.len()].copy_from_slice(suite_param);
+            context.ocra_suite_length = suite_param.len();
+        }
+
+        let suite = context.ocra_suite;
+        write_text(context, &suite[0..context.ocra_suite_length]);
+        ufmt::uwriteln!(context.serial, "").unwrap();
+    }
+
+    // Calculate the OCRA response to a challenge: `ocra <challenge> [PIN] [session information]`
+    // The PIN and session information are only read if the suite uses them
+    fn ocra_response<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let suite_bytes = context.ocra_suite;
+        let suite = match ocra::parse_suite(&suite_bytes[0..context.ocra_suite_length]) {
+            Ok(suite) => suite,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error parsing OCRA suite - "), e).unwrap();
+                return;
+            },
+        };
+
+        let mut args = param.unwrap_or(&[]).splitn(3, |byte| *byte == b' ');
+        let challenge = args.next().unwrap_or(&[]);
+        let pin = match suite.pin_algorithm {
+            Some(_) => args.next().unwrap_or(&[]),
+            None => &[],
+        };
+        let session = match suite.session_length {
+            0 => &[],
+            _ => args.next().unwrap_or(&[]),
+        };
+
+        let timestamp = match suite.time_step {
+            0 => 0,
+            _ => match trusted_timestamp(context) {
+                Some(timestamp) => timestamp,
+                None => return,
+            },
+        };
+
+        // Counter-based suites use the stored HOTP counter
+        let counter = context.counter;
+        let input = ocra::OcraInput { counter, challenge, pin, session, timestamp };
+        let response = match ocra::gen_ocra(&suite, &context.key[0..context.key_length], &input) {
+            Ok(response) => response,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error calculating OCRA response - "), e).unwrap();
+                return;
+            },
+        };
+
+        // ...and advance it before the response is shown, like `next`
+        if suite.counter {
+            if !store_counter(context, counter + 1) {
+                return;
+            }
+            ufmt::uwriteln!(context.serial, "Counter: {}", counter).unwrap();
+        }
+        let mut code = [0_u8; otp::MAX_CODE_LENGTH];
+        let length = otp::format_code(response, suite.digits, otp::Format::Decimal, &mut code).unwrap_or(0);
+        write_text(context, &code[0..length]);
+    }
+
+    // Answer an HMAC-SHA1 challenge with the challenge-response secret, like a YubiKey slot
+    fn challenge_response<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let (variable_length, length, mut secret) = match context.rtc.read_chalresp_eeprom() {
+            Ok(Some(saved)) => saved,
+            Ok(None) => {
+                ufmt::uwriteln!(context.serial, "{}", text!("No challenge-response secret set - use crkey")).unwrap();
+                return;
+            },
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return;
+            },
+        };
+        if !open_record(context, Record::ChallengeResponse, &chalresp_header(variable_length, length), &mut secret) {
+            return;
+        }
+
+        let mut challenge = [0_u8; chalresp::CHALLENGE_SIZE];
+        let challenge_length = match byte_helper::hex_decode(param.unwrap_or(&[]), &mut challenge) {
+            Ok(challenge_length) => challenge_length,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error decoding challenge - "), e).unwrap();
+                return;
+            },
+        };
+
+        match chalresp::respond(&secret[0..length], &challenge[0..challenge_length], variable_length) {
+            Ok(response) => {
+                for byte in response {
+                    write_text(context, &byte_helper::byte_to_hex(byte));
+                }
+                ufmt::uwriteln!(context.serial, "").unwrap();
+            },
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error calculating response - "), e).unwrap();
+            },
+        }
+    }
+
+    // Set and save the challenge-response secret from hex. The secret can't be read back.
+    fn challenge_response_key<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let saved = match context.rtc.read_chalresp_eeprom() {
+            Ok(saved) => saved,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return;
+            },
+        };
+
+        let secret_param = match param {
+            Some(secret_param) => secret_param,
+            None => {
+                match saved {
+                    Some((_, length, _)) => ufmt::uwriteln!(context.serial, "Challenge-response secret of length {} set", length).unwrap(),
+                    None => ufmt::uwriteln!(context.serial, "{}", text!("No challenge-response secret set")).unwrap(),
+                }
+                return;
+            },
+        };
+
+        let mut secret = [0_u8; chalresp::SECRET_SIZE];
+        let length = match byte_helper::hex_decode(secret_param, &mut secret) {
+            Ok(length) => length,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error decoding secret - "), e).unwrap();
+                return;
+            },
+        };
+
+        // Variable-length challenges unless fixed mode was chosen before
+        let variable_length = saved.map_or(true, |(variable_length, _, _)| variable_length);
+        let wrap_key = context.wrap_key;
+        let seal = match seal_record(context, wrap_key, Record::ChallengeResponse, &chalresp_header(variable_length, length), &mut secret) {
+            Some(seal) => seal,
+            None => return,
+        };
+        match context.rtc.write_chalresp_eeprom(variable_length, length, secret, seal) {
+            Ok(_) => {
+                ufmt::uwriteln!(context.serial, "{}", text!("Saved challenge-response secret to RTC EEPROM")).unwrap();
+            },
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+            },
+        }
+    }
+
+    // Show or set whether challenges shorter than 64 bytes are padded (`variable`) or used as-is (`fixed`)
+    fn challenge_response_mode<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let (saved_variable_length, length, mut secret) = match context.rtc.read_chalresp_eeprom() {
+            Ok(Some(saved)) => saved,
+            Ok(None) => {
+                ufmt::uwriteln!(context.serial, "{}", text!("No challenge-response secret set - use crkey")).unwrap();
+                return;
+            },
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return;
+            },
+        };
+
+        let variable_length = match param {
+            Some(b"variable") => true,
+            Some(b"fixed") => false,
+            Some(_) => {
+                ufmt::uwriteln!(context.serial, "{}", text!("Unknown mode - use fixed or variable")).unwrap();
+                return;
+            },
+            None => saved_variable_length,
+        };
+        if param.is_some() {
+            // The mode is sealed along with the secret, so the secret is sealed again
+            if !open_record(context, Record::ChallengeResponse, &chalresp_header(saved_variable_length, length), &mut secret) {
+                return;
+            }
+            let wrap_key = context.wrap_key;
+            let seal = match seal_record(context, wrap_key, Record::ChallengeResponse, &chalresp_header(variable_length, length), &mut secret) {
+                Some(seal) => seal,
+                None => return,
+            };
+            if let Err(e) = context.rtc.write_chalresp_eeprom(variable_length, length, secret, seal) {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+                return;
+            }
+        }
+
+        match variable_length {
+            true => ufmt::uwriteln!(context.serial, "{}", text!("variable")).unwrap(),
+            false => ufmt::uwriteln!(context.serial, "{}", text!("fixed")).unwrap(),
+        }
+    }
+
+    // Check a submitted TOTP code, or a HOTP code with `verify hotp <code>`
+    fn verify<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let (hotp_mode, code_param) = match param {
+            Some(param) if param.starts_with(b"hotp ") => (true, &param[5..]),
+            Some(param) => (false, param),
+            None => return,
+        };
+
+        if context.format != otp::Format::Decimal {
+            ufmt::uwriteln!(context.serial, "{}", text!("Only decimal codes can be verified")).unwrap();
+            return;
+        }
+
+        // The code must have exactly as many digits as we generate, leading zeros included
+        let code = match byte_helper::parse_u64(code_param) {
+            Ok(code) if code_param.len() == context.digits as usize => code as u32,
+            _ => {
+                ufmt::uwriteln!(context.serial, "{}{}{}", text!("Code must be "), context.digits, text!(" digits")).unwrap();
+                return;
+            },
+        };
+        if hotp_mode {
+            let key = &context.key[0..context.key_length];
+            match otp::verify_hotp(context.algorithm, key, context.counter, context.digits as u32, code, context.look_ahead) {
+                Ok(Some(matched)) => {
+                    // Never accept the same or an earlier counter again
+                    if store_counter(context, matched + 1) {
+                        ufmt::uwriteln!(context.serial, "Valid - counter {}, next counter {}", matched, context.counter).unwrap();
+                    }
+                },
+                Ok(None) => {
+                    ufmt::uwriteln!(context.serial, "{}", text!("Invalid code")).unwrap();
+                },
+                Err(e) => {
+                    ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error verifying code - "), e).unwrap();
+                },
+            }
+            return;
+        }
+
+        let timestamp = match trusted_timestamp(context) {
+            Some(timestamp) => timestamp,
+            None => return,
+        };
+        let key = &context.key[0..context.key_length];
+        match otp::verify_totp(context.algorithm, key, timestamp, context.t0, context.period, context.digits as u32, code, context.window) {
+            Ok(Some(offset)) => {
+                ufmt::uwriteln!(context.serial, "Valid - step offset {}", offset).unwrap();
+            },
+            Ok(None) => {
+                ufmt::uwriteln!(context.serial, "{}", text!("Invalid code")).unwrap();
+            },
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error verifying code - "), e).unwrap();
+            },
+        }
+    }
+
+    // Show or set the TOTP window (steps either side) and HOTP look-ahead used by `verify`
+    fn window<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(window_param) = param {
+            let mut args = window_param.split(|byte| *byte == b' ');
+            let window = args.next().map(byte_helper::parse_u64);
+            let look_ahead = args.next().map(byte_helper::parse_u64).unwrap_or(Ok(context.look_ahead as u64));
+
+            match (window, look_ahead) {
+                (Some(Ok(window @ 0..=255)), Ok(look_ahead @ 0..=255)) => {
+                    context.window = window as u8;
+                    context.look_ahead = look_ahead as u8;
+                },
+                _ => {
+                    ufmt::uwriteln!(context.serial, "{}", text!("Window and look-ahead must be between 0 and 255")).unwrap();
+                    return;
+                },
+            }
+        }
+
+        ufmt::uwriteln!(context.serial, "TOTP window: +/-{} steps", context.window).unwrap();
+        ufmt::uwriteln!(context.serial, "HOTP look-ahead: {} counters", context.look_ahead).unwrap();
+    }
+
+    // `time <UNIX timestamp> [PIN]` - large changes need the PIN, see `clocklog`
+    fn time_i2c<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(time_param) = param {
+            let mut args = time_param.splitn(2, |byte| *byte == b' ');
+            let timestamp = match byte_helper::parse_u64(args.next().unwrap_or(&[])) {
+                Ok(timestamp) => timestamp,
+                Err(_) => {
+                    ufmt::uwriteln!(&mut context.serial, "{}", text!("Error setting time - invalid timestamp")).unwrap();
+                    return;
+                },
+            };
+
+            let authorized = match args.next() {
+                Some(entered_pin) => match authorize_clock_change(context, entered_pin) {
+                    Some(authorized) => authorized,
+                    None => return,
+                },
+                None => None,
+            };
+
+            if change_clock(context, timestamp, Source::Time, authorized).is_none() {
+                return;
+            }
+        }
+
+        match context.rtc.now() {
+            Ok(stored_date) => {
+                ufmt::uwriteln!(&mut context.serial, "Date: {}/{}/{} - {}:{}:{}", 
+                    stored_date.year, stored_date.month, stored_date.date, 
+                    stored_date.hours, stored_date.minutes, stored_date.seconds)
+                .unwrap();
+                ufmt::uwriteln!(&mut context.serial, "Timestamp: {}", stored_date.unix_timestamp()).unwrap();
+                warn_clock(context);
+            }
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_RTC_READ, e).unwrap();
+            }
+        }
+    }
+
+    // Check the PIN given with a clock change
+    // Return: Some(None) if no PIN is set, or None if the PIN is wrong or can't be read
+    fn authorize_clock_change<S: Serial, B: Backend>(context: &mut Context<S, B>, entered_pin: &[u8]) -> Option<Option<Authorization>> {
+        match context.rtc.read_pin_eeprom() {
+            Ok(bytes) => match pin::PinConfig::from_bytes(&bytes) {
+                Some(config) => check_pin(context, &config, entered_pin).map(|_| Some(Authorization::Pin)),
+                None => Some(None),
+            },
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                None
+            },
+        }
+    }
+
+    // `sync [PIN]` sets the clock from the host's with the `sync` handshake. The reply is read
+    // here rather than typed at the prompt, so it is not echoed and its round trip can be timed.
+    // Large changes need the PIN, which is checked before the handshake so it doesn't add to
+    // the round trip.
+    fn sync<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let authorized = match param {
+            Some(entered_pin) => match authorize_clock_change(context, entered_pin) {
+                Some(authorized) => authorized,
+                None => return,
+            },
+            None => None,
+        };
+
+        let nonce = match context.rtc.next_nonce_eeprom() {
+            Ok(nonce) => nonce,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+                return;
+            },
+        };
+        ufmt::uwrite!(context.serial, "Sync ").unwrap();
+        write_text(context, &sync::encode_nonce(nonce));
+        ufmt::uwriteln!(context.serial, "").unwrap();
+
+        // Count the milliseconds spent waiting for each byte of the reply
+        let mut reply = [0_u8; sync::MAX_REPLY_LENGTH];
+        let mut length = 0;
+        let mut round_trip_ms: u16 = 0;
+        loop {
+            match context.serial.read() {
+                Ok(b'\n') => break,
+                Ok(b'\r') => { },
+                Ok(byte) => {
+                    if length < reply.len() {
+                        reply[length] = byte;
+                    }
+                    length += 1;
+                },
+                Err(nb::Error::WouldBlock) => {
+                    if round_trip_ms >= sync::REPLY_TIMEOUT_MS {
+                        ufmt::uwriteln!(context.serial, "{}", text!("Error syncing time - no reply from the host")).unwrap();
+                        return;
+                    }
+                    context.rtc.delay_ms(1);
+                    round_trip_ms += 1;
+                },
+                Err(nb::Error::Other(_)) => { },
+            }
+        }
+
+        let host_ms = match reply.get(0..length).and_then(|reply| sync::parse_reply(reply, nonce)) {
+            Some(host_ms) => host_ms,
+            None => {
+                ufmt::uwriteln!(context.serial, "{}", text!("Error syncing time - the reply does not match")).unwrap();
+                return;
+            },
+        };
+
+        let (wait_ms, timestamp) = sync::schedule(host_ms, round_trip_ms);
+        context.rtc.delay_ms(wait_ms);
+        let previous = match change_clock(context, timestamp, Source::Sync, authorized) {
+            Some(previous) => previous,
+            None => return,
+        };
+
+        ufmt::uwriteln!(context.serial, "Round trip: {}ms", round_trip_ms).unwrap();
+        match timestamp >= previous {
+            true => ufmt::uwriteln!(context.serial, "Correction: +{}s", timestamp - previous).unwrap(),
+            false => ufmt::uwriteln!(context.serial, "Correction: -{}s", previous - timestamp).unwrap(),
+        }
+        time_i2c(context, None);
+    }
+
+    // Check whether the RTC has lost time. Once it has, the clock stays untrusted until the
+    // time is set again.
+    // Return: true if the clock can be trusted
+    fn check_clock<S: Serial, B: Backend>(context: &mut Context<S, B>) -> bool {
+        if context.clock_trusted {
+            match context.rtc.lost_time() {
+                Ok(lost_time) => context.clock_trusted = !lost_time,
+                Err(e) => {
+                    ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_RTC_READ, e).unwrap();
+                    return false;
+                },
+            }
+        }
+
+        context.clock_trusted
+    }
+
+    pub(super) fn warn_clock<S: Serial, B: Backend>(context: &mut Context<S, B>) {
+        if !check_clock(context) {
+            ufmt::uwriteln!(&mut context.serial, "{}", text!("Warning: the RTC lost time, e.g. its battery ran out. Set the time before using totp.")).unwrap();
+        }
+    }
+
+    // Read the time for a time-based code, refusing while the clock can't be trusted
+    // Return: the UNIX timestamp, or None if there is no time that can be used
+    fn trusted_timestamp<S: Serial, B: Backend>(context: &mut Context<S, B>) -> Option<u64> {
+        if !check_clock(context) {
+            ufmt::uwriteln!(&mut context.serial, "{}", text!("Error reading time - the RTC lost time, set it with time or otpctl sync")).unwrap();
+            return None;
+        }
+
+        match context.rtc.now() {
+            Ok(date) => Some(date.unix_timestamp()),
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_RTC_READ, e).unwrap();
+                None
+            },
+        }
+    }
+
+    // Move the clock to `timestamp` and log the change. Large changes, or small ones that add up
+    // to more than the drift allowed since the last authorized change, are refused unless they
+    // are `authorized` already, or no PIN is set.
+    // Return: the time before the change, or None if the clock was not changed
+    fn change_clock<S: Serial, B: Backend>(context: &mut Context<S, B>, timestamp: u64, source: Source, authorized: Option<Authorization>) -> Option<u64> {
+        let previous = match context.rtc.now() {
+            Ok(date) => date.unix_timestamp(),
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_RTC_READ, e).unwrap();
+                return None;
+            },
+        };
+
+        let drift = match authorized {
+            Some(_) => Drift::default(),
+            None => match context.rtc.clock_drift_eeprom() {
+                Ok(drift) => drift,
+                Err(e) => {
+                    ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                    return None;
+                },
+            },
+        };
+        let (authorization, drift) = match authorized {
+            Some(authorization) => (authorization, 0),
+            None if !clocklog::needs_authorization(previous, timestamp, &drift) => (Authorization::Small, drift.after(previous, timestamp)),
+            None if !context.pin_set => (Authorization::NoPin, 0),
+            None => {
+                match source {
+                    Source::Time => ufmt::uwriteln!(&mut context.serial, "{}",
+                        text!("Error setting time - large changes need the PIN, use time <UNIX timestamp> <PIN>")).unwrap(),
+                    Source::Sync => ufmt::uwriteln!(&mut context.serial, "{}",
+                        text!("Error syncing time - large changes need the PIN, use otpctl --pin")).unwrap(),
+                }
+                return None;
+            },
+        };
+
+        if let Err(e) = context.rtc.set(Datetime::from_timestamp(timestamp)) {
+            ufmt::uwriteln!(&mut context.serial, "{}{:?}", text!("Error setting time for RTC - "), e).unwrap();
+            return None;
+        }
+        // The idle timeout counts from the new time, so a change can't lock or unlock the console
+        context.last_activity = timestamp;
+        context.clock_trusted = true;
+        if let Err(e) = context.rtc.log_clock_change_eeprom(previous, timestamp, source, authorization, drift) {
+            ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+        }
+
+        Some(previous)
+    }
+
+    // Show the clock log, authorized changes first, and the oldest change first in each
+    fn timelog<S: Serial, B: Backend>(context: &mut Context<S, B>, _: Option<&[u8]>) {
+        let mut logged = false;
+        for log in [Log::Authorized, Log::Small] {
+            let latest = match context.rtc.latest_clock_change_eeprom(log) {
+                Ok(Some((index, _))) => index,
+                Ok(None) => continue,
+                Err(e) => {
+                    ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                    return;
+                },
+            };
+            logged = true;
+
+            let heading = match log {
+                Log::Authorized => text!("Authorized changes:"),
+                Log::Small => text!("Small changes:"),
+            };
+            ufmt::uwriteln!(context.serial, "{}", heading).unwrap();
+
+            let size = eeprom::clock_log_size(log);
+            for offset in 1..=size {
+                let change = match context.rtc.read_clock_change_eeprom(log, (latest + offset) % size) {
+                    Ok(Some(change)) => change,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                        return;
+                    },
+                };
+                print_clock_change(context, &change);
+            }
+        }
+
+        if !logged {
+            ufmt::uwriteln!(&mut context.serial, "{}", text!("No clock changes logged")).unwrap();
+        }
+    }
+
+    fn print_clock_change<S: Serial, B: Backend>(context: &mut Context<S, B>, change: &ClockChange) {
+        ufmt::uwrite!(context.serial, "{}: {} -> {} ", change.sequence, change.previous, change.new).unwrap();
+        match change.new >= change.previous {
+            true => ufmt::uwrite!(context.serial, "(+{}s)", change.new - change.previous).unwrap(),
+            false => ufmt::uwrite!(context.serial, "(-{}s)", change.previous - change.new).unwrap(),
+        }
+        let source = match change.source {
+            Source::Time => text!("time"),
+            Source::Sync => text!("sync"),
+        };
+        let authorization = match change.authorization {
+            Authorization::Small => text!("small change"),
+            Authorization::NoPin => text!("no PIN set"),
+            Authorization::Pin => text!("PIN"),
+        };
+        ufmt::uwriteln!(context.serial, " {}, {}", source, authorization).unwrap();
+    }
+
+    // `alarm <1|2> <HH:MM:SS|HH:MM> [date <1-31>|day <1-7>]`, `alarm <1|2> clear` or `alarm [1|2]`
+    // to show them. See `Schedule::parse` for the format.
+    fn alarm<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let mut args = param.unwrap_or(&[]).splitn(2, |byte| *byte == b' ');
+        let alarms: &[Alarm] = match args.next() {
+            None | Some(b"") => &[Alarm::One, Alarm::Two],
+            Some(b"1") => &[Alarm::One],
+            Some(b"2") => &[Alarm::Two],
+            Some(_) => {
+                ufmt::uwriteln!(context.serial, "{}", text!("Error finding alarm - there are only alarms 1 and 2")).unwrap();
+                return;
+            },
+        };
+
+        match (alarms, args.next()) {
+            ([alarm], Some(b"clear")) => {
+                if let Err(e) = context.rtc.clear_alarm(*alarm) {
+                    ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error clearing alarm - "), e).unwrap();
+                    return;
+                }
+            },
+            ([alarm], Some(schedule)) => {
+                let schedule = match Schedule::parse(*alarm, schedule) {
+                    Some(schedule) => schedule,
+                    None => {
+                        let error = match alarm {
+                            Alarm::One => text!("Error setting alarm - use HH:MM:SS, with * for any, then date <1-31> or day <1-7>"),
+                            Alarm::Two => text!("Error setting alarm - alarm 2 has no seconds, use HH:MM, with * for any, then date <1-31> or day <1-7>"),
+                        };
+                        ufmt::uwriteln!(context.serial, "{}", error).unwrap();
+                        return;
+                    },
+                };
+                if let Err(e) = context.rtc.set_alarm(*alarm, schedule) {
+                    ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error setting alarm - "), e).unwrap();
+                    return;
+                }
+            },
+            _ => {},
+        }
+
+        for alarm in alarms {
+            let state = match context.rtc.alarm(*alarm) {
+                Ok(state) => state,
+                Err(e) => {
+                    ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error reading alarm - "), e).unwrap();
+                    return;
+                },
+            };
+
+            let number = match alarm {
+                Alarm::One => 1,
+                Alarm::Two => 2,
+            };
+            ufmt::uwrite!(context.serial, "Alarm {}: ", number).unwrap();
+            match state.schedule {
+                Some(schedule) => write_schedule(context, *alarm, &schedule),
+                None => ufmt::uwrite!(context.serial, "{}", text!("not set")).unwrap(),
+            }
+            let interrupt = match state.interrupt {
+                true => text!("on"),
+                false => text!("off"),
+            };
+            ufmt::uwrite!(context.serial, ", interrupt {}", interrupt).unwrap();
+            if state.fired {
+                ufmt::uwrite!(context.serial, "{}", text!(", fired")).unwrap();
+            }
+            ufmt::uwriteln!(context.serial, "").unwrap();
+        }
+    }
+
+    // Write the schedule the way `alarm` takes it, with a `*` for each field that isn't matched
+    fn write_schedule<S: Serial, B: Backend>(context: &mut Context<S, B>, alarm: Alarm, schedule: &Schedule) {
+        let matched = match schedule.matching {
+            Match::EverySecond => 0,
+            Match::EveryMinute | Match::Seconds => 1,
+            Match::Minutes => 2,
+            Match::Hours | Match::Date(_) | Match::Day(_) => 3,
+        };
+        let mut fields = [schedule.hours, schedule.minutes, schedule.seconds].into_iter().enumerate();
+        if alarm == Alarm::Two {
+            fields.next_back();
+        }
+
+        for (i, value) in fields {
+            if i > 0 {
+                ufmt::uwrite!(context.serial, ":").unwrap();
+            }
+            match i + matched >= 3 {
+                true => ufmt::uwrite!(context.serial, "{}{}", value / 10, value % 10).unwrap(),
+                false => ufmt::uwrite!(context.serial, "*").unwrap(),
+            }
+        }
+
+        match schedule.matching {
+            Match::Date(date) => ufmt::uwrite!(context.serial, " date {}", date).unwrap(),
+            Match::Day(day) => ufmt::uwrite!(context.serial, " day {}", day).unwrap(),
+            _ => {},
+        }
+    }
+
+    // `sqw <1|1024|4096|8192>` puts a square wave out on INT/SQW, `sqw off` hands the pin back
+    // to the alarms and `sqw 32k <on|off>` switches the 32kHz pin
+    fn square_wave<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(sqw_param) = param {
+            let mut args = sqw_param.splitn(2, |byte| *byte == b' ');
+            let result = match (args.next().unwrap_or(&[]), args.next()) {
+                (b"off", None) => context.rtc.set_square_wave(None),
+                (b"32k", Some(b"on")) => context.rtc.set_32khz_output(true),
+                (b"32k", Some(b"off")) => context.rtc.set_32khz_output(false),
+                (rate, None) if rate != b"32k" => match Rate::parse(rate) {
+                    Some(rate) => context.rtc.set_square_wave(Some(rate)),
+                    None => {
+                        ufmt::uwriteln!(context.serial, "{}", text!("Error setting square wave - use 1, 1024, 4096 or 8192 Hz, or off")).unwrap();
+                        return;
+                    },
+                },
+                _ => {
+                    ufmt::uwriteln!(context.serial, "{}", text!("Error setting square wave - use 32k on or 32k off")).unwrap();
+                    return;
+                },
+            };
+            if let Err(e) = result {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error setting square wave - "), e).unwrap();
+                return;
+            }
+        }
+
+        let state = match context.rtc.square_wave() {
+            Ok(state) => state,
+            Err(e) => {
+                ufmt::uwriteln!(context.serial, "{}{:?}", text!("Error reading square wave - "), e).unwrap();
+                return;
+            },
+        };
+        match state.square_wave {
+            Some(rate) => ufmt::uwriteln!(context.serial, "INT/SQW: {}Hz square wave", rate.hertz()).unwrap(),
+            None => ufmt::uwriteln!(context.serial, "{}", text!("INT/SQW: alarm interrupt")).unwrap(),
+        }
+        let output_32khz = match state.output_32khz {
+            true => text!("on"),
+            false => text!("off"),
+        };
+        ufmt::uwriteln!(context.serial, "32kHz: {}", output_32khz).unwrap();
+    }
+
+    fn read_i2c<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(address) = param.and_then(command::parse_address) {
+            match context.rtc.read_byte_eeprom(address) {
+                Ok(byte) => {
+                    ufmt::uwriteln!(&mut context.serial, "Byte: {}", byte[0]).unwrap();
+                },
+                Err(e) => {
+                    ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                },
+            }
+        }
+    }
+    fn read_page_i2c<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(address) = param.and_then(command::parse_address) {
+            match context.rtc.read_page_eeprom(address) {
+                Ok(page) => {
+                    ufmt::uwriteln!(&mut context.serial, "Page: {:?}", page).unwrap();
+                },
+                Err(e) => {
+                    ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                },
+            }
+        }
+    }
+
+    fn write_i2c<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        if let Some(input_bytes) = param {
+            let mut args = input_bytes.split(|byte| byte == &b' ');
+            let address = args.next().and_then(command::parse_address);
+            let input = args.next().and_then(command::parse_hex_byte);
+            if let (Some(address), Some(input)) = (address, input) {
+                if context.pin_set && eeprom::is_protected(address) {
+                    ufmt::uwriteln!(&mut context.serial, "{}", text!("Error writing EEPROM - the PIN and clock log records can't be written while a PIN is set")).unwrap();
+                    return;
+                }
+                match context.rtc.write_byte_eeprom(address, input) {
+                    Ok(_) => context.rtc.wait_for_write(),
+                    Err(e) => {
+                        ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+                    },
+                }
+            }
+        }
+    }
+
+    // Write the current key, its TOTP settings and HOTP counter to EEPROM
+    fn write_key<S: Serial, B: Backend>(context: &mut Context<S, B>, _: Option<&[u8]>) {
+        let mut key = context.key;
+        let (wrap_key, length) = (context.wrap_key, context.key_length);
+        let seal = match seal_record(context, wrap_key, Record::Key, &[length as u8], &mut key) {
+            Some(seal) => seal,
+            None => return,
+        };
+
+        let result = context.rtc.write_key_eeprom(length, key, seal)
+            .and_then(|_| context.rtc.write_totp_config_eeprom(context.period, context.t0))
+            .and_then(|_| context.rtc.write_counter_eeprom(None, context.counter));
+        match result {
+            Ok(_) => {
+                context.unsaved = false;
+                ufmt::uwriteln!(&mut context.serial, "{}", text!("Saved key to RTC EEPROM")).unwrap();
+            },
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+            },
+        }
+    }
+    // Read the currently saved key and TOTP settings from EEPROM
+    pub(super) fn read_key<S: Serial, B: Backend>(context: &mut Context<S, B>, _: Option<&[u8]>) {
+        match context.rtc.read_key_eeprom() {
+            Ok((length, mut key)) => {
+                if !open_record(context, Record::Key, &[length as u8], &mut key) {
+                    return;
+                }
+                context.key_length = length;
+                context.key = key;
+                ufmt::uwriteln!(&mut context.serial, "{}{}{}", text!("Loaded key of length "), length, text!(" from RTC EEPROM")).unwrap();
+            },
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", text!("Error loading previous key from EEPROM - "), e).unwrap();
+            },
+        }
+
+        context.slot = None;
+        context.unsaved = false;
+        match context.rtc.read_counter_eeprom(None) {
+            Ok(counter) => context.counter = counter.unwrap_or(0),
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+            },
+        }
+
+        // Keep the defaults if the TOTP settings were never saved
+        match context.rtc.read_totp_config_eeprom() {
+            Ok(Some((period, t0))) => {
+                context.period = period;
+                context.t0 = t0;
+            },
+            Ok(None) => { },
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+            },
+        }
+    }
+
+    // Load whether a PIN is set and its idle timeout, locking the console if there is one
+    pub(super) fn read_pin<S: Serial, B: Backend>(context: &mut Context<S, B>) {
+        match context.rtc.read_pin_eeprom() {
+            Ok(bytes) => {
+                let config = pin::PinConfig::from_bytes(&bytes);
+                context.pin_set = config.is_some();
+                context.locked = config.is_some();
+                context.idle_timeout = config.map_or(0, |config| config.timeout);
+            },
+            Err(e) => {
+                // Better to stay locked than to open up because the EEPROM couldn't be read
+                lock_console(context);
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+            },
+        }
+    }
+
+    // Lock the console if it was idle for longer than the timeout.
+    // If the time can't be read it locks too, rather than staying open forever.
+    pub(super) fn check_idle<S: Serial, B: Backend>(context: &mut Context<S, B>) {
+        if !context.pin_set || context.locked || context.idle_timeout == 0 {
+            return;
+        }
+
+        match context.rtc.now() {
+            Ok(date) => {
+                let now = date.unix_timestamp();
+                if now.saturating_sub(context.last_activity) > context.idle_timeout as u64 {
+                    lock_console(context);
+                    ufmt::uwriteln!(&mut context.serial, "{}", text!("Locked after being idle")).unwrap();
+                }
+                else {
+                    context.last_activity = now;
+                }
+            },
+            Err(e) => {
+                lock_console(context);
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_RTC_READ, e).unwrap();
+            },
+        }
+    }
+
+    // Lock the console, forgetting the wrap key and the loaded key until the PIN is entered again
+    fn lock_console<S: Serial, B: Backend>(context: &mut Context<S, B>) {
+        context.locked = true;
+        context.wrap_key = None;
+        context.key = [0; 256];
+        context.key_length = 0;
+    }
+
+    fn unlock<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let config = match context.rtc.read_pin_eeprom() {
+            Ok(bytes) => pin::PinConfig::from_bytes(&bytes),
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return;
+            },
+        };
+        let config = match config {
+            Some(config) => config,
+            None => {
+                context.pin_set = false;
+                context.locked = false;
+                ufmt::uwriteln!(&mut context.serial, "{}", text!("No PIN set")).unwrap();
+                return;
+            },
+        };
+
+        let entered_pin = param.unwrap_or(&[]);
+        let wrap_key = match check_pin(context, &config, entered_pin) {
+            Some(wrap_key) => wrap_key,
+            None => return,
+        };
+
+        context.wrap_key = Some(wrap_key);
+        context.idle_timeout = config.timeout;
+        context.last_activity = context.rtc.now().map_or(0, |date| date.unix_timestamp());
+        context.locked = false;
+        ufmt::uwriteln!(&mut context.serial, "{}", text!("Unlocked")).unwrap();
+
+        // Locking cleared the key, so open it again from the slot in use, or the saved key
+        // if there isn't one or it has gone
+        let reopened = match context.slot {
+            Some(index) => match context.rtc.read_slot_eeprom(index).map(|bytes| Slot::from_bytes(&bytes)) {
+                Ok(Some(slot)) => select_slot(context, index, slot),
+                Ok(None) => false,
+                Err(e) => {
+                    ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                    false
+                },
+            },
+            None => false,
+        };
+        if !reopened {
+            read_key(context, None);
+        }
+    }
+
+    // Check a PIN, waiting out any lockout from earlier failed attempts first
+    // Return: the wrap key, or None if the PIN is wrong or the attempt could not be counted
+    fn check_pin<S: Serial, B: Backend>(context: &mut Context<S, B>, config: &pin::PinConfig, entered_pin: &[u8]) -> Option<[u8; KEY_SIZE]> {
+        // Count the attempt before checking it, so cutting the power can't skip the count
+        let failed_attempts = match context.rtc.read_failed_attempts_eeprom() {
+            Ok(failed_attempts) => failed_attempts,
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return None;
+            },
+        };
+        if let Err(e) = context.rtc.write_failed_attempts_eeprom(failed_attempts + 1) {
+            ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+            return None;
+        }
+
+        let delay = pin::lockout_delay(failed_attempts);
+        if delay > 0 {
+            ufmt::uwriteln!(&mut context.serial, "{} failed attempts - waiting {}s", failed_attempts, delay).unwrap();
+            for _ in 0..delay {
+                context.rtc.delay_ms(1000);
+            }
+        }
+
+        let wrap_key = match config.unlock(entered_pin) {
+            Some(wrap_key) => wrap_key,
+            None => {
+                ufmt::uwriteln!(&mut context.serial, "{}", text!("Incorrect PIN")).unwrap();
+                return None;
+            },
+        };
+
+        if let Err(e) = context.rtc.write_failed_attempts_eeprom(0) {
+            ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+        }
+
+        Some(wrap_key)
+    }
+
+    fn lock<S: Serial, B: Backend>(context: &mut Context<S, B>, _: Option<&[u8]>) {
+        match context.pin_set {
+            true => lock_console(context),
+            false => ufmt::uwriteln!(&mut context.serial, "{}", text!("No PIN set - use pin set <PIN>")).unwrap(),
+        }
+    }
+
+    // `pin set <PIN>`, `pin clear`, or show whether a PIN is set
+    fn set_pin<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let saved = match context.rtc.read_pin_eeprom() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return;
+            },
+        };
+        let old_config = pin::PinConfig::from_bytes(&saved);
+
+        let mut args = param.unwrap_or(&[]).splitn(2, |byte| *byte == b' ');
+        let new_config = match (args.next(), args.next()) {
+            (Some(b"set"), Some(new_pin)) => {
+                if new_pin.len() < pin::MIN_PIN_LENGTH || new_pin.len() > pin::MAX_PIN_LENGTH {
+                    ufmt::uwriteln!(&mut context.serial, "PIN must be {} to {} characters", pin::MIN_PIN_LENGTH, pin::MAX_PIN_LENGTH).unwrap();
+                    return;
+                }
+
+                // Seed a fresh salt from the time, temperature and the old salt
+                let mut seed = [0_u8; 8 + 2 + pin::SALT_SIZE];
+                let timestamp = context.rtc.now().map_or(0, |date| date.unix_timestamp());
+                let (temp, quarter_temp) = context.rtc.read_temperature().unwrap_or((0, 0));
+                seed[0..8].copy_from_slice(&byte_helper::u64_to_bytes(timestamp));
+                seed[8..10].copy_from_slice(&[temp as u8, quarter_temp]);
+                seed[10..].copy_from_slice(&saved[0x10..0x20]);
+
+                let timeout = old_config.map_or(pin::DEFAULT_TIMEOUT, |config| config.timeout);
+                Some(pin::PinConfig::with_wrap_key(new_pin, pin::gen_salt(&seed), pin::DEFAULT_ITERATIONS, timeout))
+            },
+            (Some(b"clear"), None) => None,
+            (Some(b""), None) => {
+                match old_config {
+                    Some(config) => ufmt::uwriteln!(&mut context.serial, "PIN set - locks after {}s idle", config.timeout).unwrap(),
+                    None => ufmt::uwriteln!(&mut context.serial, "{}", text!("No PIN set")).unwrap(),
+                }
+                return;
+            },
+            _ => {
+                ufmt::uwriteln!(&mut context.serial, "{}", text!("Use pin set <PIN> or pin clear")).unwrap();
+                return;
+            },
+        };
+
+        // Seal the stored secrets under the new PIN (or store them as plain text without one)
+        // before the new verifier is saved
+        let new_wrap_key = new_config.map(|(_, wrap_key)| wrap_key);
+        let new_config = new_config.map(|(config, _)| config);
+        if !rewrap_secrets(context, new_wrap_key) {
+            return;
+        }
+
+        let bytes = new_config.map_or([0xff; pin::PIN_CONFIG_SIZE], |config| config.to_bytes());
+        let result = context.rtc.write_pin_eeprom(bytes)
+            .and_then(|_| context.rtc.write_failed_attempts_eeprom(0));
+        if let Err(e) = result {
+            ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+            return;
+        }
+
+        context.pin_set = new_config.is_some();
+        context.wrap_key = new_wrap_key;
+        context.idle_timeout = new_config.map_or(0, |config| config.timeout);
+        context.last_activity = context.rtc.now().map_or(0, |date| date.unix_timestamp());
+        match new_config {
+            Some(_) => ufmt::uwriteln!(&mut context.serial, "{}", text!("PIN set")).unwrap(),
+            None => ufmt::uwriteln!(&mut context.serial, "{}", text!("PIN cleared")).unwrap(),
+        }
+    }
+
+    // Show or set how long the console may be idle before it locks again (0 never locks)
+    fn timeout<S: Serial, B: Backend>(context: &mut Context<S, B>, param: Option<&[u8]>) {
+        let mut config = match context.rtc.read_pin_eeprom() {
+            Ok(bytes) => match pin::PinConfig::from_bytes(&bytes) {
+                Some(config) => config,
+                None => {
+                    ufmt::uwriteln!(&mut context.serial, "{}", text!("No PIN set - use pin set <PIN>")).unwrap();
+                    return;
+                },
+            },
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_READ, e).unwrap();
+                return;
+            },
+        };
+
+        if let Some(timeout_param) = param {
+            config.timeout = match byte_helper::parse_u64(timeout_param) {
+                Ok(timeout) if timeout <= u32::MAX as u64 => timeout as u32,
+                _ => {
+                    ufmt::uwriteln!(&mut context.serial, "{}", text!("Timeout must be a number of seconds")).unwrap();
+                    return;
+                },
+            };
+            if let Err(e) = context.rtc.write_pin_eeprom(config.to_bytes()) {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", ERROR_EEPROM_WRITE, e).unwrap();
+                return;
+            }
+            context.idle_timeout = config.timeout;
+        }
+
+        ufmt::uwriteln!(&mut context.serial, "Idle timeout: {}s", context.idle_timeout).unwrap();
+    }
+
+    // Read the current temperature from the RTC 
+    pub(super) fn read_temperature<S: Serial, B: Backend>(context: &mut Context<S, B>, _: Option<&[u8]>) {
+        match context.rtc.read_temperature() {
+            Ok((temp, quarter_temp)) => {
+                ufmt::uwriteln!(&mut context.serial, "Current Temperature: {}.{} °C", temp, quarter_temp*25).unwrap();
+            },
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", text!("Error reading temperature from RTC - "), e).unwrap();
+            },
+        }
+    }
+
+    // Read the current temperature from the RTC 
+    pub(super) fn update_temperature<S: Serial, B: Backend>(context: &mut Context<S, B>, _: Option<&[u8]>) {
+        match context.rtc.update_temperature() {
+            Ok(true) => {
+                ufmt::uwriteln!(&mut context.serial, "{}", text!("Requested temperature update...")).unwrap();
+            },
+            Ok(false) => {
+                ufmt::uwriteln!(&mut context.serial, "{}", text!("Temperature update already in-progress!")).unwrap();
+            },
+            Err(e) => {
+                ufmt::uwriteln!(&mut context.serial, "{}{:?}", text!("Error sending command to RTC - "), e).unwrap();
+            },
+        }
+    }
+
+    fn help_screen<S: Serial, B: Backend>(context: &mut Context<S, B>, _: Option<&[u8]>) {
+        ufmt::uwriteln!(&mut context.serial, "{}",
+            text!("key <OTP Key> - Set OTP key.\n\
+            key <hex|b32|b64> <OTP Key> - Set OTP key from hex, base32 or base64.\n\
+            key - Show current OTP key.\n\
+            key <hex|b32|b64> - Show current OTP key as hex, base32 or base64.\n\
+            digit <OTP Digits> - Set digits of OTP. (default is 6)\n\
+            digit - Show OTP digits setting.\n\
+            format <decimal|steam> - Set OTP code format. Steam codes are 5 characters. (default is decimal)\n\
+            format - Show OTP code format setting.\n\
+            algo <SHA1|SHA256|SHA512> - Set OTP hash algorithm. (default is SHA1)\n\
+            algo - Show OTP hash algorithm setting.\n\
+            uri <otpauth://...> - Set key, digits, period and algorithm from an otpauth URI, kept in RAM until save or add.\n\
+            uri [totp|hotp] - Show current settings as an otpauth URI, HOTP with the counter.\n\
+            list - Show the key slots saved in RTC EEPROM. The selected slot is marked with *.\n\
+            use <slot> - Load the key and settings from a slot, by number or label.\n\
+            use - Show the selected slot.\n\
+            add <label> - Save the current key and settings into a free slot.\n\
+            delete <slot> - Erase a slot, by number or label. Deleting the slot in use loads the saved key.\n\
+            hotp <HOTP Counter> [slot] - Calculate OTP for a given counter value.\n\
+            next - Calculate OTP for the stored HOTP counter and advance it.\n\
+            counter <n> - Set and save the stored HOTP counter.\n\
+            counter - Show the stored HOTP counter.\n\
+            totp [slot] - Calculate OTP for the current time and show the seconds left in this step.\n\
+            period <seconds> [T0] - Set and save the TOTP time step and start time, for the slot in use if there is one. (default is 30 and 0)\n\
+            period - Show TOTP time step and start time.\n\
+            suite <OCRA suite> - Set the OCRA suite, e.g. OCRA-1:HOTP-SHA256-8:C-QN08-PSHA1. (default is OCRA-1:HOTP-SHA1-6:QN08)\n\
+            suite - Show the OCRA suite.\n\
+            ocra <challenge> [PIN] [session] - Calculate the OCRA response to a challenge. Counter suites advance the HOTP counter.\n\
+            chalresp <hex challenge> - Calculate the HMAC-SHA1 response with the challenge-response secret.\n\
+            crkey <hex secret> - Set and save the challenge-response secret. (up to 30 bytes)\n\
+            crkey - Show whether a challenge-response secret is set.\n\
+            crmode <fixed|variable> - Set whether 64-byte challenges have their padding removed. (default is variable)\n\
+            crmode - Show the challenge-response mode.\n\
+            verify <code> - Check a TOTP code within the allowed window of steps.\n\
+            verify hotp <code> - Check a HOTP code within the look-ahead window and advance the counter.\n\
+            window <steps> [look-ahead] - Set the TOTP window and HOTP look-ahead for verify. (default is 1 and 10)\n\
+            window - Show the TOTP window and HOTP look-ahead.\n\
+            time <UNIX timestamp> [PIN] - Set date and time. With a PIN set, moving more than 5 minutes forward or 30s back, in one go or in total since the last change with the PIN, needs the PIN.\n\
+            time - Show current date and time.\n\
+            sync [PIN] - Set date and time from the host with otpctl, to within a fraction of a second. Large changes need the PIN.\n\
+            timelog - Show the last 4 changes to the date and time made with the PIN, and the last 16 small ones.\n\
+            alarm <1|2> <HH:MM:SS> [date <1-31>|day <1-7>] - Set an alarm and enable its interrupt on INT/SQW. Use * for any, e.g. *:*:00 every minute. Alarm 2 takes HH:MM.\n\
+            alarm <1|2> clear - Disable an alarm's interrupt and clear its flag.\n\
+            alarm - Show the alarms, whether their interrupts are on and whether they have fired.\n\
+            sqw <1|1024|4096|8192> - Put a square wave out on INT/SQW instead of the alarm interrupt.\n\
+            sqw off - Hand INT/SQW back to the alarm interrupt.\n\
+            sqw 32k <on|off> - Turn the 32kHz output on or off.\n\
+            sqw - Show what INT/SQW and the 32kHz pin put out.\n\
+            temp - Show current temperature in Celsius.\n\
+            utemp - Force the RTC to update its temperature reading.\n\
+            read <addr> - Read a byte from RTC EEPROM at the given 2-byte address. Must provide four hex digits.\n\
+            readp <addr> - Read a 32-byte page from the RTC EEPROM at the given 2-byte address. Must provide four hex digits.\n\
+            write <addr> <data> - Write a byte to the RTC EEPROM at the given 2-byte address. Must provide four and two hex digits. From 0b00 on only while no PIN is set.\n\
+            save - Save the current key, TOTP settings and HOTP counter into RTC EEPROM. A key that is entered counts in RAM until saved.\n\
+            load - Load the saved key and TOTP settings from RTC EEPROM.\n\
+            unlock <PIN> - Unlock the console. Failed attempts make the next one wait longer.\n\
+            lock - Lock the console until the PIN is entered.\n\
+            pin set <PIN> - Set the console PIN, 8 to 32 characters. Only a salted verifier is saved, and saved secrets are sealed with a key derived from the PIN.\n\
+            pin clear - Remove the console PIN. Saved secrets are stored as plain text again.\n\
+            pin - Show whether a PIN is set.\n\
+            timeout <seconds> - Set how long the console can be idle before it locks. (default is 300, 0 never locks)\n\
+            timeout - Show the idle timeout.\n\
+            help - Show this help menu.")
+            ).unwrap();
+    }
+}
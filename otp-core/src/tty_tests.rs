@@ -0,0 +1,676 @@
+#[cfg(test)]
+mod transcript {
+    extern crate std;
+    use core::cell::RefCell;
+    use core::convert::Infallible;
+    use std::boxed::Box;
+    use std::collections::VecDeque;
+    use std::string::String;
+    use crate::sync;
+    use crate::clock::Clock;
+    use crate::datetime::Datetime;
+    use crate::eeprom::Storage;
+    use crate::rtc::Rtc;
+    use crate::sim::{Sim, SimBus, SimDelay, WRITE_CYCLE_MS};
+    use crate::tty::TTY;
+
+    // Answers the console whenever it waits for input, given everything it has printed
+    type Host = Box<dyn FnMut(&str) -> Option<String>>;
+
+    // Both ends of the serial line: what has been typed and what the console printed
+    struct Terminal {
+        input: RefCell<VecDeque<u8>>,
+        output: RefCell<String>,
+        host: RefCell<Option<Host>>,
+    }
+
+    struct Port<'a>(&'a Terminal);
+
+    impl embedded_hal::serial::Read<u8> for Port<'_> {
+        type Error = Infallible;
+
+        fn read(&mut self) -> nb::Result<u8, Self::Error> {
+            if self.0.input.borrow().is_empty() {
+                if let Some(host) = self.0.host.borrow_mut().as_mut() {
+                    if let Some(reply) = host(&self.0.output.borrow()) {
+                        self.0.input.borrow_mut().extend(reply.bytes());
+                    }
+                }
+            }
+
+            self.0.input.borrow_mut().pop_front().ok_or(nb::Error::WouldBlock)
+        }
+    }
+
+    impl ufmt::uWrite for Port<'_> {
+        type Error = Infallible;
+
+        fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+            self.0.output.borrow_mut().push_str(s);
+            Ok(())
+        }
+    }
+
+    impl Terminal {
+        fn new() -> Self {
+            Self { input: RefCell::new(VecDeque::new()), output: RefCell::new(String::new()), host: RefCell::new(None) }
+        }
+
+        fn start<'a>(&'a self, sim: &'a Sim) -> TTY<Port<'a>, Rtc<SimBus<'a>, SimDelay<'a>>> {
+            TTY::new(Port(self), Rtc::new(sim.bus(), sim.delay()))
+        }
+
+        // Type `input` a byte at a time and return everything printed since the last call
+        fn send(&self, tty: &mut TTY<Port<'_>, Rtc<SimBus<'_>, SimDelay<'_>>>, input: &str) -> String {
+            for byte in input.bytes() {
+                self.input.borrow_mut().push_back(byte);
+                tty.wait_for_byte();
+            }
+
+            self.output.replace(String::new())
+        }
+    }
+
+    // The module from the README session, with a key already saved and the clock running
+    fn provisioned() -> Sim {
+        let sim = Sim::new();
+        let mut rtc = Rtc::new(sim.bus(), sim.delay());
+        let mut key = [0_u8; 256];
+        key[0..10].copy_from_slice(b"Hello!\xde\xad\xbe\xef");
+        rtc.write_key_eeprom(10, key, None).unwrap();
+        Clock::set(&mut rtc, Datetime::from_timestamp(0)).unwrap();
+
+        sim
+    }
+
+    #[test]
+    fn loads_the_saved_key_at_startup() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+
+        assert_eq!(terminal.send(&mut tty, ""), "Loaded key of length 10 from RTC EEPROM\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "key\n"), "key\nHello!\u{de}\u{ad}\u{be}\u{ef}\n$ ");
+    }
+
+    #[test]
+    fn readme_session() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+
+        assert_eq!(
+            terminal.send(&mut tty, "time 1669714637\n"),
+            "time 1669714637\nDate: 2022/11/29 - 9:37:17\nTimestamp: 1669714637\n\n$ "
+        );
+        sim.advance_ms(22_000);
+        assert_eq!(
+            terminal.send(&mut tty, "totp\n"),
+            "totp\nTimestamp: 1669714659\nCounter: 55657155\nRemaining: 21s\n169658\n$ "
+        );
+        assert_eq!(
+            terminal.send(&mut tty, "readp 0020\n"),
+            "readp 0020\nPage: [72, 101, 108, 108, 111, 33, 222, 173, 190, 239, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]\n\n$ "
+        );
+    }
+
+    #[test]
+    fn readme_shows_the_help_listing() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+
+        let help = terminal.send(&mut tty, "help\n");
+        assert!(include_str!("../../README.md").contains(&std::format!("$ {}", help)));
+    }
+
+    #[test]
+    fn uri_round_trip() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "format steam\n");
+        terminal.send(&mut tty, "period 60 1000\n");
+
+        // A bad URI leaves the key alone
+        assert_eq!(
+            terminal.send(&mut tty, "uri otpauth://totp/a?secret=JBSWY3DPEHPK3PX1\n"),
+            "uri otpauth://totp/a?secret=JBSWY3DPEHPK3PX1\nError parsing URI - InvalidSecret\n\n$ "
+        );
+        assert_eq!(terminal.send(&mut tty, "key\n"), "key\nHello!\u{de}\u{ad}\u{be}\u{ef}\n$ ");
+
+        // Importing starts over with decimal codes and T0 of 0
+        let totp = "otpauth://totp/Example%20Co:alice%40example.com?secret=MFRGGZDFMZTWQ2LK&issuer=Example%20Co&algorithm=SHA256&digits=8&period=45";
+        assert_eq!(
+            terminal.send(&mut tty, &std::format!("uri {}\n", totp)),
+            std::format!("uri {}\nLoaded SHA256 key - 8 digits, period of 45\n\n$ ", totp)
+        );
+        assert_eq!(terminal.send(&mut tty, "format\n"), "format\ndecimal\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "uri\n"), std::format!("uri\n{}\n\n$ ", totp));
+        assert_eq!(terminal.send(&mut tty, "period\n"), "period\nPeriod: 45s\nT0: 0\n\n$ ");
+
+        // The saved key keeps its own settings until the imported one is saved
+        let mut restarted = terminal.start(&sim);
+        terminal.send(&mut restarted, "");
+        assert_eq!(terminal.send(&mut restarted, "period\n"), "period\nPeriod: 60s\nT0: 1000\n\n$ ");
+        assert_eq!(terminal.send(&mut restarted, "key\n"), "key\nHello!\u{de}\u{ad}\u{be}\u{ef}\n$ ");
+
+        let hotp = "otpauth://hotp/bob?secret=MFRGGZDFMZTWQ2LK&algorithm=SHA1&digits=6&counter=42";
+        assert_eq!(
+            terminal.send(&mut tty, &std::format!("uri {}\n", hotp)),
+            std::format!("uri {}\nLoaded SHA1 key - 6 digits, period of 30\nHOTP counter: 42\n\n$ ", hotp)
+        );
+        assert_eq!(terminal.send(&mut tty, "uri hotp\n"), std::format!("uri hotp\n{}\n\n$ ", hotp));
+        terminal.send(&mut tty, "next\n");
+
+        // Adding it to a slot keeps its period and counter there, and leaves the saved key's alone
+        terminal.send(&mut tty, "add bob\n");
+        let mut restarted = terminal.start(&sim);
+        terminal.send(&mut restarted, "");
+        assert_eq!(terminal.send(&mut restarted, "counter\n"), "counter\nHOTP counter: 0\n\n$ ");
+        terminal.send(&mut restarted, "use bob\n");
+        assert_eq!(terminal.send(&mut restarted, "counter\n"), "counter\nHOTP counter: 43\n\n$ ");
+
+        // Saving it makes it the saved key, with its period
+        terminal.send(&mut tty, &std::format!("uri {}\n", totp));
+        terminal.send(&mut tty, "save\n");
+        let mut restarted = terminal.start(&sim);
+        terminal.send(&mut restarted, "");
+        assert_eq!(terminal.send(&mut restarted, "period\n"), "period\nPeriod: 45s\nT0: 0\n\n$ ");
+        assert_eq!(terminal.send(&mut restarted, "key b32\n"), "key b32\nMFRGGZDFMZTWQ2LK\n$ ");
+    }
+
+    #[test]
+    fn entering_a_key_keeps_the_saved_counter() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "key 12345678901234567890\n");
+        terminal.send(&mut tty, "save\n");
+        for _ in 0..3 {
+            terminal.send(&mut tty, "next\n");
+        }
+
+        // A key that is never saved counts in RAM only
+        let mut restarted = terminal.start(&sim);
+        terminal.send(&mut restarted, "");
+        terminal.send(&mut restarted, "key somethingelse\n");
+        assert_eq!(terminal.send(&mut restarted, "counter\n"), "counter\nHOTP counter: 0\n\n$ ");
+        terminal.send(&mut restarted, "next\n");
+
+        let mut restarted = terminal.start(&sim);
+        terminal.send(&mut restarted, "");
+        assert_eq!(terminal.send(&mut restarted, "counter\n"), "counter\nHOTP counter: 3\n\n$ ");
+        assert_eq!(terminal.send(&mut restarted, "next\n"), "next\nCounter: 3\n969429\n$ ");
+
+        // Saving the key saves its counter along with it
+        terminal.send(&mut restarted, "key other\n");
+        terminal.send(&mut restarted, "next\n");
+        terminal.send(&mut restarted, "save\n");
+        let mut restarted = terminal.start(&sim);
+        terminal.send(&mut restarted, "");
+        assert_eq!(terminal.send(&mut restarted, "counter\n"), "counter\nHOTP counter: 1\n\n$ ");
+    }
+
+    #[test]
+    fn deleting_the_slot_in_use_loads_the_saved_key() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "counter 100\n");
+        terminal.send(&mut tty, "key b32 JBSWY3DPEHPK3PXP\n");
+        terminal.send(&mut tty, "add slotA\n");
+        terminal.send(&mut tty, "next\n");
+        terminal.send(&mut tty, "next\n");
+        terminal.send(&mut tty, "use slotA\n");
+
+        assert_eq!(
+            terminal.send(&mut tty, "delete slotA\n"),
+            "delete slotA\nDeleted slot 0\nLoaded key of length 10 from RTC EEPROM\n\n$ "
+        );
+        assert_eq!(terminal.send(&mut tty, "use\n"), "use\nNo slot selected\n\n$ ");
+        assert!(terminal.send(&mut tty, "next\n").starts_with("next\nCounter: 100\n"));
+        terminal.send(&mut tty, "load\n");
+        assert_eq!(terminal.send(&mut tty, "counter\n"), "counter\nHOTP counter: 101\n\n$ ");
+    }
+
+    #[test]
+    fn period_is_saved_with_the_slot_in_use() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "period 60 1000\n");
+        terminal.send(&mut tty, "key other\n");
+        terminal.send(&mut tty, "add work\n");
+        terminal.send(&mut tty, "pin set 12345678\n");
+
+        // The slot's secret is sealed with its period, so it is sealed again
+        assert_eq!(terminal.send(&mut tty, "period 45\n"), "period 45\nPeriod: 45s\nT0: 1000\n\n$ ");
+        terminal.send(&mut tty, "load\n");
+        assert_eq!(terminal.send(&mut tty, "period\n"), "period\nPeriod: 60s\nT0: 1000\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "use work\n"), "use work\nUsing slot 0: work\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "period\n"), "period\nPeriod: 45s\nT0: 1000\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "key\n"), "key\nother\n$ ");
+    }
+
+    #[test]
+    fn usage_errors() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+
+        assert_eq!(terminal.send(&mut tty, "hotp 0\n"), "hotp 0\n282760\n$ ");
+        assert_eq!(terminal.send(&mut tty, "hotp\n"), "hotp\nUse hotp <counter> [slot]\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "hotp 1x\n"), "hotp 1x\nCounter must be a decimal number\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "hotp 99999999999999999999\n"), "hotp 99999999999999999999\nCounter must be a decimal number\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "delete\n"), "delete\nUse delete <slot number or label>\n\n$ ");
+    }
+
+    #[test]
+    fn stopped_clock_must_be_set() {
+        let sim = provisioned();
+        sim.power_loss();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+
+        assert_eq!(
+            terminal.send(&mut tty, ""),
+            "Warning: the RTC lost time, e.g. its battery ran out. Set the time before using totp.\n\
+            Loaded key of length 10 from RTC EEPROM\n\n$ "
+        );
+        assert!(terminal.send(&mut tty, "time\n").ends_with("\nWarning: the RTC lost time, e.g. its battery ran out. Set the time before using totp.\n\n$ "));
+        let refused = "Error reading time - the RTC lost time, set it with time or otpctl sync\n";
+        assert_eq!(terminal.send(&mut tty, "totp\n"), std::format!("totp\n{}\n$ ", refused));
+        assert_eq!(terminal.send(&mut tty, "verify 123456\n"), std::format!("verify 123456\n{}\n$ ", refused));
+
+        // Setting the time clears OSF
+        terminal.send(&mut tty, "time 1669714637\n");
+        assert_eq!(sim.registers()[0x0f] & 0x80, 0);
+        assert!(terminal.send(&mut tty, "totp\n").starts_with("totp\nTimestamp: 1669714637\n"));
+
+        // Losing time while running is noticed too
+        sim.power_loss();
+        assert_eq!(terminal.send(&mut tty, "totp\n"), std::format!("totp\n{}\n$ ", refused));
+    }
+
+    #[test]
+    fn alarms() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "time 1669714637\n");
+
+        assert_eq!(
+            terminal.send(&mut tty, "alarm\n"),
+            "alarm\nAlarm 1: not set, interrupt off\nAlarm 2: not set, interrupt off\n\n$ "
+        );
+        assert_eq!(
+            terminal.send(&mut tty, "alarm 1 *:*:20\n"),
+            "alarm 1 *:*:20\nAlarm 1: *:*:20, interrupt on\n\n$ "
+        );
+        assert_eq!(
+            terminal.send(&mut tty, "alarm 2 7:05 day 3\n"),
+            "alarm 2 7:05 day 3\nAlarm 2: 07:05 day 3, interrupt on\n\n$ "
+        );
+
+        sim.advance_ms(3_000);
+        assert!(sim.interrupt());
+        assert_eq!(
+            terminal.send(&mut tty, "alarm\n"),
+            "alarm\nAlarm 1: *:*:20, interrupt on, fired\nAlarm 2: 07:05 day 3, interrupt on\n\n$ "
+        );
+        assert_eq!(terminal.send(&mut tty, "alarm 1 clear\n"), "alarm 1 clear\nAlarm 1: *:*:20, interrupt off\n\n$ ");
+        assert!(!sim.interrupt());
+
+        assert_eq!(
+            terminal.send(&mut tty, "alarm 2 *:*:30\n"),
+            "alarm 2 *:*:30\nError setting alarm - alarm 2 has no seconds, use HH:MM, with * for any, then date <1-31> or day <1-7>\n\n$ "
+        );
+        assert_eq!(
+            terminal.send(&mut tty, "alarm 1 8:*:00\n"),
+            "alarm 1 8:*:00\nError setting alarm - use HH:MM:SS, with * for any, then date <1-31> or day <1-7>\n\n$ "
+        );
+        assert_eq!(terminal.send(&mut tty, "alarm 3\n"), "alarm 3\nError finding alarm - there are only alarms 1 and 2\n\n$ ");
+    }
+
+    #[test]
+    fn square_wave() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+
+        assert_eq!(terminal.send(&mut tty, "sqw\n"), "sqw\nINT/SQW: alarm interrupt\n32kHz: on\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "sqw 1\n"), "sqw 1\nINT/SQW: 1Hz square wave\n32kHz: on\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "sqw 32k off\n"), "sqw 32k off\nINT/SQW: 1Hz square wave\n32kHz: off\n\n$ ");
+        assert_eq!(sim.registers()[0x0e] & 0b0001_1100, 0);
+        assert_eq!(terminal.send(&mut tty, "sqw 8192\n"), "sqw 8192\nINT/SQW: 8192Hz square wave\n32kHz: off\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "sqw off\n"), "sqw off\nINT/SQW: alarm interrupt\n32kHz: off\n\n$ ");
+
+        assert_eq!(
+            terminal.send(&mut tty, "sqw 2048\n"),
+            "sqw 2048\nError setting square wave - use 1, 1024, 4096 or 8192 Hz, or off\n\n$ "
+        );
+        assert_eq!(
+            terminal.send(&mut tty, "sqw 32k\n"),
+            "sqw 32k\nError setting square wave - use 32k on or 32k off\n\n$ "
+        );
+        assert_eq!(
+            terminal.send(&mut tty, "sqw 32k maybe\n"),
+            "sqw 32k maybe\nError setting square wave - use 32k on or 32k off\n\n$ "
+        );
+    }
+
+    #[test]
+    fn line_editing() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+
+        // Carriage returns are ignored and backspace rubs out the last character
+        assert_eq!(terminal.send(&mut tty, "digti\x08\x08it\r\n"), "digti\x08 \x08\x08 \x08it\n6\n\n$ ");
+        // Nothing to rub out at the start of a line
+        assert_eq!(terminal.send(&mut tty, "\x08"), "");
+        // Unknown commands print nothing
+        assert_eq!(terminal.send(&mut tty, "bogus\n"), "bogus\n\n$ ");
+    }
+
+    #[test]
+    fn locked_console_only_unlocks() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        assert_eq!(terminal.send(&mut tty, "pin set 1234\n"), "pin set 1234\nPIN must be 8 to 32 characters\n\n$ ");
+        terminal.send(&mut tty, "pin set 12345678\n");
+
+        // A restart comes up locked, with the key still sealed
+        let mut tty = terminal.start(&sim);
+        assert_eq!(terminal.send(&mut tty, "key\n"), "\n$ key\nLocked - use unlock <PIN>\n\n$ ");
+        assert!(terminal.send(&mut tty, "unlock 12345678\n").contains("Loaded key of length 10 from RTC EEPROM"));
+        assert_eq!(terminal.send(&mut tty, "key\n"), "key\nHello!\u{de}\u{ad}\u{be}\u{ef}\n$ ");
+    }
+
+    #[test]
+    fn unlocking_keeps_the_slot_in_use() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "key other\n");
+        terminal.send(&mut tty, "add work\n");
+        terminal.send(&mut tty, "pin set 12345678\n");
+        assert_eq!(terminal.send(&mut tty, "use work\n"), "use work\nUsing slot 0: work\n\n$ ");
+
+        terminal.send(&mut tty, "lock\n");
+        assert_eq!(terminal.send(&mut tty, "unlock 12345678\n"), "unlock 12345678\nUnlocked\nUsing slot 0: work\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "use\n"), "use\nUsing slot 0\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "key\n"), "key\nother\n$ ");
+    }
+
+    // Reply to the sync handshake after the console has waited `latency_ms` for it
+    fn host_replying(latency_ms: u32, host_ms: u64, nonce: Option<&'static str>) -> Host {
+        let mut waited = 0;
+        Box::new(move |output: &str| {
+            let sent = output.rfind("Sync ").map(|start| &output[start + 5..start + 21])?;
+            waited += 1;
+            match waited > latency_ms {
+                true => Some(std::format!("{} {}\n", nonce.unwrap_or(sent), host_ms)),
+                false => None,
+            }
+        })
+    }
+
+    #[test]
+    fn sync_sets_the_next_second() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "time 1669714000\n");
+
+        // Half the round trip is added to the host's stamp, then the clock waits for 9:37:18
+        terminal.host.replace(Some(host_replying(40, 1_669_714_637_250, None)));
+        let start = sim.millis();
+        assert_eq!(
+            terminal.send(&mut tty, "sync\n"),
+            "sync\nSync 0000000000000000\nRound trip: 40ms\nCorrection: +638s\nDate: 2022/11/29 - 9:37:18\nTimestamp: 1669714638\n\n$ "
+        );
+        assert!(sim.millis() - start >= 40 + 730);
+
+        // The DS3231 counts a full second from the moment it was set, less the time taken to
+        // log the change
+        terminal.host.replace(None);
+        sim.advance_ms(900);
+        assert!(terminal.send(&mut tty, "time\n").contains("Timestamp: 1669714638\n"));
+        sim.advance_ms(100);
+        assert!(terminal.send(&mut tty, "time\n").contains("Timestamp: 1669714639\n"));
+
+        // Every handshake uses a fresh nonce
+        terminal.host.replace(Some(host_replying(0, 1_669_714_700_000, None)));
+        assert!(terminal.send(&mut tty, "sync\n").starts_with("sync\nSync 0000000000000001\nRound trip: 0ms\nCorrection: +61s\n"));
+    }
+
+    // The moment the DS3231 was last set, found from its next tick a second later
+    fn last_set_ms(sim: &Sim) -> u64 {
+        let seconds = sim.registers()[0];
+        while sim.registers()[0] == seconds {
+            sim.advance_ms(1);
+        }
+        sim.millis() - 1000
+    }
+
+    #[test]
+    fn sync_compensates_for_latency() {
+        for (latency_ms, host_ms) in [(0, 1_669_714_637_250), (40, 1_669_714_637_250), (300, 1_669_714_637_900), (1999, 1_669_714_637_000)] {
+            let sim = provisioned();
+            let terminal = Terminal::new();
+            let mut tty = terminal.start(&sim);
+            terminal.send(&mut tty, "");
+            terminal.send(&mut tty, "time 1669714000\n");
+
+            // The host stamped its reply half the round trip before it arrived, so the clock
+            // waits out the rest of that second after the reply
+            let (wait_ms, second) = sync::schedule(host_ms, latency_ms as u16);
+            assert_eq!(wait_ms as u64, 1000 - (host_ms + latency_ms / 2) % 1000);
+            terminal.host.replace(Some(host_replying(latency_ms as u32, host_ms, None)));
+            let start = sim.millis();
+            let output = terminal.send(&mut tty, "sync\n");
+            assert!(output.contains(&std::format!("Round trip: {}ms\n", latency_ms)), "{}", output);
+            assert!(output.contains(&std::format!("Timestamp: {}\n", second)), "{}", output);
+
+            // The nonce is saved, taking an EEPROM write cycle, before it is sent
+            terminal.host.replace(None);
+            assert_eq!(last_set_ms(&sim) - start, WRITE_CYCLE_MS + latency_ms + wait_ms as u64);
+        }
+    }
+
+    #[test]
+    fn sync_rejects_other_nonces() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "time 1669714000\n");
+
+        terminal.host.replace(Some(host_replying(0, 1_669_714_637_250, Some("00000000000000ff"))));
+        assert_eq!(
+            terminal.send(&mut tty, "sync\n"),
+            "sync\nSync 0000000000000000\nError syncing time - the reply does not match\n\n$ "
+        );
+        terminal.host.replace(None);
+        assert!(terminal.send(&mut tty, "time\n").contains("Timestamp: 1669714000\n"));
+    }
+
+    #[test]
+    fn sync_gives_up_without_a_reply() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+
+        let start = sim.millis();
+        assert_eq!(
+            terminal.send(&mut tty, "sync\n"),
+            "sync\nSync 0000000000000000\nError syncing time - no reply from the host\n\n$ "
+        );
+        assert_eq!(sim.millis() - start, WRITE_CYCLE_MS + sync::REPLY_TIMEOUT_MS as u64);
+        // The console is back at the prompt
+        assert_eq!(terminal.send(&mut tty, "digit\n"), "digit\n6\n\n$ ");
+
+        // A reply that comes a millisecond too late is not waited for, and the clock is left alone
+        terminal.send(&mut tty, "time 1669714000\n");
+        terminal.host.replace(Some(host_replying(sync::REPLY_TIMEOUT_MS as u32 + 1, 1_669_714_637_250, None)));
+        assert_eq!(
+            terminal.send(&mut tty, "sync\n"),
+            "sync\nSync 0000000000000001\nError syncing time - no reply from the host\n\n$ "
+        );
+        terminal.host.replace(None);
+        assert!(terminal.send(&mut tty, "time\n").contains("Timestamp: 1669714002\n"));
+    }
+
+    #[test]
+    fn large_clock_changes_need_the_pin() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+
+        // Without a PIN anything goes
+        assert!(terminal.send(&mut tty, "time 1669714637\n").contains("Timestamp: 1669714637\n"));
+        terminal.send(&mut tty, "pin set 12345678\n");
+
+        assert_eq!(
+            terminal.send(&mut tty, "time 1669800000\n"),
+            "time 1669800000\nError setting time - large changes need the PIN, use time <UNIX timestamp> <PIN>\n\n$ "
+        );
+        assert_eq!(
+            terminal.send(&mut tty, "time 1669714000\n"),
+            "time 1669714000\nError setting time - large changes need the PIN, use time <UNIX timestamp> <PIN>\n\n$ "
+        );
+        assert_eq!(terminal.send(&mut tty, "time 1669800000 87654321\n"), "time 1669800000 87654321\nIncorrect PIN\n\n$ ");
+        assert_eq!(terminal.send(&mut tty, "time 16697x\n"), "time 16697x\nError setting time - invalid timestamp\n\n$ ");
+
+        // Drift corrections don't need the PIN
+        assert!(terminal.send(&mut tty, "time 1669714900\n").contains("Timestamp: 1669714900\n"));
+        assert!(terminal.send(&mut tty, "time 1669714880\n").contains("Timestamp: 1669714880\n"));
+        assert!(terminal.send(&mut tty, "time 1669800000 12345678\n").contains("Timestamp: 1669800000\n"));
+
+        // Only the changes that were made are logged
+        let log = terminal.send(&mut tty, "timelog\n");
+        let lines: std::vec::Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[1], "Authorized changes:");
+        assert_eq!(lines[2], "0: 0 -> 1669714637 (+1669714637s) time, no PIN set");
+        assert_eq!(lines[3], "3: 1669714880 -> 1669800000 (+85120s) time, PIN");
+        assert_eq!(lines[4], "Small changes:");
+        assert_eq!(lines[5], "1: 1669714637 -> 1669714900 (+263s) time, small change");
+        assert_eq!(lines[6], "2: 1669714900 -> 1669714880 (-20s) time, small change");
+    }
+
+    #[test]
+    fn raw_writes_leave_the_pin_and_clock_log_alone() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "time 1669714637\n");
+        let refused = "Error writing EEPROM - the PIN and clock log records can't be written while a PIN is set\n\n$ ";
+
+        // Without a PIN there is nothing to protect
+        assert_eq!(terminal.send(&mut tty, "write 0e00 00\n"), "write 0e00 00\n\n$ ");
+        terminal.send(&mut tty, "pin set 12345678\n");
+        for address in ["0b00", "0b40", "0b80", "0bc0", "0d80", "0e00", "0fff"] {
+            let command = std::format!("write {} 00\n", address);
+            assert_eq!(terminal.send(&mut tty, &command), std::format!("{}{}", command, refused));
+        }
+        assert_eq!(terminal.send(&mut tty, "write 0aff 00\n"), "write 0aff 00\n\n$ ");
+
+        // So the PIN still stands between an unlocked console and a far off time
+        assert_eq!(terminal.send(&mut tty, "unlock x\n"), "unlock x\nIncorrect PIN\n\n$ ");
+        assert!(terminal.send(&mut tty, "time 1769714637\n").contains("large changes need the PIN"));
+    }
+
+    #[test]
+    fn small_clock_changes_keep_the_authorized_ones_logged() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "pin set 12345678\n");
+        terminal.send(&mut tty, "time 1669714637 12345678\n");
+
+        // More small changes than the log holds, going back and forth
+        for change in 0..20 {
+            terminal.send(&mut tty, &std::format!("time {}\n", 1669714637 + change % 2 * 10));
+        }
+
+        let log = terminal.send(&mut tty, "timelog\n");
+        let lines: std::vec::Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 3 + 1 + 16 + 2);
+        assert_eq!(lines[1], "Authorized changes:");
+        assert!(lines[2].starts_with("0: ") && lines[2].ends_with(" -> 1669714637 (+1669714637s) time, PIN"), "{}", lines[2]);
+        assert_eq!(lines[3], "Small changes:");
+        assert!(lines[4].starts_with("5: "), "{}", lines[4]);
+        assert!(lines[19].starts_with("20: "), "{}", lines[19]);
+    }
+
+    #[test]
+    fn small_clock_changes_add_up() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "time 1669714637\n");
+        terminal.send(&mut tty, "pin set 12345678\n");
+
+        assert!(terminal.send(&mut tty, "time 1669714837\n").contains("Timestamp: 1669714837\n"));
+        assert_eq!(
+            terminal.send(&mut tty, "time 1669715037\n"),
+            "time 1669715037\nError setting time - large changes need the PIN, use time <UNIX timestamp> <PIN>\n\n$ "
+        );
+        assert!(terminal.send(&mut tty, "time 1669714817\n").contains("Timestamp: 1669714817\n"));
+
+        // 50 hours later the clock may move 100 seconds further
+        sim.advance_ms(50 * 3_600_000);
+        let now = 1669714817 + 50 * 3600;
+        terminal.send(&mut tty, "unlock 12345678\n");
+        assert!(terminal.send(&mut tty, &std::format!("time {}\n", now + 230)).contains("Error setting time"));
+        assert!(terminal.send(&mut tty, &std::format!("time {}\n", now + 210)).contains(&std::format!("Timestamp: {}\n", now + 210)));
+
+        // Setting the clock with the PIN starts over
+        assert!(terminal.send(&mut tty, &std::format!("time {} 12345678\n", now)).contains(&std::format!("Timestamp: {}\n", now)));
+        assert!(terminal.send(&mut tty, &std::format!("time {}\n", now + 300)).contains(&std::format!("Timestamp: {}\n", now + 300)));
+    }
+
+    #[test]
+    fn sync_with_the_pin_can_make_large_changes() {
+        let sim = provisioned();
+        let terminal = Terminal::new();
+        let mut tty = terminal.start(&sim);
+        terminal.send(&mut tty, "");
+        terminal.send(&mut tty, "time 1669714000\n");
+        terminal.send(&mut tty, "pin set 12345678\n");
+
+        terminal.host.replace(Some(host_replying(0, 1_669_800_000_500, None)));
+        assert!(terminal.send(&mut tty, "sync\n").ends_with("\nError syncing time - large changes need the PIN, use otpctl --pin\n\n$ "));
+        // A wrong PIN stops before the handshake
+        assert_eq!(terminal.send(&mut tty, "sync 87654321\n"), "sync 87654321\nIncorrect PIN\n\n$ ");
+
+        let response = terminal.send(&mut tty, "sync 12345678\n");
+        assert!(response.contains("\nRound trip: 0ms\nCorrection: +86000s\n"), "{}", response);
+        assert!(response.contains("\nTimestamp: 1669800001\n"), "{}", response);
+
+        terminal.host.replace(None);
+        assert!(terminal.send(&mut tty, "timelog\n").contains(" -> 1669800001 (+86000s) sync, PIN\n"));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::byte_helper;
+use crate::chacha20poly1305::{self, AeadError, KEY_SIZE, NONCE_SIZE, TAG_SIZE};
+
+// Secrets in the RTC EEPROM are sealed with ChaCha20-Poly1305 under a key derived from the
+// console PIN (see `pin::PinConfig::unlock`). Each record's seal is kept in its own page:
+// 0x00 => sealed marker: u8 (anything else means the record is plain text)
+// 0x08..0x10 => nonce counter: u64
+// 0x10..0x20 => tag: [u8; 16]
+// The nonce is the record's id followed by the nonce counter, which is persisted and only
+// ever moves forwards, so no nonce is used twice under the same key.
+pub const SEAL_PAGE_SIZE: usize = 32;
+
+const SEALED: u8 = 0xa5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Record {
+    Key,
+    ChallengeResponse,
+    Slot(usize),
+}
+
+impl Record {
+    pub fn id(&self) -> u32 {
+        match self {
+            Record::Key => 0x000,
+            Record::Slot(index) => 0x100 + *index as u32,
+            Record::ChallengeResponse => 0x200,
+        }
+    }
+
+    pub fn nonce(&self, counter: u64) -> [u8; NONCE_SIZE] {
+        let mut nonce = [0_u8; NONCE_SIZE];
+        nonce[0..4].copy_from_slice(&byte_helper::u32_to_bytes(self.id()));
+        nonce[4..12].copy_from_slice(&byte_helper::u64_to_bytes(counter));
+        nonce
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Seal {
+    pub counter: u64,
+    pub tag: [u8; TAG_SIZE],
+}
+
+impl Seal {
+    // Return: None for a record stored as plain text
+    pub fn from_page(page: &[u8; SEAL_PAGE_SIZE]) -> Option<Self> {
+        if page[0] != SEALED {
+            return None;
+        }
+
+        let mut counter_bytes = [0_u8; 8];
+        counter_bytes.copy_from_slice(&page[0x08..0x10]);
+        let mut tag = [0_u8; TAG_SIZE];
+        tag.copy_from_slice(&page[0x10..0x20]);
+
+        Some(Seal { counter: byte_helper::bytes_to_u64(counter_bytes), tag })
+    }
+
+    pub fn to_page(&self) -> [u8; SEAL_PAGE_SIZE] {
+        let mut page = [0xff_u8; SEAL_PAGE_SIZE];
+        page[0] = SEALED;
+        page[0x08..0x10].copy_from_slice(&byte_helper::u64_to_bytes(self.counter));
+        page[0x10..0x20].copy_from_slice(&self.tag);
+
+        page
+    }
+}
+
+// Encrypt a record in place. `associated_data` is authenticated but left as it is.
+pub fn seal(key: &[u8; KEY_SIZE], record: Record, counter: u64, associated_data: &[u8], data: &mut [u8]) -> Seal {
+    let tag = chacha20poly1305::seal(key, &record.nonce(counter), associated_data, data);
+    Seal { counter, tag }
+}
+
+// Decrypt a record in place
+// Return: an error if the record, its associated data or its seal were changed,
+// or it belongs to another record
+pub fn open(key: &[u8; KEY_SIZE], record: Record, seal: &Seal, associated_data: &[u8], data: &mut [u8]) -> Result<(), AeadError> {
+    chacha20poly1305::open(key, &record.nonce(seal.counter), associated_data, data, &seal.tag)
+}
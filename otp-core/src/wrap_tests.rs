@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod sealed_records {
+    use crate::otp::Algorithm;
+    use crate::slot::Slot;
+    use crate::wrap::{self, Record, Seal};
+
+    const KEY: [u8; 32] = [0x42; 32];
+
+    #[test]
+    fn round_trip() {
+        let mut secret = *b"12345678901234567890";
+        let seal = wrap::seal(&KEY, Record::Key, 7, &[20], &mut secret);
+        assert_ne!(&secret, b"12345678901234567890");
+
+        wrap::open(&KEY, Record::Key, &seal, &[20], &mut secret).unwrap();
+        assert_eq!(&secret, b"12345678901234567890");
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let mut sealed = *b"12345678901234567890";
+        let seal = wrap::seal(&KEY, Record::Key, 7, &[20], &mut sealed);
+
+        let mut secret = sealed;
+        secret[0] ^= 0x01;
+        assert!(wrap::open(&KEY, Record::Key, &seal, &[20], &mut secret).is_err());
+
+        // The associated data, e.g. the key length, is covered too
+        let mut secret = sealed;
+        assert!(wrap::open(&KEY, Record::Key, &seal, &[10], &mut secret).is_err());
+
+        let mut secret = sealed;
+        let mut forged = seal;
+        forged.tag[15] ^= 0x01;
+        assert!(wrap::open(&KEY, Record::Key, &forged, &[20], &mut secret).is_err());
+
+        let mut secret = sealed;
+        assert!(wrap::open(&[0x24; 32], Record::Key, &seal, &[20], &mut secret).is_err());
+    }
+
+    #[test]
+    fn records_cannot_be_swapped() {
+        let mut secret = *b"12345678901234567890";
+        let seal = wrap::seal(&KEY, Record::Slot(1), 7, &[20], &mut secret);
+
+        assert!(wrap::open(&KEY, Record::Slot(2), &seal, &[20], &mut secret.clone()).is_err());
+        assert!(wrap::open(&KEY, Record::ChallengeResponse, &seal, &[20], &mut secret.clone()).is_err());
+        assert!(wrap::open(&KEY, Record::Slot(1), &seal, &[20], &mut secret).is_ok());
+    }
+
+    #[test]
+    fn nonces_are_unique_per_record_and_counter() {
+        assert_ne!(Record::Key.nonce(1), Record::Key.nonce(2));
+        assert_ne!(Record::Key.nonce(1), Record::Slot(0).nonce(1));
+        assert_ne!(Record::Slot(0).nonce(1), Record::Slot(1).nonce(1));
+        assert_ne!(Record::Slot(0).nonce(1), Record::ChallengeResponse.nonce(1));
+    }
+
+    #[test]
+    fn seal_page_round_trip() {
+        let seal = Seal { counter: 0x0102_0304, tag: [0x5a; 16] };
+        let decoded = Seal::from_page(&seal.to_page()).unwrap();
+        assert_eq!(decoded.counter, 0x0102_0304);
+        assert_eq!(decoded.tag, [0x5a; 16]);
+
+        // Erased pages mean plain text
+        assert!(Seal::from_page(&[0xff; 32]).is_none());
+    }
+
+    #[test]
+    fn slot_header_is_authenticated() {
+        let mut slot = Slot::new(b"mail", b"Example", Algorithm::Sha1, 6, 30, 0, b"12345678901234567890").unwrap();
+        let header = slot.header();
+        let seal = wrap::seal(&KEY, Record::Slot(3), 1, &header, &mut slot.secret[0..slot.secret_length]);
+
+        // Stored and read back, then opened
+        let mut stored = Slot::from_bytes(&slot.to_bytes()).unwrap();
+        let header = stored.header();
+        wrap::open(&KEY, Record::Slot(3), &seal, &header, &mut stored.secret[0..stored.secret_length]).unwrap();
+        assert_eq!(stored.secret(), b"12345678901234567890");
+
+        // A changed algorithm fails to open
+        let mut changed = Slot::from_bytes(&slot.to_bytes()).unwrap();
+        changed.algorithm = Algorithm::Sha256;
+        let header = changed.header();
+        assert!(wrap::open(&KEY, Record::Slot(3), &seal, &header, &mut changed.secret[0..changed.secret_length]).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+// Copying the whole EEPROM to and from the host with the console's `readp` and `write` commands
+use std::io::Write;
+
+use otp_core::eeprom::PAGE_SIZE;
+
+use crate::device::{Device, DeviceError};
+
+// The AT24C32 on the RTC module
+pub const EEPROM_SIZE: usize = 4096;
+
+pub fn read_image<W: Write>(device: &mut Device<W>) -> Result<[u8; EEPROM_SIZE], DeviceError> {
+    let mut image = [0_u8; EEPROM_SIZE];
+    for (index, page) in image.chunks_mut(PAGE_SIZE).enumerate() {
+        page.copy_from_slice(&read_page(device, index * PAGE_SIZE)?);
+    }
+
+    Ok(image)
+}
+
+// Write an image back a byte at a time, skipping the bytes that already match, then read the
+// changed pages back to check them
+// Return: the number of bytes written
+pub fn write_image<W: Write>(device: &mut Device<W>, image: &[u8; EEPROM_SIZE]) -> Result<usize, DeviceError> {
+    let current = read_image(device)?;
+    let mut written = 0;
+    for (address, (old, new)) in current.iter().zip(image.iter()).enumerate() {
+        if old != new {
+            let response = device.command(&format!("write {:04x} {:02x}", address, new))?;
+            if !response.is_empty() {
+                return Err(DeviceError::Response(response));
+            }
+            written += 1;
+        }
+    }
+
+    for (index, (old, new)) in current.chunks(PAGE_SIZE).zip(image.chunks(PAGE_SIZE)).enumerate() {
+        if old != new && read_page(device, index * PAGE_SIZE)? != new {
+            return Err(DeviceError::Response(format!("Page {:04x} did not read back as written", index * PAGE_SIZE)));
+        }
+    }
+
+    Ok(written)
+}
+
+pub fn read_page<W: Write>(device: &mut Device<W>, address: usize) -> Result<[u8; PAGE_SIZE], DeviceError> {
+    let response = device.command(&format!("readp {:04x}", address))?;
+    parse_page(&response).ok_or(DeviceError::Response(response))
+}
+
+// Parse the console's "Page: [1, 2, ...]" output
+pub fn parse_page(response: &str) -> Option<[u8; PAGE_SIZE]> {
+    let bytes = response.strip_prefix("Page: [")?.strip_suffix(']')?;
+    let mut page = [0_u8; PAGE_SIZE];
+    let mut values = bytes.split(", ");
+    for byte in page.iter_mut() {
+        *byte = values.next()?.parse().ok()?;
+    }
+
+    match values.next() {
+        None => Some(page),
+        Some(_) => None,
+    }
+}
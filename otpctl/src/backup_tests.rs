@@ -0,0 +1,22 @@
+#[cfg(test)]
+mod parse_page {
+    use crate::backup::parse_page;
+
+    #[test]
+    fn console_output() {
+        let mut expected = [0_u8; 32];
+        expected[0..10].copy_from_slice(b"Hello!\xde\xad\xbe\xef");
+        let response = "Page: [72, 101, 108, 108, 111, 33, 222, 173, 190, 239, 0, 0, 0, 0, 0, 0, \
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]";
+
+        assert_eq!(parse_page(response), Some(expected));
+    }
+
+    #[test]
+    fn wrong_length_or_values() {
+        assert_eq!(parse_page("Page: [1, 2, 3]"), None);
+        assert_eq!(parse_page(&format!("Page: [{}]", ["0"; 33].join(", "))), None);
+        assert_eq!(parse_page(&format!("Page: [{}]", ["256"; 32].join(", "))), None);
+        assert_eq!(parse_page("Error reading from RTC EEPROM - InvalidAddress"), None);
+    }
+}
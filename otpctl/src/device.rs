@@ -0,0 +1,237 @@
+// The firmware's console, spoken over a serial port. Every command is one line of text, which
+// the console echoes back before printing its response and a fresh "$ " prompt.
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+const PROMPT: &[u8] = b"\n$ ";
+const LOCKED: &str = "Locked - use unlock <PIN>";
+// The console's line buffer
+const MAX_LINE_LENGTH: usize = 256;
+// Opening the port resets the Arduino, which takes a couple of seconds to boot
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// How long the console may go quiet in the middle of a response
+pub const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+// How long the console has to stay quiet after a prompt before it is taken as ready
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+#[derive(Debug)]
+pub enum DeviceError {
+    Io(io::Error),
+    // The console stopped answering before printing a prompt
+    Timeout,
+    // The console is locked, and needs to be unlocked with the PIN first
+    Locked,
+    // The command does not fit in the console's line buffer, or is not printable ASCII
+    InvalidCommand(String),
+    // The console printed an error, or something other than what was expected
+    Response(String),
+}
+
+impl fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceError::Io(e) => write!(f, "serial port error - {}", e),
+            DeviceError::Timeout => write!(f, "no response from the device"),
+            DeviceError::Locked => write!(f, "the device is locked - use --pin"),
+            DeviceError::InvalidCommand(line) => write!(f, "command cannot be sent to the device - {}", line),
+            DeviceError::Response(response) => write!(f, "{}", response),
+        }
+    }
+}
+
+impl From<io::Error> for DeviceError {
+    fn from(error: io::Error) -> Self {
+        DeviceError::Io(error)
+    }
+}
+
+pub struct Device<W> {
+    output: W,
+    input: Receiver<io::Result<Vec<u8>>>,
+    // Received bytes that are not part of a complete response yet
+    pending: Vec<u8>,
+}
+
+impl Device<File> {
+    // Open a serial port at 9600 baud, e.g. /dev/ttyACM0 for the Arduino Uno
+    pub fn open(path: &str) -> Result<Self, DeviceError> {
+        configure(path)?;
+        let port = OpenOptions::new().read(true).write(true).open(path)?;
+        let input = port.try_clone()?;
+
+        Device::connect(input, port)
+    }
+}
+
+impl<W: Write> Device<W> {
+    // Start talking to a console on any pair of streams, and wait for its prompt
+    pub fn connect<R: Read + Send + 'static>(mut input: R, output: W) -> Result<Self, DeviceError> {
+        // Reads block, so they run on their own thread and time out on this one
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buffer = [0_u8; 256];
+            loop {
+                let result = match input.read(&mut buffer) {
+                    Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+                    Ok(length) => Ok(buffer[0..length].to_vec()),
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => Err(e),
+                };
+                let failed = result.is_err();
+                if sender.send(result).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        let mut device = Self { output, input: receiver, pending: Vec::new() };
+
+        // A fresh prompt, in case the banner has already gone by
+        device.output.write_all(b"\n")?;
+        device.output.flush()?;
+        device.read_prompt(CONNECT_TIMEOUT)?;
+        loop {
+            match device.receive(SETTLE_TIME) {
+                Ok(()) => device.read_prompt(RESPONSE_TIMEOUT)?,
+                Err(DeviceError::Timeout) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        device.pending.clear();
+
+        Ok(device)
+    }
+
+    // Run a console command
+    // Return: the response, without the echoed command or the prompt
+    pub fn command(&mut self, line: &str) -> Result<String, DeviceError> {
+        self.command_with_timeout(line, RESPONSE_TIMEOUT)
+    }
+
+    pub fn command_with_timeout(&mut self, line: &str, timeout: Duration) -> Result<String, DeviceError> {
+        self.send_line(line)?;
+        self.read_prompt(timeout)?;
+
+        self.take_response(line)
+    }
+
+    // Run a console command that asks the host something partway through. `answer` is given
+    // the rest of the first line starting with `request`, and what it returns is sent back.
+    // Return: the response after the request, without the prompt
+    pub fn converse<F: FnOnce(&str) -> String>(&mut self, line: &str, request: &str, answer: F) -> Result<String, DeviceError> {
+        self.send_line(line)?;
+
+        let (question, end) = loop {
+            if let Some(found) = find_request(&self.pending, request) {
+                break found;
+            }
+            // The command finished without asking, most likely with an error
+            if self.pending.ends_with(PROMPT) {
+                return self.take_response(line).and_then(|response| Err(DeviceError::Response(response)));
+            }
+            self.receive(RESPONSE_TIMEOUT)?;
+        };
+
+        // The answer is not echoed, so everything after the request is the response
+        self.pending.drain(0..end);
+        let answer = answer(&question);
+        self.output.write_all(answer.as_bytes())?;
+        self.output.write_all(b"\n")?;
+        self.output.flush()?;
+        self.read_prompt(RESPONSE_TIMEOUT)?;
+
+        self.take_response("")
+    }
+
+    fn send_line(&mut self, line: &str) -> Result<(), DeviceError> {
+        if line.len() >= MAX_LINE_LENGTH || !line.bytes().all(|byte| (b' '..=b'~').contains(&byte)) {
+            return Err(DeviceError::InvalidCommand(line.to_string()));
+        }
+
+        self.output.write_all(line.as_bytes())?;
+        self.output.write_all(b"\n")?;
+        self.output.flush()?;
+
+        Ok(())
+    }
+
+    // Take everything received up to the prompt, without `echo` at the start
+    fn take_response(&mut self, echo: &str) -> Result<String, DeviceError> {
+        let received = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+
+        let response = received.strip_prefix(echo).unwrap_or(&received);
+        let response = response.strip_suffix("\n$ ").unwrap_or(response).trim_matches('\n').to_string();
+        if response == LOCKED {
+            Err(DeviceError::Locked)
+        }
+        else if response.starts_with("Error") {
+            Err(DeviceError::Response(response))
+        }
+        else {
+            Ok(response)
+        }
+    }
+
+    // Wait until the console prints a prompt, giving up if it goes quiet for `timeout`
+    fn read_prompt(&mut self, timeout: Duration) -> Result<(), DeviceError> {
+        while !self.pending.ends_with(PROMPT) {
+            self.receive(timeout)?;
+        }
+
+        Ok(())
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<(), DeviceError> {
+        match self.input.recv_timeout(timeout) {
+            Ok(Ok(bytes)) => {
+                self.pending.extend_from_slice(&bytes);
+                Ok(())
+            },
+            Ok(Err(e)) => Err(DeviceError::Io(e)),
+            Err(RecvTimeoutError::Timeout) => Err(DeviceError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Err(DeviceError::Io(io::ErrorKind::UnexpectedEof.into())),
+        }
+    }
+}
+
+// Find a complete line starting with `request`
+// Return: (the rest of that line, the index just past it)
+fn find_request(received: &[u8], request: &str) -> Option<(String, usize)> {
+    let mut start = 0;
+    for (i, byte) in received.iter().enumerate() {
+        if *byte == b'\n' {
+            if let Some(rest) = received[start..i].strip_prefix(request.as_bytes()) {
+                return Some((String::from_utf8_lossy(rest).into_owned(), i + 1));
+            }
+            start = i + 1;
+        }
+    }
+
+    None
+}
+
+// Set the port to 9600 baud 8N1, with no translation of the bytes in either direction
+#[cfg(unix)]
+fn configure(path: &str) -> Result<(), DeviceError> {
+    use std::process::Command;
+
+    let status = Command::new("stty")
+        .args(["9600", "cs8", "-cstopb", "-parenb", "raw", "-echo"])
+        .stdin(File::open(path)?)
+        .status()?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(DeviceError::Io(io::Error::new(io::ErrorKind::Other, format!("stty could not configure {}", path)))),
+    }
+}
+
+// Windows COM ports are set up with `mode` beforehand
+#[cfg(not(unix))]
+fn configure(_: &str) -> Result<(), DeviceError> {
+    Ok(())
+}
@@ -0,0 +1,159 @@
+// otpctl against the emulated console, connected through a pseudo-terminal like a real serial port
+#[cfg(all(test, target_os = "linux"))]
+mod emulator {
+    use std::cell::Cell;
+    use std::ffi::CStr;
+    use std::fs::File;
+    use std::os::raw::{c_char, c_int};
+    use std::os::unix::io::FromRawFd;
+    use std::thread;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use otp_core::sim::{Sim, EEPROM_SIZE};
+    use otp_core::tty::TTY;
+    use otp_emulator::console::Console;
+    use otp_emulator::module::Module;
+
+    use crate::backup;
+    use crate::device::{Device, DeviceError};
+    use crate::options::{Action, Options};
+    use crate::provision;
+    use crate::sync;
+
+    const O_RDWR: c_int = 0o2;
+    const O_NOCTTY: c_int = 0o400;
+
+    extern "C" {
+        fn posix_openpt(flags: c_int) -> c_int;
+        fn grantpt(fd: c_int) -> c_int;
+        fn unlockpt(fd: c_int) -> c_int;
+        fn ptsname_r(fd: c_int, buffer: *mut c_char, length: usize) -> c_int;
+    }
+
+    const URI: &str = "otpauth://totp/Example:alice@google.com?secret=JBSWY3DPEHPK3PXP&issuer=Example";
+
+    // The emulated console on the master side of a new pseudo-terminal
+    // Return: the terminal's path, and the terminal held open so the console never sees a hangup
+    fn start_emulator(image: [u8; EEPROM_SIZE]) -> (String, File) {
+        let (master, path) = unsafe {
+            let master = posix_openpt(O_RDWR | O_NOCTTY);
+            assert!(master >= 0 && grantpt(master) == 0 && unlockpt(master) == 0);
+            let mut name = [0 as c_char; 64];
+            assert_eq!(ptsname_r(master, name.as_mut_ptr(), name.len()), 0);
+            (File::from_raw_fd(master), CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned())
+        };
+        let terminal = File::open(&path).unwrap();
+
+        thread::spawn(move || {
+            let sim = Sim::new();
+            sim.load_eeprom(&image);
+            let hangup = Cell::new(false);
+            let console = Console::new(master.try_clone().unwrap(), master, &hangup);
+            let pending = console.pending();
+            let mut tty = TTY::new(console, Module::new(&sim));
+            while !hangup.get() {
+                pending.wait();
+                tty.wait_for_byte();
+            }
+        });
+
+        (path, terminal)
+    }
+
+    fn run(device: &mut Device<File>, action: Action) -> Result<(), String> {
+        crate::run(device, &Options { port: String::new(), pin: None, action })
+    }
+
+    #[test]
+    fn connects_after_the_banner() {
+        let (path, _terminal) = start_emulator([0xff; EEPROM_SIZE]);
+        let mut device = Device::open(&path).unwrap();
+
+        assert_eq!(device.command("digit").unwrap(), "6");
+        assert!(matches!(device.command("uri otpauth://totp/caf\u{e9}"), Err(DeviceError::InvalidCommand(_))));
+    }
+
+    #[test]
+    fn sync_sets_the_clock() {
+        let (path, _terminal) = start_emulator([0xff; EEPROM_SIZE]);
+        let mut device = Device::open(&path).unwrap();
+
+        let response = sync::sync(&mut device, None).unwrap();
+        assert!(response.starts_with("Round trip: "), "{}", response);
+        assert!(response.contains("\nCorrection: +"), "{}", response);
+
+        // The clock was set on a second boundary, so it agrees with the host's to the second
+        let response = device.command("time").unwrap();
+        let timestamp: u64 = response.lines().nth(1).unwrap().strip_prefix("Timestamp: ").unwrap().parse().unwrap();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert!(now - timestamp <= 1);
+        run(&mut device, Action::Sync).unwrap();
+    }
+
+    #[test]
+    fn large_changes_need_the_pin_with_sync() {
+        let (path, _terminal) = start_emulator([0xff; EEPROM_SIZE]);
+        let mut device = Device::open(&path).unwrap();
+        device.command("pin set 12345678").unwrap();
+
+        // The clock starts at the DS3231's power-on time, a century behind
+        match sync::sync(&mut device, None) {
+            Err(DeviceError::Response(response)) => assert!(response.contains("large changes need the PIN"), "{}", response),
+            result => panic!("{:?}", result),
+        }
+        assert!(matches!(sync::sync(&mut device, Some("87654321")), Err(DeviceError::Response(_))));
+
+        sync::sync(&mut device, Some("12345678")).unwrap();
+        assert!(device.command("timelog").unwrap().lines().any(|line| line.ends_with(" sync, PIN")));
+    }
+
+    #[test]
+    fn provision_list_and_code() {
+        let (path, _terminal) = start_emulator([0xff; EEPROM_SIZE]);
+        let mut device = Device::open(&path).unwrap();
+
+        assert_eq!(provision::provision(&mut device, URI, "alice@google.com").unwrap(), 0);
+        assert_eq!(
+            device.command("list").unwrap(),
+            "*0: alice@google.com (Example) - SHA1, 6 digits, period of 30\n1 of 12 slots in use"
+        );
+        assert!(matches!(provision::provision(&mut device, URI, "alice@google.com"), Err(DeviceError::Response(_))));
+        // The emulated DS3231 has just powered up, so there are no codes until the clock is set
+        assert!(run(&mut device, Action::Code { slot: None }).unwrap_err().contains("the RTC lost time"));
+        run(&mut device, Action::Sync).unwrap();
+        run(&mut device, Action::Code { slot: Some("alice@google.com".to_string()) }).unwrap();
+        assert!(run(&mut device, Action::Code { slot: Some("bob".to_string()) }).is_err());
+    }
+
+    #[test]
+    fn backup_and_restore() {
+        let (path, _terminal) = start_emulator([0xff; EEPROM_SIZE]);
+        let mut device = Device::open(&path).unwrap();
+        provision::provision(&mut device, URI, "alice").unwrap();
+
+        let image = backup::read_image(&mut device).unwrap();
+        device.command("delete alice").unwrap();
+        assert!(backup::write_image(&mut device, &image).unwrap() > 0);
+        assert_eq!(backup::read_image(&mut device).unwrap(), image);
+        assert!(device.command("list").unwrap().contains("0: alice (Example)"));
+
+        // The image works in another module too
+        let (path, _terminal) = start_emulator(image);
+        let mut device = Device::open(&path).unwrap();
+        assert!(device.command("list").unwrap().contains("0: alice (Example)"));
+    }
+
+    #[test]
+    fn locked_console() {
+        let (path, _terminal) = start_emulator([0xff; EEPROM_SIZE]);
+        let mut device = Device::open(&path).unwrap();
+        device.command("pin set 12345678").unwrap();
+        device.command("lock").unwrap();
+
+        assert!(matches!(device.command("list"), Err(DeviceError::Locked)));
+        assert!(matches!(sync::sync(&mut device, None), Err(DeviceError::Locked)));
+        assert!(crate::unlock(&mut device, "87654321").is_err());
+        crate::unlock(&mut device, "12345678").unwrap();
+        assert!(device.command("list").is_ok());
+    }
+}
@@ -0,0 +1,112 @@
+// Host companion for the Arduino OTP generator. Drives the firmware's serial console to set the
+// clock, provision keys, fetch codes, and back up the EEPROM.
+use std::fs;
+use std::io::Write;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use otp_core::pin::MAX_LOCKOUT_SECONDS;
+
+mod backup;
+mod device;
+mod options;
+mod provision;
+mod sync;
+
+mod backup_tests;
+mod device_tests;
+mod options_tests;
+mod provision_tests;
+
+use device::{Device, DeviceError, RESPONSE_TIMEOUT};
+use options::{Action, Options};
+
+fn main() -> ExitCode {
+    let options = match Options::from_env() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, options::USAGE);
+            return ExitCode::from(2);
+        },
+    };
+
+    let result = Device::open(&options.port)
+        .map_err(|e| format!("Error opening {} - {}", options.port, e))
+        .and_then(|mut device| run(&mut device, &options));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run<W: Write>(device: &mut Device<W>, options: &Options) -> Result<(), String> {
+    if let Some(pin) = &options.pin {
+        unlock(device, pin).map_err(|e| format!("Error unlocking - {}", e))?;
+    }
+
+    match &options.action {
+        Action::Sync => {
+            let response = sync::sync(device, options.pin.as_deref()).map_err(|e| format!("Error syncing the clock - {}", e))?;
+            println!("{}", response);
+        },
+        Action::Provision { label, sources } => {
+            let uris = provision::read_uris(sources)?;
+            if label.is_some() && uris.len() != 1 {
+                return Err("--label can only be used with a single URI".to_string());
+            }
+
+            for uri in uris {
+                let label = match label {
+                    Some(label) => label.clone(),
+                    None => provision::default_label(&uri)?,
+                };
+                let index = provision::provision(device, &uri, &label)
+                    .map_err(|e| format!("Error provisioning {} - {}", label, e))?;
+                println!("Added {} to slot {}", label, index);
+            }
+        },
+        Action::List => {
+            println!("{}", device.command("list").map_err(|e| e.to_string())?);
+        },
+        Action::Code { slot } => {
+            let line = match slot {
+                Some(slot) => format!("totp {}", slot),
+                None => "totp".to_string(),
+            };
+            let response = device.command(&line).map_err(|e| e.to_string())?;
+            // The code follows the timestamp, counter and time remaining
+            match response.lines().collect::<Vec<_>>()[..] {
+                [timestamp, _, _, code] if timestamp.starts_with("Timestamp:") => println!("{}", code),
+                _ => return Err(response),
+            }
+        },
+        Action::Backup { file } => {
+            let image = backup::read_image(device).map_err(|e| format!("Error reading the EEPROM - {}", e))?;
+            fs::write(file, image).map_err(|e| format!("Error writing {} - {}", file, e))?;
+            println!("Saved {} bytes to {}", image.len(), file);
+        },
+        Action::Restore { file } => {
+            let image = fs::read(file).map_err(|e| format!("Error reading {} - {}", file, e))?;
+            let image: [u8; backup::EEPROM_SIZE] = image.try_into().map_err(|image: Vec<u8>| {
+                format!("{} is {} bytes, expected {}", file, image.len(), backup::EEPROM_SIZE)
+            })?;
+            let written = backup::write_image(device, &image).map_err(|e| format!("Error writing the EEPROM - {}", e))?;
+            println!("Restored {} bytes from {}, {} of them changed", image.len(), file, written);
+        },
+    }
+
+    Ok(())
+}
+
+// Failed attempts make the console wait before checking the PIN, for up to an hour
+fn unlock<W: Write>(device: &mut Device<W>, pin: &str) -> Result<(), DeviceError> {
+    let timeout = RESPONSE_TIMEOUT + Duration::from_secs(MAX_LOCKOUT_SECONDS as u64);
+    let response = device.command_with_timeout(&format!("unlock {}", pin), timeout)?;
+    match response.lines().any(|line| line == "Unlocked" || line == "No PIN set") {
+        true => Ok(()),
+        false => Err(DeviceError::Response(response)),
+    }
+}
@@ -0,0 +1,84 @@
+// Command line options for otpctl
+use std::env;
+
+pub const USAGE: &str = "\
+Usage: otpctl [--port <device>] [--pin <PIN>] <command>
+
+Commands:
+  sync                              Set the device clock from the host clock.
+  provision [--label <label>] <URI|file>...
+                                    Add otpauth:// URIs, or files with one URI per line, to free slots.
+                                    Slots are labelled with the account name unless --label is given.
+  list                              List the slots in use.
+  code [slot]                       Show the TOTP code for a slot, by number or label, or for the loaded key.
+  backup <file>                     Save the EEPROM to an image file.
+  restore <file>                    Write an image file back to the EEPROM.
+
+The port defaults to $OTPCTL_PORT, or /dev/ttyACM0. The PIN can also be given in $OTPCTL_PIN.";
+
+const DEFAULT_PORT: &str = "/dev/ttyACM0";
+
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Sync,
+    Provision { label: Option<String>, sources: Vec<String> },
+    List,
+    Code { slot: Option<String> },
+    Backup { file: String },
+    Restore { file: String },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub port: String,
+    pub pin: Option<String>,
+    pub action: Action,
+}
+
+impl Options {
+    // Parse the arguments, falling back on the environment for the port and PIN
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(env::args().skip(1), env::var("OTPCTL_PORT").ok(), env::var("OTPCTL_PIN").ok())
+    }
+
+    pub fn parse<I: Iterator<Item = String>>(args: I, port: Option<String>, pin: Option<String>) -> Result<Self, String> {
+        let mut args = args.peekable();
+        let mut port = port.unwrap_or_else(|| DEFAULT_PORT.to_string());
+        let mut pin = pin;
+        while let Some(option) = args.next_if(|arg| arg.starts_with("--")) {
+            match option.as_str() {
+                "--port" => port = args.next().ok_or("--port needs a device")?,
+                "--pin" => pin = Some(args.next().ok_or("--pin needs a PIN")?),
+                _ => return Err(format!("Unknown option {}", option)),
+            }
+        }
+
+        let command = args.next().ok_or("A command is required")?;
+        let mut rest: Vec<String> = args.collect();
+        let action = match (command.as_str(), rest.len()) {
+            ("sync", 0) => Action::Sync,
+            ("list", 0) => Action::List,
+            ("code", 0 | 1) => Action::Code { slot: rest.pop() },
+            ("backup", 1) => Action::Backup { file: rest.remove(0) },
+            ("restore", 1) => Action::Restore { file: rest.remove(0) },
+            ("provision", _) => {
+                let label = match rest.first().map(String::as_str) {
+                    Some("--label") if rest.len() >= 2 => Some(rest.drain(0..2).nth(1).unwrap_or_default()),
+                    Some("--label") => return Err("--label needs a label".to_string()),
+                    _ => None,
+                };
+                match (rest.len(), &label) {
+                    (0, _) => return Err("provision needs a URI or file".to_string()),
+                    (1, _) | (_, None) => Action::Provision { label, sources: rest },
+                    _ => return Err("--label can only be used with a single URI".to_string()),
+                }
+            },
+            ("sync" | "list" | "code" | "backup" | "restore", _) => {
+                return Err(format!("Wrong number of arguments for {}", command));
+            },
+            _ => return Err(format!("Unknown command {}", command)),
+        };
+
+        Ok(Options { port, pin, action })
+    }
+}
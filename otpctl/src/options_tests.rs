@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod parse {
+    use crate::options::{Action, Options};
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()), None, None)
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(parse(&["sync"]).unwrap().action, Action::Sync);
+        assert_eq!(parse(&["list"]).unwrap().action, Action::List);
+        assert_eq!(parse(&["code"]).unwrap().action, Action::Code { slot: None });
+        assert_eq!(parse(&["code", "mail"]).unwrap().action, Action::Code { slot: Some("mail".to_string()) });
+        assert_eq!(parse(&["backup", "module.bin"]).unwrap().action, Action::Backup { file: "module.bin".to_string() });
+        assert_eq!(parse(&["restore", "module.bin"]).unwrap().action, Action::Restore { file: "module.bin".to_string() });
+        assert_eq!(
+            parse(&["provision", "keys.txt", "otpauth://totp/a?secret=AA"]).unwrap().action,
+            Action::Provision { label: None, sources: vec!["keys.txt".to_string(), "otpauth://totp/a?secret=AA".to_string()] }
+        );
+        assert_eq!(
+            parse(&["provision", "--label", "mail", "otpauth://totp/a?secret=AA"]).unwrap().action,
+            Action::Provision { label: Some("mail".to_string()), sources: vec!["otpauth://totp/a?secret=AA".to_string()] }
+        );
+    }
+
+    #[test]
+    fn port_and_pin() {
+        let options = parse(&["list"]).unwrap();
+        assert_eq!((options.port.as_str(), options.pin), ("/dev/ttyACM0", None));
+
+        let options = Options::parse(["list".to_string()].into_iter(), Some("/dev/pts/3".to_string()), Some("1234".to_string())).unwrap();
+        assert_eq!((options.port.as_str(), options.pin.as_deref()), ("/dev/pts/3", Some("1234")));
+
+        let options = parse(&["--port", "/dev/ttyUSB0", "--pin", "9876", "list"]).unwrap();
+        assert_eq!((options.port.as_str(), options.pin.as_deref()), ("/dev/ttyUSB0", Some("9876")));
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["--port"]).is_err());
+        assert!(parse(&["--baud", "9600", "list"]).is_err());
+        assert!(parse(&["erase"]).is_err());
+        assert!(parse(&["sync", "now"]).is_err());
+        assert!(parse(&["backup"]).is_err());
+        assert!(parse(&["provision"]).is_err());
+        assert!(parse(&["provision", "--label"]).is_err());
+        assert!(parse(&["provision", "--label", "mail", "a.txt", "b.txt"]).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+// Adding keys to free slots from otpauth:// URIs
+use std::fs;
+use std::io::Write;
+
+use otp_core::otpauth;
+use otp_core::slot::SLOT_LABEL_SIZE;
+
+use crate::device::{Device, DeviceError};
+
+const SCHEME: &str = "otpauth://";
+
+// Return: the URIs in `sources`, each of which is either a URI or a file with one URI per line.
+// Blank lines and lines starting with # are skipped.
+pub fn read_uris(sources: &[String]) -> Result<Vec<String>, String> {
+    let mut uris = Vec::new();
+    for source in sources {
+        if source.starts_with(SCHEME) {
+            uris.push(source.clone());
+            continue;
+        }
+
+        let contents = fs::read_to_string(source).map_err(|e| format!("Error reading {} - {}", source, e))?;
+        uris.extend(contents.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string));
+    }
+
+    Ok(uris)
+}
+
+// Return: the slot label for a URI, which is its account name
+pub fn default_label(uri: &str) -> Result<String, String> {
+    let (mut issuer, mut account, mut secret) = ([0_u8; 256], [0_u8; 256], [0_u8; 256]);
+    let parsed = otpauth::parse(uri.as_bytes(), &mut issuer, &mut account, &mut secret).map_err(|e| format!("Error parsing {} - {:?}", uri, e))?;
+    let label = String::from_utf8_lossy(parsed.account).into_owned();
+    match label.len() {
+        0 => Err(format!("{} has no account name, use --label", uri)),
+        length if length > SLOT_LABEL_SIZE => Err(format!("Account name {} is longer than {} bytes, use --label", label, SLOT_LABEL_SIZE)),
+        _ => Ok(label),
+    }
+}
+
+// Load a URI's key and settings, then save them to a free slot
+// Return: the slot index
+pub fn provision<W: Write>(device: &mut Device<W>, uri: &str, label: &str) -> Result<usize, DeviceError> {
+    let response = device.command(&format!("uri {}", uri))?;
+    if !response.starts_with("Loaded") {
+        return Err(DeviceError::Response(response));
+    }
+
+    let response = device.command(&format!("add {}", label))?;
+    response.strip_prefix("Added slot ")
+        .and_then(|index| index.parse().ok())
+        .ok_or(DeviceError::Response(response))
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod labels {
+    use crate::provision::default_label;
+
+    #[test]
+    fn account_name() {
+        assert_eq!(default_label("otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&issuer=Example"), Ok("alice".to_string()));
+        assert_eq!(default_label("otpauth://hotp/bob%20smith?secret=JBSWY3DPEHPK3PXP&counter=3"), Ok("bob smith".to_string()));
+    }
+
+    #[test]
+    fn unusable_names() {
+        assert!(default_label("otpauth://totp/Example:alice.smith@example.com?secret=JBSWY3DPEHPK3PXP").is_err());
+        assert!(default_label("otpauth://totp/?secret=JBSWY3DPEHPK3PXP").is_err());
+        assert!(default_label("otpauth://totp/alice").is_err());
+    }
+}
+
+#[cfg(test)]
+mod sources {
+    extern crate std;
+    use std::env;
+    use std::fs;
+    use crate::provision::read_uris;
+
+    #[test]
+    fn uris_and_files() {
+        let path = env::temp_dir().join(format!("otpctl-uris-{}.txt", std::process::id()));
+        fs::write(&path, "# Exported keys\notpauth://totp/a?secret=AA\n\n  otpauth://totp/b?secret=BB  \n").unwrap();
+
+        let sources = ["otpauth://totp/c?secret=CC".to_string(), path.to_string_lossy().into_owned()];
+        let uris = read_uris(&sources);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(uris.unwrap(), ["otpauth://totp/c?secret=CC", "otpauth://totp/a?secret=AA", "otpauth://totp/b?secret=BB"]);
+        assert!(read_uris(&["/nonexistent/uris.txt".to_string()]).is_err());
+    }
+}
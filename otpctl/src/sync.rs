@@ -0,0 +1,23 @@
+// Setting the device clock with the firmware's `sync` handshake. The console prints a nonce,
+// and the host answers with it and the time in milliseconds, stamped as late as possible.
+// Given the PIN, the console lets it move the clock by more than drift.
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::device::{Device, DeviceError};
+
+const REQUEST: &str = "Sync ";
+
+// Return: the console's report of the round trip, the correction and the new time
+pub fn sync<W: Write>(device: &mut Device<W>, entered_pin: Option<&str>) -> Result<String, DeviceError> {
+    let line = match entered_pin {
+        Some(entered_pin) => format!("sync {}", entered_pin),
+        None => "sync".to_string(),
+    };
+
+    device.converse(&line, REQUEST, |nonce| format!("{} {}", nonce, host_millis()))
+}
+
+fn host_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64)
+}